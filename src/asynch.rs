@@ -0,0 +1,854 @@
+//! API asynchrone, pour les exécuteurs embarqués (SD en DMA...) que les
+//! lectures bloquantes de `BlockDevice` arrêteraient.
+//!
+//! Seul l'enchaînement des E/S est propre à ce module : la disposition du
+//! volume (`geometry.rs`), l'encodage des entrées de FAT, des entrées de
+//! répertoire et de l'indicateur « sale », le placement des nouvelles
+//! entrées (`write::plan_insert`), le découpage des écritures de fichier
+//! (`file::plan_write`), l'ordre de recherche des clusters libres et les
+//! compteurs du FSInfo sont ceux de l'API synchrone.
+//!
+//! Les chemins sont toujours résolus depuis la racine (pas de répertoire
+//! courant).
+
+use crate::boot::{BootSector, FsInfo};
+use crate::dir::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, DirEntry, RawSlot, find_entry_slots, normalize_path,
+    parse_dir_entries, parse_entry, slots_of_sector,
+};
+use crate::dirty::{dirty_on_disk, fat1_with_state, set_bpb_state};
+use crate::fat::{
+    alloc_scan_order, chain_links, contiguous_runs, decode_fat_sector, fat_raw_at, is_eoc,
+    next_free_after, put_fat_entry,
+};
+use crate::file::{is_writable, plan_write, resize_entry};
+use crate::geometry::{Geometry, layout_of};
+use crate::time::DateTime;
+use crate::write::{
+    build_short_entry, dir_skeleton, fill_cluster_sector, is_empty_dir, parent_ref, plan_insert,
+    renames_in_place, set_first_cluster, split_path,
+};
+use crate::{BlockDevice, DEFAULT_BACKUP_BOOT_SECTOR, Error, MountOptions};
+use alloc::{vec, vec::Vec};
+use core::cmp;
+
+/// Équivalent asynchrone de `BlockDevice`.
+// Pas de borne `Send` sur les futures : les exécuteurs visés tournent sur
+// un seul cœur.
+#[allow(async_fn_in_trait)]
+pub trait AsyncBlockDevice {
+    async fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error>;
+    async fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error>;
+
+    /// Rend durables les écritures déjà acceptées.
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Indique que `count` secteurs à partir de `lba` ne contiennent plus
+    /// rien d'utile (voir `BlockDevice::discard`).
+    async fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        let _ = (lba, count);
+        Ok(())
+    }
+}
+
+/// Utilise un `BlockDevice` (bloquant) là où un `AsyncBlockDevice` est
+/// attendu : chaque accès se termine dès le premier `poll`.
+pub struct Blocking<D: BlockDevice>(pub D);
+
+impl<D: BlockDevice> AsyncBlockDevice for Blocking<D> {
+    async fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        self.0.read_sector(lba, buf)
+    }
+
+    async fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        self.0.write_sector(lba, buf)
+    }
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.0.flush()
+    }
+
+    async fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        self.0.discard(lba, count)
+    }
+}
+
+/// Volume FAT32 monté sur un périphérique asynchrone.
+pub struct AsyncFat32<D: AsyncBlockDevice> {
+    pub device: D,
+    pub boot: BootSector,
+    pub fat_start_lba: u32,
+    pub data_start_lba: u32,
+    /// Vrai si le volume a été monté depuis le boot sector de secours
+    pub boot_from_backup: bool,
+    /// Clusters libérés signalés au périphérique (`MountOptions::discard`)
+    pub discard: bool,
+    /// Le volume était marqué sale au montage (voir `dirty.rs`)
+    pub was_dirty: bool,
+    /// Le volume a été marqué sale par ce montage
+    pub dirty: bool,
+}
+
+impl<D: AsyncBlockDevice> AsyncFat32<D> {
+    /// Monte le volume avec les options par défaut.
+    pub async fn new(device: D) -> Result<Self, Error> {
+        Self::mount(device, MountOptions::default()).await
+    }
+
+    /// Monte le volume (voir `Fat32::mount`).
+    pub async fn mount(mut device: D, options: MountOptions) -> Result<Self, Error> {
+        let (boot, boot_from_backup) = match Self::read_boot(&mut device, 0).await {
+            Ok(boot) => (boot, false),
+            Err(_) if options.backup_boot_fallback => (
+                Self::read_boot(&mut device, DEFAULT_BACKUP_BOOT_SECTOR).await?,
+                true,
+            ),
+            Err(e) => return Err(e),
+        };
+        let (fat_start_lba, data_start_lba) = layout_of(&boot)?;
+
+        let mut fs = Self {
+            device,
+            boot,
+            fat_start_lba,
+            data_start_lba,
+            boot_from_backup,
+            discard: options.discard,
+            was_dirty: false,
+            dirty: false,
+        };
+        fs.was_dirty = fs.is_dirty_on_disk().await?;
+        Ok(fs)
+    }
+
+    async fn read_boot(device: &mut D, lba: u64) -> Result<BootSector, Error> {
+        let mut sector = [0u8; 512];
+        device.read_sector(lba, &mut sector).await?;
+        BootSector::parse(&sector)
+    }
+
+    fn geometry(&self) -> Geometry {
+        Geometry::new(&self.boot, self.fat_start_lba, self.data_start_lba)
+    }
+
+    pub fn bytes_per_sector(&self) -> u32 {
+        self.boot.bytes_per_sector as u32
+    }
+
+    pub fn sectors_per_cluster(&self) -> u32 {
+        self.boot.sectors_per_cluster as u32
+    }
+
+    /// Nombre de clusters de la zone data (numérotés de 2 à count + 1).
+    pub fn cluster_count(&self) -> u32 {
+        self.geometry().cluster_count()
+    }
+
+    pub fn cluster_to_lba(&self, cluster: u32) -> u32 {
+        self.geometry().cluster_to_lba(cluster)
+    }
+
+    /// FAT lue par le driver (voir `Fat32::active_fat`).
+    pub fn active_fat(&self) -> u8 {
+        self.geometry().active_fat()
+    }
+
+    /// Rend durables les écritures faites jusqu'ici.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.device.flush().await
+    }
+
+    /// Lit une entrée de FAT (28 bits significatifs).
+    pub async fn read_fat_entry(&mut self, cluster: u32) -> Result<u32, Error> {
+        let geometry = self.geometry();
+        let (lba, offset) = geometry.fat_entry_pos(geometry.active_fat(), cluster);
+        let mut buf = vec![0u8; geometry.bytes_per_sector as usize];
+        self.device.read_sector(lba, &mut buf).await?;
+        Ok(fat_raw_at(&buf, offset) & 0x0FFF_FFFF)
+    }
+
+    /// Écrit une entrée de FAT (voir `Fat32::write_fat_entry`).
+    pub async fn write_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        self.mark_dirty().await?;
+        self.store_fat_entry(cluster, value).await
+    }
+
+    /// `write_fat_entry` sans marquer le volume sale.
+    async fn store_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        let geometry = self.geometry();
+        let mut buf = vec![0u8; geometry.bytes_per_sector as usize];
+        for copy in geometry.written_fats() {
+            let (lba, offset) = geometry.fat_entry_pos(copy, cluster);
+            self.device.read_sector(lba, &mut buf).await?;
+            put_fat_entry(&mut buf, offset, value);
+            self.device.write_sector(lba, &buf).await?;
+        }
+        Ok(())
+    }
+
+    /// Remplit `out` avec la chaîne de clusters à partir de `start`.
+    pub async fn cluster_chain(&mut self, start: u32, out: &mut Vec<u32>) -> Result<(), Error> {
+        let mut current = start;
+        let max_len = self.cluster_count() as usize + 2;
+        loop {
+            // Chaîne plus longue que le volume → boucle dans la FAT
+            if out.len() >= max_len {
+                return Err(Error::InvalidFs);
+            }
+            out.push(current);
+            let next = self.read_fat_entry(current).await?;
+            if is_eoc(next) {
+                break;
+            }
+            current = next;
+        }
+        Ok(())
+    }
+
+    /// Lit une copie complète de la FAT (voir `Fat32::read_fat_copy`).
+    pub async fn read_fat_copy(&mut self, copy: u8) -> Result<Vec<u32>, Error> {
+        let geometry = self.geometry();
+        let entries = (geometry.cluster_count() + 2) as usize;
+
+        let mut out = Vec::with_capacity(entries);
+        let mut buf = vec![0u8; geometry.bytes_per_sector as usize];
+        let mut lba = geometry.fat_copy_lba(copy);
+        while out.len() < entries {
+            self.device.read_sector(lba as u64, &mut buf).await?;
+            decode_fat_sector(&buf, &mut out, entries);
+            lba += 1;
+        }
+        Ok(out)
+    }
+
+    /// Alloue `count` clusters libres et les chaîne entre eux (voir
+    /// `Fat32::alloc_clusters`).
+    async fn alloc_clusters(&mut self, count: u32) -> Result<Vec<u32>, Error> {
+        let mut found = Vec::new();
+        if count == 0 {
+            return Ok(found);
+        }
+        let max = self.cluster_count() + 1;
+        let hint = self.read_fsinfo().await.ok().map(|info| info.next_free);
+        for cluster in alloc_scan_order(hint, max) {
+            if self.read_fat_entry(cluster).await? == 0 {
+                found.push(cluster);
+                if found.len() == count as usize {
+                    break;
+                }
+            }
+        }
+        if found.len() < count as usize {
+            return Err(Error::NoSpace);
+        }
+
+        for (c, next) in chain_links(&found) {
+            self.write_fat_entry(c, next).await?;
+        }
+        let next_free = next_free_after(found[found.len() - 1], max);
+        self.fsinfo_update(-(count as i64), Some(next_free)).await?;
+        Ok(found)
+    }
+
+    /// Libère toute une chaîne à partir de `start` (voir `Fat32::free_chain`).
+    async fn free_chain(&mut self, start: u32) -> Result<u32, Error> {
+        if start < 2 {
+            return Ok(0);
+        }
+        let mut chain = Vec::new();
+        self.cluster_chain(start, &mut chain).await?;
+        for &c in &chain {
+            self.write_fat_entry(c, 0).await?;
+        }
+        self.fsinfo_update(chain.len() as i64, None).await?;
+
+        if self.discard {
+            let spc = self.sectors_per_cluster() as u64;
+            for (first, run) in contiguous_runs(&chain) {
+                let lba = self.cluster_to_lba(first) as u64;
+                // Simple indication : un échec ne remet pas la libération en cause
+                let _ = self.device.discard(lba, run as u64 * spc).await;
+            }
+        }
+        Ok(chain.len() as u32)
+    }
+
+    /// Lit un cluster entier dans `buf`.
+    async fn read_cluster(&mut self, cluster: u32, buf: &mut [u8]) -> Result<(), Error> {
+        let geometry = self.geometry();
+        let sectors = buf.chunks_mut(geometry.bytes_per_sector as usize);
+        for (lba, sector) in geometry.cluster_sectors(cluster).zip(sectors) {
+            self.device.read_sector(lba as u64, sector).await?;
+        }
+        Ok(())
+    }
+
+    /// Écrit `data` au début d'un cluster (le reste est mis à zéro).
+    async fn write_cluster_data(&mut self, cluster: u32, data: &[u8]) -> Result<(), Error> {
+        self.mark_dirty().await?;
+        let geometry = self.geometry();
+        let mut sector = vec![0u8; geometry.bytes_per_sector as usize];
+        for (i, lba) in geometry.cluster_sectors(cluster).enumerate() {
+            fill_cluster_sector(data, i, &mut sector);
+            self.device.write_sector(lba as u64, &sector).await?;
+        }
+        Ok(())
+    }
+
+    /// Lit toutes les entrées d'un répertoire à partir de son premier cluster.
+    pub async fn read_dir_cluster(&mut self, first_cluster: u32) -> Result<Vec<DirEntry>, Error> {
+        let mut chain = Vec::new();
+        self.cluster_chain(first_cluster, &mut chain).await?;
+        let cluster_bytes = self.geometry().cluster_bytes();
+        let mut buf = vec![0u8; chain.len() * cluster_bytes];
+        for (&cluster, chunk) in chain.iter().zip(buf.chunks_mut(cluster_bytes)) {
+            self.read_cluster(cluster, chunk).await?;
+        }
+        Ok(parse_dir_entries(&buf))
+    }
+
+    /// Lit toutes les entrées brutes des clusters d'une chaîne.
+    async fn read_slots(&mut self, chain: &[u32]) -> Result<Vec<RawSlot>, Error> {
+        let geometry = self.geometry();
+        let mut slots = Vec::new();
+        let mut buf = vec![0u8; geometry.bytes_per_sector as usize];
+        for &cluster in chain {
+            for lba in geometry.cluster_sectors(cluster) {
+                self.device.read_sector(lba as u64, &mut buf).await?;
+                slots_of_sector(lba, &buf, &mut slots);
+            }
+        }
+        Ok(slots)
+    }
+
+    async fn write_slot(&mut self, slot: &RawSlot) -> Result<(), Error> {
+        self.mark_dirty().await?;
+        let mut buf = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(slot.lba as u64, &mut buf).await?;
+        slot.store_in(&mut buf);
+        self.device.write_sector(slot.lba as u64, &buf).await
+    }
+
+    /// Retrouve les entrées brutes (LFN puis 8.3) de `name` dans un
+    /// répertoire.
+    async fn find_slots(&mut self, dir_cluster: u32, name: &str) -> Result<Vec<RawSlot>, Error> {
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain).await?;
+        let slots = self.read_slots(&chain).await?;
+        let range = find_entry_slots(&slots, name).ok_or(Error::NotFound)?;
+        Ok(slots[range].to_vec())
+    }
+
+    /// Marque comme supprimées des entrées de répertoire.
+    async fn erase_slots(&mut self, slots: &mut [RawSlot]) -> Result<(), Error> {
+        for slot in slots.iter_mut() {
+            slot.bytes[0] = 0xE5;
+            self.write_slot(slot).await?;
+        }
+        Ok(())
+    }
+
+    /// Premier cluster désigné par une entrée (`..` vers la racine est codé 0).
+    pub fn dir_cluster_of(&self, entry: &DirEntry) -> u32 {
+        if entry.is_dir && entry.first_cluster == 0 {
+            self.boot.root_cluster
+        } else {
+            entry.first_cluster
+        }
+    }
+
+    /// Résout un chemin depuis la racine (`.` et `..` sont résolus
+    /// lexicalement, comme `normalize_path`).
+    pub async fn resolve_path(&mut self, path: &str) -> Result<DirEntry, Error> {
+        let path = normalize_path("/", path);
+        let mut cluster = self.boot.root_cluster;
+        let mut last = None;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            let entry = self
+                .read_dir_cluster(cluster)
+                .await?
+                .into_iter()
+                .find(|e| e.matches(part))
+                .ok_or(Error::NotFound)?;
+            cluster = self.dir_cluster_of(&entry);
+            last = Some(entry);
+        }
+        last.ok_or(Error::InvalidFs)
+    }
+
+    /// Premier cluster d'un répertoire (`/` → racine).
+    async fn dir_cluster(&mut self, path: &str) -> Result<u32, Error> {
+        if normalize_path("/", path) == "/" {
+            return Ok(self.boot.root_cluster);
+        }
+        let entry = self.resolve_path(path).await?;
+        if !entry.is_dir {
+            return Err(Error::NotDirectory);
+        }
+        Ok(self.dir_cluster_of(&entry))
+    }
+
+    /// Contenu d'un répertoire.
+    pub async fn list_dir(&mut self, path: &str) -> Result<Vec<DirEntry>, Error> {
+        let cluster = self.dir_cluster(path).await?;
+        self.read_dir_cluster(cluster).await
+    }
+
+    /// Ouvre un fichier à partir de son chemin, en lecture et en écriture.
+    pub async fn open_file(&mut self, path: &str) -> Result<AsyncFile<'_, D>, Error> {
+        let path = normalize_path("/", path);
+        let (parent, name) = split_path(&path)?;
+        let dir = self.dir_cluster(parent).await?;
+        let slot = self
+            .find_slots(dir, name)
+            .await?
+            .pop()
+            .ok_or(Error::InvalidFs)?;
+        let entry = parse_entry(&slot.bytes).ok_or(Error::InvalidFs)?;
+        if entry.is_dir {
+            return Err(Error::IsDirectory);
+        }
+        let mut chain = Vec::new();
+        if entry.first_cluster != 0 {
+            self.cluster_chain(entry.first_cluster, &mut chain).await?;
+        }
+        Ok(AsyncFile {
+            fs: self,
+            chain,
+            size: entry.size,
+            cursor: 0,
+            entry: Some(slot),
+            unflushed: false,
+        })
+    }
+
+    /// Lit entièrement un fichier en mémoire.
+    pub async fn read_file(&mut self, path: &str) -> Result<Vec<u8>, Error> {
+        self.open_file(path).await?.read_to_end().await
+    }
+
+    /// Lit le secteur FSInfo.
+    pub async fn read_fsinfo(&mut self) -> Result<FsInfo, Error> {
+        let mut buf = vec![0u8; self.bytes_per_sector() as usize];
+        self.device
+            .read_sector(self.boot.fs_info_sector as u64, &mut buf)
+            .await?;
+        FsInfo::parse(&buf)
+    }
+
+    /// Met à jour le secteur FSInfo (voir `Fat32::write_fsinfo`).
+    async fn write_fsinfo(&mut self, info: &FsInfo) -> Result<(), Error> {
+        self.mark_dirty().await?;
+        let lba = self.boot.fs_info_sector as u64;
+        let mut buf = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(lba, &mut buf).await?;
+        info.write_to(&mut buf);
+        self.device.write_sector(lba, &buf).await
+    }
+
+    /// Ajuste les compteurs du FSInfo (voir `Fat32::fsinfo_update`).
+    async fn fsinfo_update(&mut self, delta: i64, next_free: Option<u32>) -> Result<(), Error> {
+        let Ok(mut info) = self.read_fsinfo().await else {
+            return Ok(());
+        };
+        info.adjust(delta, next_free);
+        self.write_fsinfo(&info).await
+    }
+
+    /// Ajoute une entrée dans un répertoire, en l'agrandissant au besoin
+    /// (voir `Fat32::insert_entry`).
+    async fn insert_entry(
+        &mut self,
+        dir_cluster: u32,
+        name: &str,
+        template: [u8; 32],
+    ) -> Result<DirEntry, Error> {
+        if self
+            .read_dir_cluster(dir_cluster)
+            .await?
+            .iter()
+            .any(|e| e.matches(name))
+        {
+            return Err(Error::AlreadyExists);
+        }
+
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain).await?;
+        let mut slots = self.read_slots(&chain).await?;
+
+        let per_cluster = self.geometry().cluster_bytes() / 32;
+        let plan = plan_insert(&slots, name, template, per_cluster)?;
+        if plan.grow > 0 {
+            let added = self.alloc_clusters(plan.grow).await?;
+            for &c in &added {
+                self.write_cluster_data(c, &[]).await?;
+            }
+            self.write_fat_entry(chain[chain.len() - 1], added[0])
+                .await?;
+            slots.extend(self.read_slots(&added).await?);
+        }
+
+        for (slot, bytes) in slots[plan.start..].iter_mut().zip(&plan.new_slots) {
+            slot.bytes = *bytes;
+            self.write_slot(slot).await?;
+        }
+        plan.entry(name)
+    }
+
+    /// Crée un fichier contenant `data` (voir `Fat32::create_file`).
+    pub async fn create_file(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        time: DateTime,
+    ) -> Result<DirEntry, Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent).await?;
+        if self
+            .read_dir_cluster(dir)
+            .await?
+            .iter()
+            .any(|e| e.matches(name))
+        {
+            return Err(Error::AlreadyExists);
+        }
+        let size = u32::try_from(data.len()).map_err(|_| Error::NoSpace)?;
+
+        let cluster_bytes = self.geometry().cluster_bytes();
+        let clusters = self
+            .alloc_clusters(data.len().div_ceil(cluster_bytes) as u32)
+            .await?;
+        for (&c, chunk) in clusters.iter().zip(data.chunks(cluster_bytes)) {
+            self.write_cluster_data(c, chunk).await?;
+        }
+
+        let first = clusters.first().copied().unwrap_or(0);
+        let template = build_short_entry(&[b' '; 11], ATTR_ARCHIVE, first, size, time);
+        match self.insert_entry(dir, name, template).await {
+            Ok(entry) => {
+                self.flush().await?;
+                Ok(entry)
+            }
+            Err(e) => {
+                self.free_chain(first).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Crée un répertoire vide (voir `Fat32::create_dir`).
+    pub async fn create_dir(&mut self, path: &str, time: DateTime) -> Result<DirEntry, Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent).await?;
+        if self
+            .read_dir_cluster(dir)
+            .await?
+            .iter()
+            .any(|e| e.matches(name))
+        {
+            return Err(Error::AlreadyExists);
+        }
+
+        let cluster = self.alloc_clusters(1).await?[0];
+        let content = dir_skeleton(cluster, parent_ref(dir, self.boot.root_cluster), time);
+        self.write_cluster_data(cluster, &content).await?;
+
+        let template = build_short_entry(&[b' '; 11], ATTR_DIRECTORY, cluster, 0, time);
+        match self.insert_entry(dir, name, template).await {
+            Ok(entry) => {
+                self.flush().await?;
+                Ok(entry)
+            }
+            Err(e) => {
+                self.free_chain(cluster).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Supprime un fichier et libère ses clusters.
+    pub async fn remove_file(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent).await?;
+        let mut slots = self.find_slots(dir, name).await?;
+        let entry = parse_entry(&slots[slots.len() - 1].bytes).ok_or(Error::InvalidFs)?;
+        if entry.is_dir {
+            return Err(Error::IsDirectory);
+        }
+
+        self.erase_slots(&mut slots).await?;
+        self.free_chain(entry.first_cluster).await?;
+        self.flush().await
+    }
+
+    /// Supprime un répertoire vide (voir `Fat32::remove_dir`).
+    pub async fn remove_dir(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent).await?;
+        let mut slots = self.find_slots(dir, name).await?;
+        let entry = parse_entry(&slots[slots.len() - 1].bytes).ok_or(Error::InvalidFs)?;
+        if !entry.is_dir {
+            return Err(Error::NotDirectory);
+        }
+        if entry.first_cluster < 2 {
+            return Err(Error::InvalidFs);
+        }
+        if !is_empty_dir(&self.read_dir_cluster(entry.first_cluster).await?) {
+            return Err(Error::NotEmpty);
+        }
+
+        self.erase_slots(&mut slots).await?;
+        self.free_chain(entry.first_cluster).await?;
+        self.flush().await
+    }
+
+    /// Renomme ou déplace une entrée (voir `Fat32::rename`).
+    pub async fn rename(&mut self, from: &str, to: &str) -> Result<DirEntry, Error> {
+        let (src_parent, src_name) = split_path(from)?;
+        let (dst_parent, dst_name) = split_path(to)?;
+        let src_dir = self.dir_cluster(src_parent).await?;
+        let dst_dir = self.dir_cluster(dst_parent).await?;
+
+        let mut slots = self.find_slots(src_dir, src_name).await?;
+        let template = slots[slots.len() - 1].bytes;
+        let entry = parse_entry(&template).ok_or(Error::InvalidFs)?;
+
+        if entry.is_dir && src_dir != dst_dir {
+            // Interdit de déplacer un répertoire dans sa propre descendance
+            let mut cluster = dst_dir;
+            while cluster != self.boot.root_cluster {
+                if cluster == entry.first_cluster {
+                    return Err(Error::InvalidName);
+                }
+                let parent = self
+                    .read_dir_cluster(cluster)
+                    .await?
+                    .into_iter()
+                    .find(|e| e.name == "..")
+                    .ok_or(Error::InvalidFs)?;
+                cluster = self.dir_cluster_of(&parent);
+            }
+        }
+
+        let same_entry = renames_in_place((src_dir, src_name), (dst_dir, dst_name));
+        let original: Vec<[u8; 32]> = slots.iter().map(|s| s.bytes).collect();
+        if same_entry {
+            self.erase_slots(&mut slots).await?;
+        }
+        let moved = match self.insert_entry(dst_dir, dst_name, template).await {
+            Ok(e) => e,
+            Err(e) => {
+                if same_entry {
+                    for (slot, bytes) in slots.iter_mut().zip(original) {
+                        slot.bytes = bytes;
+                        self.write_slot(slot).await?;
+                    }
+                }
+                return Err(e);
+            }
+        };
+        if !same_entry {
+            self.erase_slots(&mut slots).await?;
+        }
+
+        if entry.is_dir && src_dir != dst_dir {
+            let mut dot_dot = self.find_slots(entry.first_cluster, "..").await?;
+            let slot = dot_dot.last_mut().ok_or(Error::InvalidFs)?;
+            set_first_cluster(&mut slot.bytes, parent_ref(dst_dir, self.boot.root_cluster));
+            self.write_slot(slot).await?;
+        }
+        self.flush().await?;
+        Ok(moved)
+    }
+
+    /// Lit l'indicateur « sale » sur le disque (voir `Fat32::is_dirty_on_disk`).
+    pub async fn is_dirty_on_disk(&mut self) -> Result<bool, Error> {
+        let fat1 = self.read_fat_entry(1).await?;
+        if self.boot_from_backup {
+            return Ok(dirty_on_disk(fat1, None));
+        }
+        let mut boot = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(0, &mut boot).await?;
+        Ok(dirty_on_disk(fat1, Some(&boot)))
+    }
+
+    /// Écrit l'indicateur d'arrêt propre (voir `dirty.rs`).
+    async fn write_dirty_marks(&mut self, dirty: bool) -> Result<(), Error> {
+        let fat1 = self.read_fat_entry(1).await?;
+        self.store_fat_entry(1, fat1_with_state(fat1, dirty))
+            .await?;
+
+        // Secteur 0 endommagé : on n'y écrit pas
+        if self.boot_from_backup {
+            return self.flush().await;
+        }
+        let mut boot = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(0, &mut boot).await?;
+        set_bpb_state(&mut boot, dirty);
+        self.device.write_sector(0, &boot).await?;
+        self.flush().await
+    }
+
+    async fn mark_dirty(&mut self) -> Result<(), Error> {
+        if self.dirty {
+            return Ok(());
+        }
+        if !self.was_dirty {
+            self.write_dirty_marks(true).await?;
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Démonte le volume et rend le périphérique (voir `Fat32::unmount`) :
+    /// FSInfo recalculé et indicateur « sale » effacé si le volume a été
+    /// modifié.
+    pub async fn unmount(mut self) -> Result<D, Error> {
+        if self.dirty {
+            let fat = self.read_fat_copy(self.active_fat()).await?;
+            let info = FsInfo::from_fat(&fat);
+            // Sans FSInfo lisible, rien à mettre à jour
+            if self.read_fsinfo().await.is_ok() {
+                self.write_fsinfo(&info).await?;
+            }
+            if !self.was_dirty {
+                self.write_dirty_marks(false).await?;
+            }
+        }
+        self.flush().await?;
+        Ok(self.device)
+    }
+}
+
+/// Fichier ouvert sur un `AsyncFat32`.
+///
+/// Un `Drop` ne peut pas attendre : les écritures ne sont rendues
+/// durables que par `flush` ou `close` (ou la prochaine opération du
+/// volume qui vide le périphérique).
+pub struct AsyncFile<'fs, D: AsyncBlockDevice> {
+    fs: &'fs mut AsyncFat32<D>,
+    chain: Vec<u32>,
+    size: u32,
+    cursor: u64,
+    /// Entrée 8.3 du fichier (taille, premier cluster)
+    entry: Option<RawSlot>,
+    /// Écritures pas encore rendues durables par `flush`
+    unflushed: bool,
+}
+
+impl<D: AsyncBlockDevice> AsyncFile<'_, D> {
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Position courante.
+    pub fn position(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Déplace la position courante (voir `File::set_position`).
+    pub fn set_position(&mut self, pos: u64) {
+        self.cursor = pos;
+    }
+
+    /// Lit jusqu'à buf.len() octets à partir de la position courante.
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let cluster_bytes = self.fs.geometry().cluster_bytes();
+        let remaining = (self.size as u64).saturating_sub(self.cursor) as usize;
+        let to_read = cmp::min(buf.len(), remaining);
+        let mut cluster_buf = vec![0u8; cluster_bytes];
+
+        let mut written = 0;
+        while written < to_read {
+            let pos = self.cursor as usize + written;
+            let Some(&cluster) = self.chain.get(pos / cluster_bytes) else {
+                break;
+            };
+            self.fs.read_cluster(cluster, &mut cluster_buf).await?;
+            let offset = pos % cluster_bytes;
+            let n = cmp::min(cluster_bytes - offset, to_read - written);
+            buf[written..written + n].copy_from_slice(&cluster_buf[offset..offset + n]);
+            written += n;
+        }
+
+        self.cursor += written as u64;
+        Ok(written)
+    }
+
+    /// Écrit `buf` à la position courante (voir `File::write`).
+    pub async fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if !is_writable(self.entry.as_ref()) {
+            return Err(Error::ReadOnly);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let geometry = self.fs.geometry();
+        let bytes_per_sector = geometry.bytes_per_sector as usize;
+        let cluster_bytes = geometry.cluster_bytes();
+        let plan = plan_write(self.cursor, self.size, buf.len(), cluster_bytes)?;
+
+        if plan.clusters > self.chain.len() {
+            let added = self
+                .fs
+                .alloc_clusters((plan.clusters - self.chain.len()) as u32)
+                .await?;
+            if let Some(&last) = self.chain.last() {
+                self.fs.write_fat_entry(last, added[0]).await?;
+            }
+            self.chain.extend(added);
+        }
+
+        self.fs.mark_dirty().await?;
+        self.unflushed = true;
+        let mut sector = vec![0u8; bytes_per_sector];
+        for span in plan.spans(bytes_per_sector, cluster_bytes) {
+            let lba = (geometry.cluster_to_lba(self.chain[span.index]) + span.sector) as u64;
+            // Secteur partiel : lecture-modification-écriture
+            if span.len < bytes_per_sector {
+                self.fs.device.read_sector(lba, &mut sector).await?;
+            }
+            plan.fill(&span, &mut sector, buf);
+            self.fs.device.write_sector(lba, &sector).await?;
+        }
+        self.cursor = plan.end as u64;
+
+        if let Some(slot) = self.entry.as_mut().filter(|_| plan.end > self.size) {
+            resize_entry(&mut slot.bytes, self.chain[0], plan.end);
+            self.fs.write_slot(slot).await?;
+            self.size = plan.end;
+        }
+        Ok(buf.len())
+    }
+
+    /// Rend durables les écritures faites sur le fichier.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.fs.flush().await?;
+        self.unflushed = false;
+        Ok(())
+    }
+
+    /// Ferme le fichier en rendant ses écritures durables.
+    pub async fn close(mut self) -> Result<(), Error> {
+        if self.unflushed {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        let mut tmp = [0u8; 1024];
+        loop {
+            let n = self.read(&mut tmp).await?;
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&tmp[..n]);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,206 @@
+//! Simulation (`--dry-run`) : l'opération s'exécute sur un
+//! `OverlayDevice` posé sur le volume, puis son delta est comparé au disque
+//! pour décrire les allocations et les modifications de répertoires
+//! prévues.
+
+use crate::CmdResult;
+use fat32_rust::dir::RawSlot;
+use fat32_rust::overlay::OverlayDevice;
+use fat32_rust::{BlockDevice, Fat32, MountOptions};
+use std::collections::BTreeSet;
+
+/// Exécute `op` sur une vue simulée du volume et affiche les changements
+/// qu'il aurait écrits.
+pub fn simulate<D, F>(fs: &mut Fat32<D>, op: F) -> CmdResult
+where
+    D: BlockDevice,
+    F: FnOnce(&mut Fat32<OverlayDevice<&mut D>>) -> CmdResult,
+{
+    let options = MountOptions {
+        backup_boot_fallback: fs.boot_from_backup,
+        discard: false,
+    };
+    let cwd = fs.cwd_cluster;
+    let fat_lba = fs.fat_copy_lba(fs.active_fat());
+    let sectors_per_fat = fs.boot.sectors_per_fat;
+    let fs_info_lba = fs.boot.fs_info_sector as u64;
+    // Répertoires avant l'opération (ceux supprimés par `rm -r` compris)
+    let mut dir_clusters = directory_clusters(fs);
+
+    let sector_size = fs.bytes_per_sector() as usize;
+    let dev = OverlayDevice::new(&mut fs.device, sector_size);
+    let mut dry =
+        Fat32::mount(dev, options).map_err(|e| crate::CliError::Fs(e, "volume".into()))?;
+    dry.cwd_cluster = cwd;
+    // L'indicateur « sale » ne fait pas partie de l'opération simulée
+    dry.dirty = true;
+    op(&mut dry)?;
+
+    // ... et après (pour décoder les secteurs des répertoires créés)
+    dir_clusters.extend(directory_clusters(&mut dry));
+    let data_lba = dry.data_start_lba as u64;
+    let spc = dry.sectors_per_cluster() as u64;
+    let written = dry
+        .device
+        .export()
+        .map_err(|e| crate::CliError::Fs(e, "dry run".into()))?;
+    drop(dry);
+
+    let mut allocated = Vec::new();
+    let mut freed = Vec::new();
+    let mut relinked = Vec::new();
+    let mut dir_edits = Vec::new();
+    let mut data_sectors = 0;
+    let mut fs_info = false;
+
+    for (lba, new) in &written {
+        let lba = *lba;
+        let mut old = vec![0u8; new.len()];
+        fs.device
+            .read_sector(lba, &mut old)
+            .map_err(|e| crate::CliError::Fs(e, format!("sector {lba}")))?;
+        if &old == new {
+            continue;
+        }
+
+        let fat_range = fat_lba as u64..fat_lba as u64 + sectors_per_fat as u64;
+        if fat_range.contains(&lba) {
+            let per_sector = (new.len() / 4) as u64;
+            for i in 0..new.len() / 4 {
+                let read = |b: &[u8]| {
+                    u32::from_le_bytes([b[i * 4], b[i * 4 + 1], b[i * 4 + 2], b[i * 4 + 3]])
+                        & 0x0FFF_FFFF
+                };
+                let (before, after) = (read(&old), read(new));
+                let cluster = ((lba - fat_range.start) * per_sector + i as u64) as u32;
+                match (before, after) {
+                    (b, a) if b == a => {}
+                    (0, _) => allocated.push(cluster),
+                    (_, 0) => freed.push(cluster),
+                    (b, a) => relinked.push((cluster, b, a)),
+                }
+            }
+        } else if lba == fs_info_lba {
+            fs_info = true;
+        } else if lba >= data_lba && dir_clusters.contains(&(((lba - data_lba) / spc) as u32 + 2)) {
+            let cluster = ((lba - data_lba) / spc) as u32 + 2;
+            dir_edits.extend(describe_dir_sector(cluster, &old, new));
+        } else if lba >= data_lba {
+            data_sectors += 1;
+        }
+    }
+
+    println!("dry run: nothing written");
+    if !allocated.is_empty() {
+        println!(
+            "  allocate {} cluster(s): {}",
+            allocated.len(),
+            ranges(&allocated)
+        );
+    }
+    if !freed.is_empty() {
+        println!("  free {} cluster(s): {}", freed.len(), ranges(&freed));
+    }
+    for (cluster, before, after) in relinked {
+        println!("  relink cluster {cluster}: {before:#x} -> {after:#x}");
+    }
+    for edit in dir_edits {
+        println!("  {edit}");
+    }
+    if data_sectors > 0 {
+        println!("  write {data_sectors} data sector(s)");
+    }
+    if fs_info {
+        println!("  update FSInfo");
+    }
+    Ok(())
+}
+
+/// Premiers clusters et chaînes de tous les répertoires du volume.
+fn directory_clusters<D: BlockDevice>(fs: &mut Fat32<D>) -> BTreeSet<u32> {
+    let mut starts = vec![fs.boot.root_cluster];
+    if let Ok(walk) = fs.walk("/") {
+        starts.extend(
+            walk.filter_map(Result::ok)
+                .filter(|(_, e)| e.is_dir && e.first_cluster >= 2)
+                .map(|(_, e)| e.first_cluster),
+        );
+    }
+
+    let mut clusters = BTreeSet::new();
+    for start in starts {
+        let mut chain = Vec::new();
+        if fs.cluster_chain(start, &mut chain).is_ok() {
+            clusters.extend(chain);
+        }
+    }
+    clusters
+}
+
+fn describe_dir_sector(cluster: u32, old: &[u8], new: &[u8]) -> Vec<String> {
+    let slot = |bytes: &[u8]| RawSlot {
+        lba: 0,
+        offset: 0,
+        bytes: bytes.try_into().unwrap(),
+    };
+    let short_name = |s: &RawSlot| s.entry().map_or_else(|| "?".into(), |e| e.short_name);
+
+    let mut edits = Vec::new();
+    let mut lfn_slots = 0;
+    for (before, after) in old.chunks(32).zip(new.chunks(32)) {
+        if before == after {
+            continue;
+        }
+        let (before, after) = (slot(before), slot(after));
+        let live = |s: &RawSlot| !s.is_end() && !s.is_deleted();
+        if (live(&before) && before.is_lfn()) || (live(&after) && after.is_lfn()) {
+            lfn_slots += 1;
+            continue;
+        }
+
+        let edit = match (live(&before), live(&after), after.entry()) {
+            (false, true, Some(e)) => format!(
+                "dir cluster {cluster}: add entry {} (cluster {}, {} bytes)",
+                e.short_name, e.first_cluster, e.size
+            ),
+            (true, false, _) => format!(
+                "dir cluster {cluster}: remove entry {}",
+                short_name(&before)
+            ),
+            (true, true, _) => {
+                format!("dir cluster {cluster}: update entry {}", short_name(&after))
+            }
+            _ => continue,
+        };
+        edits.push(edit);
+    }
+    if lfn_slots > 0 {
+        edits.push(format!(
+            "dir cluster {cluster}: {lfn_slots} long name slot(s) changed"
+        ));
+    }
+    edits
+}
+
+/// Liste compacte de clusters : "3-7, 12".
+fn ranges(clusters: &[u32]) -> String {
+    let mut sorted = clusters.to_vec();
+    sorted.sort_unstable();
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let start = sorted[i];
+        let mut end = start;
+        while i + 1 < sorted.len() && sorted[i + 1] == end + 1 {
+            i += 1;
+            end = sorted[i];
+        }
+        parts.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        });
+        i += 1;
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,150 @@
+//! Modifications du volume : cp, mkdir, rm, rmdir, mv, touch.
+//!
+//! Toutes acceptent `--dry-run` : l'opération est alors exécutée sur une
+//! vue simulée (voir `dryrun`) et seules les écritures prévues sont
+//! affichées.
+
+use crate::{CliError, CmdResult, Context, dryrun};
+use fat32_rust::time::DateTime;
+use fat32_rust::{BlockDevice, Error, Fat32};
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Côté d'une copie : fichier de l'hôte ou chemin dans l'image.
+enum Side<'a> {
+    Host(&'a str),
+    Image(&'a str),
+}
+
+fn side(arg: &str) -> Side<'_> {
+    match arg.strip_prefix("host:") {
+        Some(path) => Side::Host(path),
+        None => Side::Image(arg.strip_prefix("image:").unwrap_or(arg)),
+    }
+}
+
+fn basename(path: &str) -> &str {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+}
+
+/// `dst/nom` si `dst` est un répertoire existant de l'image, sinon `dst`.
+fn image_target<D: BlockDevice>(fs: &mut Fat32<D>, dst: &str, src: &str) -> String {
+    if dst == "/" {
+        return format!("/{}", basename(src));
+    }
+    match fs.resolve_path(dst) {
+        Ok(e) if e.is_dir => format!("{}/{}", dst.trim_end_matches('/'), basename(src)),
+        _ => dst.into(),
+    }
+}
+
+pub fn cmd_edit<D: BlockDevice>(fs: &mut Fat32<D>, cmd: &str, args: &[&str]) -> CmdResult {
+    let dry_run = args.contains(&"--dry-run");
+    let args: Vec<&str> = args.iter().copied().filter(|a| *a != "--dry-run").collect();
+    if dry_run {
+        dryrun::simulate(fs, |dry| apply(dry, cmd, &args, true))
+    } else {
+        apply(fs, cmd, &args, false)
+    }
+}
+
+fn apply<D: BlockDevice>(fs: &mut Fat32<D>, cmd: &str, args: &[&str], dry_run: bool) -> CmdResult {
+    let now = DateTime::from_system_time(SystemTime::now());
+    match (cmd, args) {
+        ("cp", [src, dst]) => cmd_cp(fs, side(src), side(dst), dry_run),
+        ("mkdir", ["-p", path]) => fs.create_dir_all(path, now).context(path),
+        ("mkdir", [path]) => fs.create_dir(path, now).map(|_| ()).context(path),
+        ("rm", ["-r", path]) => match fs.resolve_path(path).context(path)? {
+            e if e.is_dir => fs.remove_dir_all(path).context(path),
+            _ => fs.remove_file(path).context(path),
+        },
+        ("rm", [path]) => match fs.resolve_path(path).context(path)? {
+            e if e.is_dir => Err(CliError::Usage(format!("{path} is a directory (use -r)"))),
+            _ => fs.remove_file(path).context(path),
+        },
+        ("rmdir", [path]) => fs.remove_dir(path).context(path),
+        ("mv", [src, dst]) => {
+            let target = image_target(fs, dst, src);
+            fs.rename(src, &target).map(|_| ()).context(src)
+        }
+        ("touch", [path]) => match fs.set_modified(path, now) {
+            Err(Error::NotFound) => fs.create_file(path, &[], now).map(|_| ()).context(path),
+            result => result.context(path),
+        },
+        ("cp", _) => Err(CliError::Usage("needs <src> <dst>".into())),
+        ("mv", _) => Err(CliError::Usage("needs <src> <dst>".into())),
+        ("mkdir", _) => Err(CliError::Usage("needs [-p] <path>".into())),
+        ("rm", _) => Err(CliError::Usage("needs [-r] <path>".into())),
+        _ => Err(CliError::Usage("needs a path".into())),
+    }
+}
+
+fn cmd_cp<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    src: Side<'_>,
+    dst: Side<'_>,
+    dry_run: bool,
+) -> CmdResult {
+    match (src, dst) {
+        (Side::Host(src), Side::Image(dst)) => {
+            let meta = std::fs::metadata(src).map_err(|_| CliError::Fs(Error::Io, src.into()))?;
+            if meta.is_dir() {
+                return Err(CliError::Usage(format!(
+                    "{src} is a directory (use import)"
+                )));
+            }
+            let data = std::fs::read(src).map_err(|_| CliError::Fs(Error::Io, src.into()))?;
+            let time = meta
+                .modified()
+                .map(DateTime::from_system_time)
+                .unwrap_or_else(|_| DateTime::from_system_time(SystemTime::now()));
+            let target = image_target(fs, dst, src);
+            fs.create_file(&target, &data, time).context(&target)?;
+        }
+        (Side::Image(src), Side::Host(dst)) => {
+            let entry = fs.resolve_path(src).context(src)?;
+            if entry.is_dir {
+                return Err(CliError::Usage(format!(
+                    "{src} is a directory (use extract)"
+                )));
+            }
+            let data = fs.read_file(src).context(src)?;
+            let mut target = Path::new(dst).to_path_buf();
+            if target.is_dir() {
+                target.push(&entry.name);
+            }
+            if dry_run {
+                println!("would write {} bytes to {}", data.len(), target.display());
+                return Ok(());
+            }
+            let io_err = |_| CliError::Fs(Error::Io, target.display().to_string());
+            std::fs::write(&target, &data).map_err(io_err)?;
+            if let Some(time) = entry.modified {
+                std::fs::File::options()
+                    .write(true)
+                    .open(&target)
+                    .and_then(|f| f.set_modified(time.to_system_time()))
+                    .map_err(io_err)?;
+            }
+        }
+        (Side::Image(src), Side::Image(dst)) => {
+            let entry = fs.resolve_path(src).context(src)?;
+            if entry.is_dir {
+                return Err(CliError::Usage(format!("{src} is a directory")));
+            }
+            let data = fs.read_file(src).context(src)?;
+            let time = entry
+                .modified
+                .unwrap_or_else(|| DateTime::from_system_time(SystemTime::now()));
+            let target = image_target(fs, dst, src);
+            fs.create_file(&target, &data, time).context(&target)?;
+        }
+        (Side::Host(_), Side::Host(_)) => {
+            return Err(CliError::Usage("one side must be in the image".into()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Construction d'une image complète : build-image.
+
+use crate::{CliError, CmdResult, Context};
+use fat32_rust::format::FormatOptions;
+use fat32_rust::host::{ImportOptions, build_image};
+use fat32_rust::time::DateTime;
+
+fn parse_size(s: &str) -> Option<u64> {
+    let (digits, unit) = match s.char_indices().last()? {
+        (i, 'K' | 'k') => (&s[..i], 1 << 10),
+        (i, 'M' | 'm') => (&s[..i], 1 << 20),
+        (i, 'G' | 'g') => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+fn parse_time(s: &str) -> Option<DateTime> {
+    if let Ok(secs) = s.parse::<u64>() {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        return Some(DateTime::from_system_time(time));
+    }
+
+    let (date, clock) = s.split_once('T').unwrap_or((s, "00:00:00"));
+    let date: Vec<&str> = date.split('-').collect();
+    let clock: Vec<&str> = clock.split(':').collect();
+    if date.len() != 3 || clock.len() != 3 {
+        return None;
+    }
+    let dt = DateTime {
+        year: date[0].parse().ok()?,
+        month: date[1].parse().ok()?,
+        day: date[2].parse().ok()?,
+        hour: clock[0].parse().ok()?,
+        minute: clock[1].parse().ok()?,
+        second: clock[2].parse().ok()?,
+    };
+    let (d, t) = dt.to_dos();
+    DateTime::from_dos(d, t).map(|_| dt)
+}
+
+pub fn cmd_build_image(args: &[String]) -> CmdResult {
+    let mut size = None;
+    let mut format_opts = FormatOptions::default();
+    let mut import_opts = ImportOptions::default();
+    let mut volume_id = None;
+    let mut positional = Vec::new();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| {
+            it.next()
+                .cloned()
+                .ok_or_else(|| CliError::Usage(format!("{name} needs a value")))
+        };
+        let invalid = |name: &str| CliError::Usage(format!("invalid {name}"));
+        match arg.as_str() {
+            "--size" => {
+                size = Some(parse_size(&value("--size")?).ok_or_else(|| invalid("--size"))?)
+            }
+            "--label" => format_opts.label = Some(value("--label")?),
+            "--volume-id" => {
+                let v = value("--volume-id")?;
+                let id = u32::from_str_radix(&v, 16).map_err(|_| invalid("--volume-id (hex)"))?;
+                volume_id = Some(id);
+            }
+            "--time" => {
+                let time = parse_time(&value("--time")?).ok_or_else(|| invalid("--time"))?;
+                import_opts.fixed_time = Some(time);
+            }
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let (Some(size), [host_dir, image]) = (size, positional.as_slice()) else {
+        return Err(CliError::Usage(
+            "needs --size <N> <host_dir> <image>".into(),
+        ));
+    };
+    if size % 512 != 0 || size / 512 > u32::MAX as u64 {
+        return Err(CliError::Usage(
+            "size must be a multiple of 512 bytes, below 2 TiB".into(),
+        ));
+    }
+
+    // SOURCE_DATE_EPOCH (reproducible-builds.org) sert de date par défaut
+    if import_opts.fixed_time.is_none()
+        && let Ok(epoch) = std::env::var("SOURCE_DATE_EPOCH")
+    {
+        import_opts.fixed_time = parse_time(&epoch);
+    }
+    // Sans numéro imposé, un numéro dérivé de la date (comme mkfs.fat)
+    format_opts.volume_id = volume_id.unwrap_or_else(|| {
+        let now = import_opts
+            .fixed_time
+            .map(|t| t.to_system_time())
+            .unwrap_or_else(std::time::SystemTime::now);
+        now.duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or(0)
+    });
+
+    let total_sectors = (size / 512) as u32;
+    let plan = build_image(
+        image,
+        total_sectors,
+        std::path::Path::new(host_dir),
+        &format_opts,
+        &import_opts,
+    )
+    .context(host_dir)?;
+    println!(
+        "{image}: {} file(s), {} dir(s), {} bytes, {} cluster(s) left",
+        plan.files,
+        plan.dirs,
+        plan.bytes,
+        plan.clusters_free - plan.clusters_needed
+    );
+    Ok(())
+}
@@ -0,0 +1,517 @@
+//! Commandes de consultation : ls, stat, tree, walk, du, df, info.
+
+use crate::json::{self, Json};
+use crate::{CliError, CmdResult, Context, Output};
+use fat32_rust::dir::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM, DirEntry,
+};
+use fat32_rust::fat::fragment_count;
+use fat32_rust::time::DateTime;
+use fat32_rust::{BlockDevice, Fat32};
+
+pub fn format_time(time: Option<DateTime>) -> String {
+    match time {
+        Some(t) => format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            t.year, t.month, t.day, t.hour, t.minute, t.second
+        ),
+        None => "-".into(),
+    }
+}
+
+pub fn format_attr(attr: u8) -> String {
+    [
+        (ATTR_READ_ONLY, 'R'),
+        (ATTR_HIDDEN, 'H'),
+        (ATTR_SYSTEM, 'S'),
+        (ATTR_DIRECTORY, 'D'),
+        (ATTR_ARCHIVE, 'A'),
+    ]
+    .iter()
+    .map(|&(bit, c)| if attr & bit != 0 { c } else { '-' })
+    .collect()
+}
+
+pub fn cmd_ls<D: BlockDevice>(fs: &mut Fat32<D>, path: &str, out: Output) -> CmdResult {
+    let entries = match path {
+        "/" => fs.list_root().context(path)?,
+        "." => fs.list_cwd().context(path)?,
+        _ => {
+            let entry = fs.resolve_path(path).context(path)?;
+            if entry.is_dir {
+                let cluster = fs.dir_cluster_of(&entry);
+                fs.read_dir_cluster(cluster).context(path)?
+            } else {
+                vec![entry]
+            }
+        }
+    };
+
+    match out {
+        Output::Json => {
+            let items: Vec<Json> = entries.iter().map(|e| Json::Obj(json::entry(e))).collect();
+            println!("{}", Json::Arr(items));
+        }
+        Output::Text => {
+            for e in entries {
+                if e.is_dir {
+                    println!("<DIR>  {}", e.name);
+                } else {
+                    println!("       {}", e.name);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn cmd_stat<D: BlockDevice>(fs: &mut Fat32<D>, path: &str, out: Output) -> CmdResult {
+    let (first_cluster, entry) = if path == "/" {
+        (fs.boot.root_cluster, None)
+    } else {
+        let e = fs.resolve_path(path).context(path)?;
+        (fs.dir_cluster_of(&e), Some(e))
+    };
+
+    let mut chain = Vec::new();
+    if first_cluster >= 2 {
+        fs.cluster_chain(first_cluster, &mut chain).context(path)?;
+    }
+    let fragments = fragment_count(&chain);
+
+    if out == Output::Json {
+        let mut fields = vec![("path", path.into())];
+        match &entry {
+            Some(e) => fields.extend(json::entry(e)),
+            None => fields.extend([
+                ("is_dir", true.into()),
+                ("first_cluster", first_cluster.into()),
+            ]),
+        }
+        fields.push(("chain_length", (chain.len() as u64).into()));
+        fields.push(("fragments", fragments.into()));
+        fields.push(("clusters", chain.into()));
+        println!("{}", Json::Obj(fields));
+        return Ok(());
+    }
+
+    println!("Path:          {path}");
+    match &entry {
+        Some(e) => {
+            println!("Name:          {}", e.name);
+            println!("Short name:    {}", e.short_name);
+            println!(
+                "Type:          {}",
+                if e.is_dir { "directory" } else { "file" }
+            );
+            println!("Size:          {}", e.size);
+            println!("Attributes:    {} (0x{:02X})", format_attr(e.attr), e.attr);
+            println!("Created:       {}", format_time(e.created));
+            println!("Modified:      {}", format_time(e.modified));
+            println!("Accessed:      {}", format_time(e.accessed));
+        }
+        None => println!("Type:          directory (root)"),
+    }
+    println!("First cluster: {first_cluster}");
+    println!("Chain length:  {} cluster(s)", chain.len());
+    println!("Fragments:     {fragments}");
+    Ok(())
+}
+
+/// Préfixe des chemins renvoyés par `walk` pour `path`.
+fn walk_prefix(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "." => "",
+        p => p,
+    }
+}
+
+pub fn cmd_tree<D: BlockDevice>(fs: &mut Fat32<D>, path: &str) -> CmdResult {
+    let items: Vec<(String, DirEntry)> = fs.walk(path).and_then(|w| w.collect()).context(path)?;
+
+    let prefix = walk_prefix(path);
+    let depths: Vec<usize> = items
+        .iter()
+        .map(|(p, _)| {
+            let rel = p.strip_prefix(prefix).unwrap_or(p).trim_start_matches('/');
+            rel.matches('/').count()
+        })
+        .collect();
+
+    // Dernier enfant de son parent ? (parcours à rebours)
+    let mut last = vec![false; items.len()];
+    let mut seen: Vec<bool> = Vec::new();
+    for (i, &d) in depths.iter().enumerate().rev() {
+        seen.resize(d + 1, false);
+        last[i] = !seen[d];
+        seen[d] = true;
+    }
+
+    println!("{path}");
+    let mut open: Vec<bool> = Vec::new();
+    let (mut files, mut dirs) = (0, 0);
+    for (i, (_, entry)) in items.iter().enumerate() {
+        let d = depths[i];
+        open.truncate(d);
+        let indent: String = open
+            .iter()
+            .map(|&more| if more { "│   " } else { "    " })
+            .collect();
+        let branch = if last[i] { "└── " } else { "├── " };
+        println!("{indent}{branch}{}", entry.name);
+        open.push(!last[i]);
+
+        if entry.is_dir {
+            dirs += 1;
+        } else {
+            files += 1;
+        }
+    }
+    println!("\n{dirs} directories, {files} files");
+    Ok(())
+}
+
+pub fn cmd_walk<D: BlockDevice>(fs: &mut Fat32<D>, args: &[&str], out: Output) -> CmdResult {
+    let mut path = ".";
+    let mut max_depth = None;
+    let mut it = args.iter();
+    while let Some(&arg) = it.next() {
+        match arg {
+            "--max-depth" => match it.next().and_then(|v| v.parse().ok()) {
+                Some(depth) => max_depth = Some(depth),
+                None => return Err(CliError::Usage("invalid --max-depth".into())),
+            },
+            _ => path = arg,
+        }
+    }
+
+    let mut walk = fs.walk(path).context(path)?;
+    if let Some(depth) = max_depth {
+        walk = walk.max_depth(depth);
+    }
+    let items: Vec<(String, DirEntry)> = walk.collect::<Result<_, _>>().context(path)?;
+
+    match out {
+        Output::Json => {
+            let items: Vec<Json> = items
+                .iter()
+                .map(|(p, e)| {
+                    let mut fields = vec![("path", p.as_str().into())];
+                    fields.extend(json::entry(e));
+                    Json::Obj(fields)
+                })
+                .collect();
+            println!("{}", Json::Arr(items));
+        }
+        Output::Text => {
+            for (p, e) in items {
+                println!("{p}{}", if e.is_dir { "/" } else { "" });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn cmd_du<D: BlockDevice>(fs: &mut Fat32<D>, path: &str) -> CmdResult {
+    let cluster_bytes = (fs.bytes_per_sector() * fs.sectors_per_cluster()) as u64;
+    let items: Vec<(String, DirEntry)> = fs.walk(path).and_then(|w| w.collect()).context(path)?;
+
+    // Le répertoire de départ n'est pas renvoyé par `walk`
+    let start = match path {
+        "/" => fs.boot.root_cluster,
+        "." | "" => fs.cwd_cluster,
+        _ => {
+            let entry = fs.resolve_path(path).context(path)?;
+            fs.dir_cluster_of(&entry)
+        }
+    };
+    let mut chain = Vec::new();
+    fs.cluster_chain(start, &mut chain).context(path)?;
+
+    // Taille allouée cumulée par répertoire (lui compris)
+    let mut dirs: Vec<(String, u64)> = Vec::new();
+    let mut total = chain.len() as u64 * cluster_bytes;
+    for (item_path, entry) in &items {
+        let mut chain = Vec::new();
+        if entry.first_cluster >= 2 {
+            fs.cluster_chain(entry.first_cluster, &mut chain)
+                .context(item_path)?;
+        }
+        let allocated = chain.len() as u64 * cluster_bytes;
+        total += allocated;
+
+        if entry.is_dir {
+            dirs.push((item_path.clone(), 0));
+        }
+        for (dir, size) in dirs.iter_mut() {
+            if item_path == dir || item_path.starts_with(&format!("{dir}/")) {
+                *size += allocated;
+            }
+        }
+    }
+
+    for (dir, size) in &dirs {
+        println!("{size:>12}  {dir}");
+    }
+    println!("{total:>12}  {path}");
+    Ok(())
+}
+
+pub fn cmd_df<D: BlockDevice>(fs: &mut Fat32<D>, scan: bool, out: Output) -> CmdResult {
+    let total = fs.cluster_count();
+    let from_fsinfo = match fs.read_fsinfo() {
+        Ok(info) if !scan && info.free_count <= total => Some(info.free_count),
+        _ => None,
+    };
+    let (free, source) = match from_fsinfo {
+        Some(free) => (free, "FSInfo"),
+        None => (fs.free_cluster_count().context("FAT")?, "FAT scan"),
+    };
+    let cluster_bytes = (fs.bytes_per_sector() * fs.sectors_per_cluster()) as u64;
+
+    if out == Output::Json {
+        let fields = vec![
+            ("cluster_size", cluster_bytes.into()),
+            ("total_clusters", total.into()),
+            ("used_clusters", (total - free).into()),
+            ("free_clusters", free.into()),
+            ("source", source.into()),
+        ];
+        println!("{}", Json::Obj(fields));
+        return Ok(());
+    }
+
+    println!("Cluster size:   {cluster_bytes} bytes");
+    println!(
+        "Total clusters: {total} ({} bytes)",
+        total as u64 * cluster_bytes
+    );
+    println!(
+        "Used clusters:  {} ({} bytes)",
+        total - free,
+        (total - free) as u64 * cluster_bytes
+    );
+    println!(
+        "Free clusters:  {free} ({} bytes, from {source})",
+        free as u64 * cluster_bytes
+    );
+    Ok(())
+}
+
+pub fn cmd_info<D: BlockDevice>(fs: &mut Fat32<D>, out: Output) -> CmdResult {
+    let b = &fs.boot;
+    let text = |raw: &[u8]| String::from_utf8_lossy(raw).trim_end().to_string();
+    let hex = |v: u32, width: usize| format!("0x{v:0width$X}");
+    let mirroring = if b.mirroring_enabled() {
+        "enabled".to_string()
+    } else {
+        format!("disabled (active FAT {})", b.active_fat())
+    };
+
+    // (libellé, clé JSON, valeur affichée, valeur JSON)
+    let fields: Vec<(&str, &'static str, String, Json)> = vec![
+        (
+            "Jump",
+            "jump",
+            format!("{:02X} {:02X} {:02X}", b.jump[0], b.jump[1], b.jump[2]),
+            b.jump.to_vec().into(),
+        ),
+        (
+            "OEM name",
+            "oem_name",
+            text(&b.oem_name),
+            text(&b.oem_name).into(),
+        ),
+        (
+            "Bytes per sector",
+            "bytes_per_sector",
+            b.bytes_per_sector.to_string(),
+            b.bytes_per_sector.into(),
+        ),
+        (
+            "Sectors per cluster",
+            "sectors_per_cluster",
+            b.sectors_per_cluster.to_string(),
+            b.sectors_per_cluster.into(),
+        ),
+        (
+            "Reserved sectors",
+            "reserved_sectors",
+            b.reserved_sectors.to_string(),
+            b.reserved_sectors.into(),
+        ),
+        (
+            "Number of FATs",
+            "num_fats",
+            b.num_fats.to_string(),
+            b.num_fats.into(),
+        ),
+        (
+            "Root entry count",
+            "root_entry_count",
+            b.root_entry_count.to_string(),
+            b.root_entry_count.into(),
+        ),
+        (
+            "Total sectors",
+            "total_sectors",
+            b.total_sectors.to_string(),
+            b.total_sectors.into(),
+        ),
+        ("Media", "media", hex(b.media as u32, 2), b.media.into()),
+        (
+            "Sectors per FAT (16)",
+            "sectors_per_fat_16",
+            b.sectors_per_fat_16.to_string(),
+            b.sectors_per_fat_16.into(),
+        ),
+        (
+            "Sectors per track",
+            "sectors_per_track",
+            b.sectors_per_track.to_string(),
+            b.sectors_per_track.into(),
+        ),
+        (
+            "Number of heads",
+            "num_heads",
+            b.num_heads.to_string(),
+            b.num_heads.into(),
+        ),
+        (
+            "Hidden sectors",
+            "hidden_sectors",
+            b.hidden_sectors.to_string(),
+            b.hidden_sectors.into(),
+        ),
+        (
+            "Sectors per FAT",
+            "sectors_per_fat",
+            b.sectors_per_fat.to_string(),
+            b.sectors_per_fat.into(),
+        ),
+        (
+            "Ext flags",
+            "ext_flags",
+            hex(b.ext_flags as u32, 4),
+            b.ext_flags.into(),
+        ),
+        (
+            "FAT mirroring",
+            "fat_mirroring",
+            mirroring,
+            b.mirroring_enabled().into(),
+        ),
+        (
+            "Active FAT",
+            "active_fat",
+            b.active_fat().to_string(),
+            b.active_fat().into(),
+        ),
+        (
+            "FS version",
+            "fs_version",
+            format!("{}.{}", b.fs_version >> 8, b.fs_version & 0xFF),
+            b.fs_version.into(),
+        ),
+        (
+            "Root cluster",
+            "root_cluster",
+            b.root_cluster.to_string(),
+            b.root_cluster.into(),
+        ),
+        (
+            "FSInfo sector",
+            "fs_info_sector",
+            b.fs_info_sector.to_string(),
+            b.fs_info_sector.into(),
+        ),
+        (
+            "Backup boot sector",
+            "backup_boot_sector",
+            b.backup_boot_sector.to_string(),
+            b.backup_boot_sector.into(),
+        ),
+        (
+            "Drive number",
+            "drive_number",
+            hex(b.drive_number as u32, 2),
+            b.drive_number.into(),
+        ),
+        (
+            "Boot signature",
+            "boot_signature",
+            hex(b.boot_signature as u32, 2),
+            b.boot_signature.into(),
+        ),
+        (
+            "Volume ID",
+            "volume_id",
+            format!("{:08X}", b.volume_id),
+            b.volume_id.into(),
+        ),
+        (
+            "Volume label",
+            "volume_label",
+            text(&b.volume_label),
+            text(&b.volume_label).into(),
+        ),
+        (
+            "FS type string",
+            "fs_type",
+            text(&b.fs_type),
+            text(&b.fs_type).into(),
+        ),
+        (
+            "FAT type",
+            "fat_type",
+            format!("{:?}", b.fat_type()),
+            format!("{:?}", b.fat_type()).into(),
+        ),
+        (
+            "Cluster count",
+            "cluster_count",
+            b.cluster_count().to_string(),
+            b.cluster_count().into(),
+        ),
+        (
+            "FAT region",
+            "fat_start_sector",
+            format!("sector {}", fs.fat_start_lba),
+            fs.fat_start_lba.into(),
+        ),
+        (
+            "Data region",
+            "data_start_sector",
+            format!(
+                "sector {} (byte offset {})",
+                fs.data_start_lba,
+                fs.data_start_lba as u64 * b.bytes_per_sector as u64
+            ),
+            fs.data_start_lba.into(),
+        ),
+        (
+            "Boot sector used",
+            "boot_from_backup",
+            if fs.boot_from_backup {
+                "backup"
+            } else {
+                "primary"
+            }
+            .into(),
+            fs.boot_from_backup.into(),
+        ),
+    ];
+
+    match out {
+        Output::Json => {
+            let fields = fields.into_iter().map(|(_, key, _, v)| (key, v)).collect();
+            println!("{}", Json::Obj(fields));
+        }
+        Output::Text => {
+            for (name, _, value, _) in fields {
+                println!("{:<22}{value}", format!("{name}:"));
+            }
+        }
+    }
+    Ok(())
+}
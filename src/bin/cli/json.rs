@@ -0,0 +1,152 @@
+//! Sortie JSON minimale (sans dépendance externe).
+
+use fat32_rust::dir::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM, DirEntry,
+};
+use fat32_rust::time::DateTime;
+use std::fmt;
+
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(u64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(&'static str, Json)>),
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Num(n)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Self {
+        Json::Num(n as u64)
+    }
+}
+
+impl From<u16> for Json {
+    fn from(n: u16) -> Self {
+        Json::Num(n as u64)
+    }
+}
+
+impl From<u8> for Json {
+    fn from(n: u8) -> Self {
+        Json::Num(n as u64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::Str(s.into())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::Str(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(Json::Null)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::Arr(v.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Num(n) => write!(f, "{n}"),
+            Json::Str(s) => write_str(f, s),
+            Json::Arr(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")
+            }
+            Json::Obj(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_str(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Date au format ISO 8601 (sans fuseau : l'heure FAT est locale).
+pub fn iso_time(time: DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+pub fn attributes(attr: u8) -> Json {
+    Json::Obj(vec![
+        ("read_only", (attr & ATTR_READ_ONLY != 0).into()),
+        ("hidden", (attr & ATTR_HIDDEN != 0).into()),
+        ("system", (attr & ATTR_SYSTEM != 0).into()),
+        ("directory", (attr & ATTR_DIRECTORY != 0).into()),
+        ("archive", (attr & ATTR_ARCHIVE != 0).into()),
+    ])
+}
+
+/// Représentation d'une entrée de répertoire.
+pub fn entry(e: &DirEntry) -> Vec<(&'static str, Json)> {
+    vec![
+        ("name", e.name.as_str().into()),
+        ("short_name", e.short_name.as_str().into()),
+        ("is_dir", e.is_dir.into()),
+        ("size", e.size.into()),
+        ("attr", e.attr.into()),
+        ("attributes", attributes(e.attr)),
+        ("first_cluster", e.first_cluster.into()),
+        ("created", e.created.map(iso_time).into()),
+        ("modified", e.modified.map(iso_time).into()),
+        ("accessed", e.accessed.map(iso_time).into()),
+    ]
+}
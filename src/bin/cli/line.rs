@@ -0,0 +1,338 @@
+//! Édition de ligne minimale pour le shell (sans dépendance ni `unsafe`) :
+//! le terminal passe en mode brut via `stty` pour toute la session du
+//! shell (le retour à la ligne en sortie reste actif pour les commandes).
+//!
+//! Touches : flèches, Home/End, Suppr, Ctrl-A/E/U/K, Ctrl-C (annule la
+//! ligne), Ctrl-D (fin sur ligne vide), Tab (complétion), historique
+//! persistant.
+
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Nombre de lignes d'historique conservées.
+const HISTORY_MAX: usize = 1000;
+
+/// Mode brut du terminal, restauré à la destruction.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enter() -> Option<Self> {
+        let out = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()
+            .ok()?;
+        if !out.status.success() {
+            return None;
+        }
+        let saved = String::from_utf8(out.stdout).ok()?.trim().to_string();
+        let status = Command::new("stty")
+            .args(["raw", "-echo", "opost"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        status.success().then_some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .arg(&self.saved)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+/// Complétion : reçoit le texte avant le curseur, renvoie l'indice (en
+/// caractères) du début du mot à remplacer et les remplacements possibles.
+pub type Completer<'a> = dyn FnMut(&str) -> (usize, Vec<String>) + 'a;
+
+pub struct LineEditor {
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+    /// Mode brut, actif tant que l'éditeur existe (`None` hors terminal)
+    raw: Option<RawMode>,
+}
+
+impl LineEditor {
+    /// Charge l'historique depuis `history_path` s'il existe et passe le
+    /// terminal en mode brut jusqu'à la destruction de l'éditeur.
+    pub fn new(history_path: Option<PathBuf>) -> Self {
+        let mut history: Vec<String> = history_path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| text.lines().map(String::from).collect())
+            .unwrap_or_default();
+        let excess = history.len().saturating_sub(HISTORY_MAX);
+        history.drain(..excess);
+        let raw = if io::stdin().is_terminal() {
+            RawMode::enter()
+        } else {
+            None
+        };
+        LineEditor {
+            history,
+            history_path,
+            raw,
+        }
+    }
+
+    /// Ajoute une ligne à l'historique (et au fichier).
+    pub fn add_history(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || self.history.last().is_some_and(|l| l == line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if let Some(path) = &self.history_path {
+            let file = std::fs::File::options()
+                .create(true)
+                .append(true)
+                .open(path);
+            if let Ok(mut file) = file {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+
+    /// Lit une ligne ; `None` en fin d'entrée. Hors terminal (script,
+    /// tube), lecture simple sans édition.
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        complete: &mut Completer<'_>,
+    ) -> io::Result<Option<String>> {
+        print!("{prompt}");
+        io::stdout().flush()?;
+
+        if self.raw.is_none() {
+            let mut line = String::new();
+            return match io::stdin().lock().read_line(&mut line)? {
+                0 => Ok(None),
+                _ => Ok(Some(line.trim_end_matches(['\n', '\r']).to_string())),
+            };
+        }
+
+        let mut state = State {
+            prompt,
+            buf: Vec::new(),
+            pos: 0,
+            hist_index: self.history.len(),
+            draft: Vec::new(),
+        };
+        loop {
+            let Some(byte) = read_byte()? else {
+                return Ok(None);
+            };
+            match byte {
+                b'\r' | b'\n' => {
+                    print!("\r\n");
+                    return Ok(Some(state.buf.iter().collect()));
+                }
+                // Ctrl-C : abandonne la ligne
+                3 => {
+                    print!("^C\r\n");
+                    return Ok(Some(String::new()));
+                }
+                // Ctrl-D : fin sur ligne vide, sinon suppression
+                4 if state.buf.is_empty() => {
+                    print!("\r\n");
+                    return Ok(None);
+                }
+                4 => state.delete(),
+                127 | 8 if state.pos > 0 => {
+                    state.pos -= 1;
+                    state.delete();
+                }
+                b'\t' => state.complete(complete),
+                1 => state.pos = 0,
+                5 => state.pos = state.buf.len(),
+                21 => {
+                    state.buf.drain(..state.pos);
+                    state.pos = 0;
+                }
+                11 => state.buf.truncate(state.pos),
+                27 => match escape_sequence()? {
+                    Some(Key::Up) => state.history(&self.history, -1),
+                    Some(Key::Down) => state.history(&self.history, 1),
+                    Some(Key::Right) => state.pos = (state.pos + 1).min(state.buf.len()),
+                    Some(Key::Left) => state.pos = state.pos.saturating_sub(1),
+                    Some(Key::Home) => state.pos = 0,
+                    Some(Key::End) => state.pos = state.buf.len(),
+                    Some(Key::Delete) => state.delete(),
+                    None => {}
+                },
+                b if b >= 0x20 => {
+                    if let Some(c) = read_char(b)? {
+                        state.buf.insert(state.pos, c);
+                        state.pos += 1;
+                    }
+                }
+                _ => {}
+            }
+            state.redraw()?;
+        }
+    }
+}
+
+/// Ligne en cours d'édition.
+struct State<'a> {
+    prompt: &'a str,
+    buf: Vec<char>,
+    pos: usize,
+    /// Position dans l'historique (`len()` = nouvelle ligne)
+    hist_index: usize,
+    /// Ligne en cours, mise de côté pendant la navigation
+    draft: Vec<char>,
+}
+
+impl State<'_> {
+    fn delete(&mut self) {
+        if self.pos < self.buf.len() {
+            self.buf.remove(self.pos);
+        }
+    }
+
+    fn redraw(&self) -> io::Result<()> {
+        let line: String = self.buf.iter().collect();
+        print!("\r\x1b[K{}{line}", self.prompt);
+        let back = self.buf.len() - self.pos;
+        if back > 0 {
+            print!("\x1b[{back}D");
+        }
+        io::stdout().flush()
+    }
+
+    fn history(&mut self, history: &[String], step: isize) {
+        let Some(index) = self.hist_index.checked_add_signed(step) else {
+            return;
+        };
+        if index > history.len() {
+            return;
+        }
+        if self.hist_index == history.len() {
+            self.draft = self.buf.clone();
+        }
+        self.hist_index = index;
+        self.buf = match history.get(index) {
+            Some(line) => line.chars().collect(),
+            None => self.draft.clone(),
+        };
+        self.pos = self.buf.len();
+    }
+
+    fn complete(&mut self, complete: &mut Completer<'_>) {
+        let before: String = self.buf[..self.pos].iter().collect();
+        let (start, candidates) = complete(&before);
+        let current = &self.buf[start..self.pos];
+
+        let replacement: Vec<char> = match candidates.as_slice() {
+            [] => {
+                print!("\x07");
+                return;
+            }
+            [only] => {
+                let mut chars: Vec<char> = only.chars().collect();
+                // Un répertoire se poursuit : pas d'espace après `/` ou `/"`
+                if !only.trim_end_matches('"').ends_with('/') {
+                    chars.push(' ');
+                }
+                chars
+            }
+            [first, rest @ ..] => {
+                // Préfixe commun (la comparaison ignore la casse, comme FAT)
+                let mut common: Vec<char> = first.chars().collect();
+                for other in rest {
+                    let len = common
+                        .iter()
+                        .zip(other.chars())
+                        .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                        .count();
+                    common.truncate(len);
+                }
+                if common.len() <= current.len() {
+                    print!("\r\n{}\r\n", candidates.join("  "));
+                    return;
+                }
+                common
+            }
+        };
+        let len = replacement.len();
+        self.buf.splice(start..self.pos, replacement);
+        self.pos = start + len;
+    }
+}
+
+fn read_byte() -> io::Result<Option<u8>> {
+    let mut byte = [0u8];
+    match io::stdin().lock().read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+/// Touches reconnues dans les séquences d'échappement.
+enum Key {
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    Delete,
+}
+
+/// Lit la fin d'une séquence `ESC [ <paramètres> x` ou `ESC O x`. Les
+/// formes `ESC [ <n> ~` donnent Home (1, 7), Suppr (3) et End (4, 8) ;
+/// une séquence inconnue est lue en entier puis ignorée.
+fn escape_sequence() -> io::Result<Option<Key>> {
+    let csi = match read_byte()? {
+        Some(b'[') => true,
+        Some(b'O') => false,
+        _ => return Ok(None),
+    };
+    // Paramètres (chiffres, `;`...) jusqu'à l'octet final
+    let mut params = Vec::new();
+    let last = loop {
+        match read_byte()? {
+            Some(b @ 0x30..=0x3F) if csi => params.push(b),
+            Some(b) => break b,
+            None => return Ok(None),
+        }
+    };
+    Ok(match (params.as_slice(), last) {
+        (b"", b'A') => Some(Key::Up),
+        (b"", b'B') => Some(Key::Down),
+        (b"", b'C') => Some(Key::Right),
+        (b"", b'D') => Some(Key::Left),
+        (b"", b'H') | (b"1" | b"7", b'~') => Some(Key::Home),
+        (b"", b'F') | (b"4" | b"8", b'~') => Some(Key::End),
+        (b"3", b'~') => Some(Key::Delete),
+        _ => None,
+    })
+}
+
+/// Décode un caractère UTF-8 dont `first` est le premier octet.
+fn read_char(first: u8) -> io::Result<Option<char>> {
+    let len = match first {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return Ok(None),
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte()? {
+            Some(b) => bytes.push(b),
+            None => return Ok(None),
+        }
+    }
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next()))
+}
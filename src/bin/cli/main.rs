@@ -0,0 +1,317 @@
+mod dryrun;
+mod edit;
+mod image;
+mod inspect;
+mod json;
+mod line;
+mod overlay;
+mod raw;
+mod repair;
+mod shell;
+mod trace;
+mod transfer;
+
+use fat32_rust::trace::TraceDevice;
+use fat32_rust::{BlockDevice, Error, Fat32, MountOptions, std_support::StdBlockDevice};
+use json::Json;
+
+/// Format de sortie choisi par l'option globale `--json`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Text,
+    Json,
+}
+
+/// Échec d'une commande ; détermine le code de sortie du processus.
+pub enum CliError {
+    /// Erreur du driver, avec le chemin ou l'objet concerné
+    Fs(Error, String),
+    /// Arguments invalides
+    Usage(String),
+    /// fsck a trouvé des problèmes (le rapport est déjà affiché)
+    Problems,
+}
+
+pub type CmdResult = Result<(), CliError>;
+
+impl CliError {
+    /// Codes de sortie stables, documentés dans le README.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Problems => 1,
+            CliError::Usage(_) => 2,
+            CliError::Fs(e, _) => match e {
+                Error::Io => 3,
+                Error::InvalidFs => 4,
+                Error::NotFound => 5,
+                Error::AlreadyExists => 6,
+                Error::NotRecoverable => 7,
+                Error::InvalidName => 8,
+                Error::NoSpace => 9,
+                Error::NotEmpty => 10,
+                Error::ReadOnly => 11,
+                Error::InvalidSeek => 12,
+                Error::IsDirectory => 13,
+                Error::NotDirectory => 14,
+            },
+        }
+    }
+
+    /// Affiche l'erreur : sur stderr en texte, sur stdout en JSON (un seul
+    /// document par commande).
+    pub fn report(&self, cmd: &str, out: Output) {
+        match (self, out) {
+            (CliError::Problems, _) => {}
+            (CliError::Fs(e, what), Output::Text) => eprintln!("{cmd}: {e:?} ({what})"),
+            (CliError::Usage(msg), Output::Text) => eprintln!("{cmd}: {msg}"),
+            (CliError::Fs(e, what), Output::Json) => println!(
+                "{}",
+                Json::Obj(vec![
+                    ("command", cmd.into()),
+                    ("error", format!("{e:?}").into()),
+                    ("context", what.as_str().into()),
+                    ("exit_code", (self.exit_code() as u64).into()),
+                ])
+            ),
+            (CliError::Usage(msg), Output::Json) => println!(
+                "{}",
+                Json::Obj(vec![
+                    ("command", cmd.into()),
+                    ("error", "Usage".into()),
+                    ("context", msg.as_str().into()),
+                    ("exit_code", (self.exit_code() as u64).into()),
+                ])
+            ),
+        }
+    }
+}
+
+/// Ajoute à une erreur du driver le chemin ou l'objet concerné.
+pub trait Context<T> {
+    fn context(self, what: &str) -> Result<T, CliError>;
+}
+
+impl<T> Context<T> for Result<T, Error> {
+    fn context(self, what: &str) -> Result<T, CliError> {
+        self.map_err(|e| CliError::Fs(e, what.into()))
+    }
+}
+
+pub fn usage() {
+    eprintln!("Usage:");
+    eprintln!("  cli [--json] [--trace] [--overlay <delta>] <image> <command> [args]");
+    eprintln!(
+        "  cli build-image --size <N[K|M|G]> [--label L] [--volume-id HEX] [--time T] <host_dir> <image>"
+    );
+    eprintln!();
+    eprintln!("Commands:");
+    eprintln!("  shell");
+    eprintln!("  run <script>");
+    eprintln!("  ls [path]");
+    eprintln!("  cat <path>");
+    eprintln!("  stat <path>");
+    eprintln!("  tree [path]");
+    eprintln!("  walk [path] [--max-depth N]");
+    eprintln!("  du [path]");
+    eprintln!("  df [--scan]");
+    eprintln!("  info");
+    eprintln!("  fsck [--repair]");
+    eprintln!("  undelete <dir> [<index> <new_name>]");
+    eprintln!("  extract <src> <host_dir>");
+    eprintln!("  import <host_dir> <dest>");
+    eprintln!("  sector <lba> | cluster <n> | fat <n> | dirent <path>");
+    eprintln!("  cp [--dry-run] [host:|image:]<src> [host:|image:]<dst>");
+    eprintln!("  mkdir [--dry-run] [-p] <path>");
+    eprintln!("  rm [--dry-run] [-r] <path>");
+    eprintln!("  rmdir [--dry-run] <path>");
+    eprintln!("  mv [--dry-run] <src> <dst>");
+    eprintln!("  touch [--dry-run] <path>");
+    eprintln!();
+    eprintln!("  overlay [status|commit|discard]   (with --overlay)");
+    eprintln!();
+    eprintln!("Shell only: cd <path>, pwd, source <script>, help, exit");
+    eprintln!("--json is supported by ls, stat, walk, df, info and fsck.");
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let out = match args.iter().position(|a| a == "--json") {
+        Some(i) => {
+            args.remove(i);
+            Output::Json
+        }
+        None => Output::Text,
+    };
+    // Écritures détournées vers un fichier delta, l'image reste intacte
+    let overlay = match args.iter().position(|a| a == "--overlay") {
+        Some(i) if i + 1 < args.len() => {
+            args.remove(i);
+            Some(args.remove(i))
+        }
+        Some(_) => {
+            usage();
+            std::process::exit(CliError::Usage(String::new()).exit_code());
+        }
+        None => None,
+    };
+    let trace = match args.iter().position(|a| a == "--trace") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    std::process::exit(run(args, out, overlay, trace));
+}
+
+fn finish(cmd: &str, result: CmdResult, out: Output) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            e.report(cmd, out);
+            e.exit_code()
+        }
+    }
+}
+
+fn run(mut args: Vec<String>, out: Output, overlay: Option<String>, trace: bool) -> i32 {
+    if args.first().is_some_and(|a| a == "build-image") {
+        return finish("build-image", image::cmd_build_image(&args[1..]), out);
+    }
+    if args.len() < 2 {
+        usage();
+        return CliError::Usage(String::new()).exit_code();
+    }
+
+    let image = args.remove(0);
+    let cmd = args.remove(0);
+
+    match (overlay, cmd.as_str()) {
+        (Some(delta), "overlay") => finish(&cmd, overlay::cmd_overlay(&image, &delta, &args), out),
+        (None, "overlay") => finish(&cmd, Err(CliError::Usage("needs --overlay".into())), out),
+        (Some(delta), _) => match overlay::open(&image, &delta, false) {
+            Ok(dev) => session(dev, &image, &cmd, &args, out, trace),
+            Err(e) => finish(&cmd, Err(e), out),
+        },
+        (None, _) => match StdBlockDevice::open(&image, 512) {
+            Ok(dev) => session(dev, &image, &cmd, &args, out, trace),
+            Err(_) => finish(&cmd, Err(CliError::Fs(Error::Io, image.clone())), out),
+        },
+    }
+}
+
+/// Monte le volume puis exécute la commande (ou le shell, ou un script).
+fn session<D: BlockDevice>(
+    dev: D,
+    image: &str,
+    cmd: &str,
+    args: &[String],
+    out: Output,
+    trace: bool,
+) -> i32 {
+    let mut dev = TraceDevice::new(dev, 512);
+    if trace {
+        dev = dev.with_log();
+    }
+    let mut fs = match mount(dev, image) {
+        Ok(fs) => fs,
+        Err(e) => return finish(cmd, Err(e), out),
+    };
+    if trace {
+        trace::start(&mut fs);
+    }
+
+    let (place, result) = match (cmd, args) {
+        ("shell", _) => {
+            shell::shell(&mut fs, out);
+            (cmd.to_string(), Ok(()))
+        }
+        ("run", [script]) => match shell::run_script(&mut fs, script, out) {
+            Ok(()) => (cmd.to_string(), Ok(())),
+            Err((place, e)) => (place, Err(e)),
+        },
+        ("run", _) => (
+            cmd.to_string(),
+            Err(CliError::Usage("needs a script".into())),
+        ),
+        _ => {
+            let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            (cmd.to_string(), run_command(&mut fs, cmd, &args, out))
+        }
+    };
+    if trace {
+        trace::report(&mut fs);
+    }
+    let unmounted = fs.unmount().map(drop).context(image);
+    finish(&place, result.and(unmounted), out)
+}
+
+fn mount<D: BlockDevice>(dev: D, image: &str) -> Result<Fat32<D>, CliError> {
+    let options = MountOptions {
+        backup_boot_fallback: true,
+        ..MountOptions::default()
+    };
+    let fs = Fat32::mount(dev, options).context(image)?;
+    if fs.boot_from_backup {
+        eprintln!("warning: primary boot sector is damaged, using backup");
+    }
+    if fs.was_dirty {
+        eprintln!("warning: volume was not cleanly unmounted, run fsck");
+    }
+    Ok(fs)
+}
+
+/// Commandes disponibles en mode one-shot comme dans le shell.
+pub fn run_command<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    cmd: &str,
+    args: &[&str],
+    out: Output,
+) -> CmdResult {
+    let json_ok = matches!(cmd, "ls" | "stat" | "walk" | "df" | "info" | "fsck");
+    if out == Output::Json && !json_ok {
+        return Err(CliError::Usage("--json is not supported".into()));
+    }
+
+    match cmd {
+        "ls" => inspect::cmd_ls(fs, args.first().copied().unwrap_or("."), out),
+        "cat" => match args.first() {
+            Some(path) => transfer::cmd_cat(fs, path),
+            None => Err(CliError::Usage("needs a path".into())),
+        },
+        "stat" => match args.first() {
+            Some(path) => inspect::cmd_stat(fs, path, out),
+            None => Err(CliError::Usage("needs a path".into())),
+        },
+        "tree" => inspect::cmd_tree(fs, args.first().copied().unwrap_or(".")),
+        "walk" => inspect::cmd_walk(fs, args, out),
+        "du" => inspect::cmd_du(fs, args.first().copied().unwrap_or(".")),
+        "df" => inspect::cmd_df(fs, args.contains(&"--scan"), out),
+        "info" => inspect::cmd_info(fs, out),
+        "fsck" => repair::cmd_fsck(fs, args.contains(&"--repair"), out),
+        "undelete" => {
+            let dir = args.first().copied().unwrap_or("/");
+            match (args.get(1), args.get(2)) {
+                (Some(index), Some(name)) => repair::cmd_recover(fs, dir, index, name),
+                _ => repair::cmd_list_deleted(fs, dir),
+            }
+        }
+        "extract" => match args {
+            [src, dest] => transfer::cmd_extract(fs, src, dest),
+            _ => Err(CliError::Usage("needs <src> <host_dir>".into())),
+        },
+        "import" => match args {
+            [src, dest] => transfer::cmd_import(fs, src, dest),
+            _ => Err(CliError::Usage("needs <host_dir> <dest>".into())),
+        },
+        "sector" | "cluster" | "fat" | "dirent" => match (cmd, args) {
+            ("sector", [lba]) => raw::cmd_sector(fs, lba),
+            ("cluster", [n]) => raw::cmd_cluster(fs, n),
+            ("fat", [n]) => raw::cmd_fat(fs, n),
+            ("dirent", [path]) => raw::cmd_dirent(fs, path),
+            _ => Err(CliError::Usage("needs one argument".into())),
+        },
+        "cp" | "mkdir" | "rm" | "rmdir" | "mv" | "touch" => edit::cmd_edit(fs, cmd, args),
+        _ => Err(CliError::Usage(format!("unknown command {cmd}"))),
+    }
+}
@@ -0,0 +1,70 @@
+//! Option globale `--overlay <delta>` : l'image reste intacte, toutes les
+//! écritures vont dans le fichier delta. Commande `overlay` pour l'état du
+//! delta, son application à l'image ou son abandon.
+
+use crate::{CliError, CmdResult, Context};
+use fat32_rust::overlay::{DeltaStore, FileDelta, OverlayDevice};
+use fat32_rust::std_support::StdBlockDevice;
+
+pub type Overlay = OverlayDevice<StdBlockDevice, FileDelta>;
+
+/// Image (ouverte en lecture seule sauf pour `commit`) + delta.
+pub fn open(image: &str, delta: &str, writable: bool) -> Result<Overlay, CliError> {
+    let base = if writable {
+        StdBlockDevice::open(image, 512)
+    } else {
+        StdBlockDevice::open_read_only(image, 512)
+    };
+    let base = base.map_err(|_| CliError::Fs(fat32_rust::Error::Io, image.into()))?;
+    let delta_store = FileDelta::open(delta, 512).map_err(|e| {
+        let error = match e.kind() {
+            std::io::ErrorKind::InvalidData => fat32_rust::Error::InvalidFs,
+            _ => fat32_rust::Error::Io,
+        };
+        CliError::Fs(error, delta.into())
+    })?;
+    Ok(OverlayDevice::with_delta(base, delta_store, 512))
+}
+
+/// Plages contiguës de LBA, pour un affichage compact.
+fn ranges(lbas: &[u64]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lbas.len() {
+        let start = lbas[i];
+        while i + 1 < lbas.len() && lbas[i + 1] == lbas[i] + 1 {
+            i += 1;
+        }
+        out.push(match lbas[i] {
+            end if end == start => start.to_string(),
+            end => format!("{start}-{end}"),
+        });
+        i += 1;
+    }
+    out
+}
+
+pub fn cmd_overlay(image: &str, delta: &str, args: &[String]) -> CmdResult {
+    let action = args.first().map(String::as_str).unwrap_or("status");
+    let mut dev = open(image, delta, action == "commit")?;
+    match action {
+        "status" => {
+            let lbas = dev.delta.sectors();
+            println!("{delta}: {} sector(s) changed", lbas.len());
+            if !lbas.is_empty() {
+                println!("  {}", ranges(&lbas).join(", "));
+            }
+        }
+        "commit" => {
+            let n = dev.commit().context(image)?;
+            println!("{n} sector(s) written to {image}");
+        }
+        "discard" => {
+            let n = dev.changed_sectors();
+            dev.discard_delta().context(delta)?;
+            println!("{n} sector(s) discarded");
+        }
+        other => return Err(CliError::Usage(format!("unknown overlay action {other}"))),
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+//! Inspection brute pour le débogage : sector, cluster, fat, dirent.
+
+use crate::inspect::{format_attr, format_time};
+use crate::{CliError, CmdResult, Context};
+use fat32_rust::dir::RawSlot;
+use fat32_rust::fat::fragment_count;
+use fat32_rust::lfn::{lfn_checksum, slot_chars};
+use fat32_rust::{BlockDevice, Fat32};
+
+/// Nombre décimal ou hexadécimal (`0x...`).
+fn parse_number(s: &str) -> Result<u32, CliError> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| CliError::Usage(format!("invalid number {s}")))
+}
+
+/// Hexdump classique : 16 octets par ligne, `base` = adresse du premier.
+fn hexdump(base: u64, data: &[u8]) {
+    for (i, line) in data.chunks(16).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{b:02x}")).collect();
+        let (left, right) = hex.split_at(hex.len().min(8));
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!(
+            "{:08x}  {:<23}  {:<23}  |{ascii}|",
+            base + i as u64 * 16,
+            left.join(" "),
+            right.join(" ")
+        );
+    }
+}
+
+/// Signification d'une valeur de FAT.
+fn fat_value(value: u32) -> String {
+    match value {
+        0 => "free".into(),
+        1 => "reserved".into(),
+        0x0FFF_FFF7 => "bad cluster".into(),
+        v if v >= 0x0FFF_FFF8 => "end of chain".into(),
+        v => format!("next = {v}"),
+    }
+}
+
+/// Zone du volume à laquelle appartient un secteur.
+fn region<D: BlockDevice>(fs: &Fat32<D>, lba: u32) -> String {
+    let boot = &fs.boot;
+    let fat_len = boot.sectors_per_fat;
+    let fat_end = fs.fat_start_lba + boot.num_fats as u32 * fat_len;
+    match lba {
+        0 => "boot sector".into(),
+        l if l == boot.fs_info_sector as u32 => "FSInfo".into(),
+        l if l == boot.backup_boot_sector as u32 => "backup boot sector".into(),
+        l if l < fs.fat_start_lba => "reserved".into(),
+        l if l < fat_end => {
+            let rel = l - fs.fat_start_lba;
+            let per_sector = fs.bytes_per_sector() / 4;
+            let first = rel % fat_len * per_sector;
+            format!(
+                "FAT {} sector {} (clusters {}-{})",
+                rel / fat_len,
+                rel % fat_len,
+                first,
+                first + per_sector - 1
+            )
+        }
+        l if l >= fs.data_start_lba => {
+            let spc = fs.sectors_per_cluster();
+            let rel = l - fs.data_start_lba;
+            format!("data, cluster {} sector {}", rel / spc + 2, rel % spc)
+        }
+        _ => "unknown".into(),
+    }
+}
+
+fn read_sector<D: BlockDevice>(fs: &mut Fat32<D>, lba: u32) -> Result<Vec<u8>, CliError> {
+    let mut buf = vec![0u8; fs.bytes_per_sector() as usize];
+    fs.device
+        .read_sector(lba as u64, &mut buf)
+        .context(&format!("sector {lba}"))?;
+    Ok(buf)
+}
+
+pub fn cmd_sector<D: BlockDevice>(fs: &mut Fat32<D>, lba: &str) -> CmdResult {
+    let lba = parse_number(lba)?;
+    let data = read_sector(fs, lba)?;
+    println!("sector {lba}: {}", region(fs, lba));
+    hexdump(lba as u64 * data.len() as u64, &data);
+    Ok(())
+}
+
+fn check_cluster<D: BlockDevice>(fs: &Fat32<D>, cluster: u32) -> CmdResult {
+    if cluster < 2 || cluster >= fs.cluster_count() + 2 {
+        return Err(CliError::Usage(format!(
+            "cluster {cluster} out of range (2-{})",
+            fs.cluster_count() + 1
+        )));
+    }
+    Ok(())
+}
+
+pub fn cmd_cluster<D: BlockDevice>(fs: &mut Fat32<D>, cluster: &str) -> CmdResult {
+    let cluster = parse_number(cluster)?;
+    check_cluster(fs, cluster)?;
+    let first = fs.cluster_to_lba(cluster);
+    let spc = fs.sectors_per_cluster();
+    let next = fs.read_fat_entry(cluster).context("FAT")?;
+    println!(
+        "cluster {cluster}: sectors {first}-{}, FAT entry {next:#010x} ({})",
+        first + spc - 1,
+        fat_value(next)
+    );
+    for s in 0..spc {
+        let data = read_sector(fs, first + s)?;
+        println!("-- sector {} --", first + s);
+        hexdump((first + s) as u64 * data.len() as u64, &data);
+    }
+    Ok(())
+}
+
+pub fn cmd_fat<D: BlockDevice>(fs: &mut Fat32<D>, cluster: &str) -> CmdResult {
+    let cluster = parse_number(cluster)?;
+    check_cluster(fs, cluster)?;
+    let bytes_per_sector = fs.bytes_per_sector();
+    let copy = fs.active_fat();
+    let lba = fs.fat_copy_lba(copy) + cluster * 4 / bytes_per_sector;
+    let offset = (cluster * 4 % bytes_per_sector) as usize;
+
+    let data = read_sector(fs, lba)?;
+    let value = fs.read_fat_entry(cluster).context("FAT")?;
+    println!(
+        "FAT {copy} entry {cluster} at sector {lba} offset {offset}: {value:#010x} ({})",
+        fat_value(value)
+    );
+    let row = offset / 16 * 16;
+    hexdump(
+        lba as u64 * bytes_per_sector as u64 + row as u64,
+        &data[row..row + 16],
+    );
+    // Repère sous les 4 octets de l'entrée (espace double après 8 octets)
+    let column = 10 + (offset - row) * 3 + usize::from(offset - row >= 8);
+    println!("{}{}", " ".repeat(column), "^".repeat(11));
+
+    if value < 2 || value == 0x0FFF_FFF7 {
+        return Ok(());
+    }
+    let mut chain = Vec::new();
+    fs.cluster_chain(cluster, &mut chain).context("chain")?;
+    let links: Vec<String> = chain.iter().map(u32::to_string).collect();
+    println!("chain: {} -> EOC", links.join(" -> "));
+    println!(
+        "{} cluster(s), {} fragment(s)",
+        chain.len(),
+        fragment_count(&chain)
+    );
+    Ok(())
+}
+
+/// Champs décodés d'une entrée brute.
+fn describe_slot(slot: &RawSlot, short: Option<&[u8; 11]>) {
+    let b = &slot.bytes;
+    if slot.is_end() {
+        println!("  end of directory");
+        return;
+    }
+    let state = if slot.is_deleted() { " (deleted)" } else { "" };
+
+    if slot.is_lfn() {
+        let chars = slot_chars(b);
+        let len = chars
+            .iter()
+            .position(|&c| c == 0 || c == 0xFFFF)
+            .unwrap_or(chars.len());
+        let text = String::from_utf16_lossy(&chars[..len]);
+        println!(
+            "  LFN{state}: sequence {}{}, checksum {:#04x}{}, chars {text:?}",
+            b[0] & 0x1F,
+            if b[0] & 0x40 != 0 { " (last)" } else { "" },
+            b[13],
+            match short {
+                Some(raw) if lfn_checksum(raw) != b[13] => " (mismatch)",
+                _ => "",
+            }
+        );
+        return;
+    }
+
+    let word = |i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+    let first = (word(20) as u32) << 16 | word(26) as u32;
+    println!(
+        "  8.3{state}: name {:?}, attr {:#04x} ({})",
+        String::from_utf8_lossy(&b[0..11]),
+        b[11],
+        format_attr(b[11])
+    );
+    let entry = slot.entry();
+    let time =
+        |f: fn(&fat32_rust::dir::DirEntry) -> Option<_>| format_time(entry.as_ref().and_then(f));
+    println!(
+        "  cluster {first} (high {:#06x}, low {:#06x}), size {}",
+        word(20),
+        word(26),
+        u32::from_le_bytes([b[28], b[29], b[30], b[31]])
+    );
+    println!(
+        "  created {} (+{} ms), modified {}, accessed {}",
+        time(|e| e.created),
+        b[13] as u32 * 10,
+        time(|e| e.modified),
+        time(|e| e.accessed)
+    );
+}
+
+pub fn cmd_dirent<D: BlockDevice>(fs: &mut Fat32<D>, path: &str) -> CmdResult {
+    let slots = fs.entry_slots(path).context(path)?;
+    let short = slots.last().map(|s| {
+        let mut raw = [0u8; 11];
+        raw.copy_from_slice(&s.bytes[0..11]);
+        raw
+    });
+
+    for (i, slot) in slots.iter().enumerate() {
+        println!(
+            "slot {i} at sector {} offset {} ({})",
+            slot.lba,
+            slot.offset,
+            region(fs, slot.lba)
+        );
+        let base = slot.lba as u64 * fs.bytes_per_sector() as u64 + slot.offset as u64;
+        hexdump(base, &slot.bytes);
+        describe_slot(slot, short.as_ref());
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Vérification et récupération : fsck, undelete.
+
+use crate::json::Json;
+use crate::{CliError, CmdResult, Context, Output};
+use fat32_rust::check::{CheckMode, Problem};
+use fat32_rust::{BlockDevice, Fat32};
+
+fn problem_json(p: &Problem) -> Json {
+    let mut fields: Vec<(&'static str, Json)> = match p {
+        Problem::LostChain { start, clusters } => vec![
+            ("kind", "LostChain".into()),
+            ("cluster", (*start).into()),
+            ("clusters", (*clusters).into()),
+        ],
+        Problem::CrossLinked { path, cluster } => vec![
+            ("kind", "CrossLinked".into()),
+            ("path", path.as_str().into()),
+            ("cluster", (*cluster).into()),
+        ],
+        Problem::BadChain { path, cluster } => vec![
+            ("kind", "BadChain".into()),
+            ("path", path.as_str().into()),
+            ("cluster", (*cluster).into()),
+        ],
+        Problem::SizeMismatch {
+            path,
+            size,
+            clusters,
+        } => vec![
+            ("kind", "SizeMismatch".into()),
+            ("path", path.as_str().into()),
+            ("size", (*size).into()),
+            ("clusters", (*clusters).into()),
+        ],
+        Problem::BadDotEntry {
+            path,
+            name,
+            found,
+            expected,
+        } => vec![
+            ("kind", "BadDotEntry".into()),
+            ("path", path.as_str().into()),
+            ("name", (*name).into()),
+            ("cluster", (*found).into()),
+            ("expected", (*expected).into()),
+        ],
+        Problem::FatMismatch { copy, entries } => vec![
+            ("kind", "FatMismatch".into()),
+            ("copy", (*copy).into()),
+            ("entries", (*entries).into()),
+        ],
+        Problem::BadFsInfo => vec![("kind", "BadFsInfo".into())],
+        Problem::BadFreeCount { stored, actual } => vec![
+            ("kind", "BadFreeCount".into()),
+            ("stored", (*stored).into()),
+            ("actual", (*actual).into()),
+        ],
+        Problem::BadNextFree { stored } => {
+            vec![("kind", "BadNextFree".into()), ("stored", (*stored).into())]
+        }
+        Problem::InvalidShortName { path } => vec![
+            ("kind", "InvalidShortName".into()),
+            ("path", path.as_str().into()),
+        ],
+    };
+    fields.push(("message", p.to_string().into()));
+    Json::Obj(fields)
+}
+
+pub fn cmd_fsck<D: BlockDevice>(fs: &mut Fat32<D>, repair: bool, out: Output) -> CmdResult {
+    let mode = if repair {
+        CheckMode::Repair
+    } else {
+        CheckMode::ReportOnly
+    };
+    let report = fs.check(mode).context("volume")?;
+
+    match out {
+        Output::Json => {
+            let problems: Vec<Json> = report.problems.iter().map(problem_json).collect();
+            let fields = vec![
+                ("clean", report.is_clean().into()),
+                ("repaired", report.repaired.into()),
+                ("problems", Json::Arr(problems)),
+            ];
+            println!("{}", Json::Obj(fields));
+        }
+        Output::Text => {
+            for p in &report.problems {
+                println!("{p}");
+            }
+
+            if report.is_clean() {
+                println!("fsck: no problems found");
+            } else if report.repaired {
+                println!(
+                    "fsck: {} problem(s) found and repaired",
+                    report.problems.len()
+                );
+            } else {
+                println!("fsck: {} problem(s) found", report.problems.len());
+            }
+        }
+    }
+
+    if report.is_clean() {
+        Ok(())
+    } else {
+        Err(CliError::Problems)
+    }
+}
+
+pub fn cmd_list_deleted<D: BlockDevice>(fs: &mut Fat32<D>, dir: &str) -> CmdResult {
+    let entries = fs.list_deleted(dir).context(dir)?;
+    for (i, e) in entries.iter().enumerate() {
+        let kind = if e.is_dir { "<DIR>" } else { "     " };
+        let status = if e.recoverable { "recoverable" } else { "lost" };
+        println!(
+            "{i:3}  {kind}  {:12}  {:10}  cluster {:<8}  {status}",
+            e.name, e.size, e.first_cluster
+        );
+    }
+    Ok(())
+}
+
+pub fn cmd_recover<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    dir: &str,
+    index: &str,
+    name: &str,
+) -> CmdResult {
+    let entries = fs.list_deleted(dir).context(dir)?;
+    let Some(entry) = index.parse::<usize>().ok().and_then(|i| entries.get(i)) else {
+        return Err(CliError::Usage(format!(
+            "no deleted entry #{index} in {dir}"
+        )));
+    };
+
+    let e = fs.recover(entry, name).context(name)?;
+    println!("recovered {} ({} bytes)", e.name, e.size);
+    Ok(())
+}
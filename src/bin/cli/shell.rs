@@ -0,0 +1,235 @@
+//! Shell interactif et scripts (`source`, `cli <image> run script.txt`).
+//!
+//! Les lignes sont découpées comme dans un shell : espaces comme
+//! séparateurs, `'...'` littéral, `"..."` avec `\"` et `\\`, `\` hors
+//! guillemets, `#` en début de mot pour un commentaire.
+
+use crate::line::LineEditor;
+use crate::{CliError, Context, Output, run_command, usage};
+use fat32_rust::{BlockDevice, Error, Fat32};
+use std::path::PathBuf;
+
+/// Commandes proposées par la complétion du premier mot.
+const COMMANDS: &[&str] = &[
+    "cat", "cd", "cluster", "cp", "df", "dirent", "du", "exit", "extract", "fat", "fsck", "help",
+    "import", "info", "ls", "mkdir", "mv", "pwd", "quit", "rm", "rmdir", "sector", "source",
+    "stat", "touch", "tree", "undelete", "walk",
+];
+
+/// Suite de l'exécution après une ligne.
+enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Échec d'une ligne : emplacement (`script:ligne: commande`) et erreur.
+pub type LineError = (String, CliError);
+
+/// Découpe une ligne en arguments.
+fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            '#' if current.is_none() => break,
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err("unterminated quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err("unterminated quote".into()),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err("unterminated quote".into()),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    current.get_or_insert_with(String::new).push(c);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Forme citée d'un argument, relisible par `split_args`.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"\\#".contains(c)) {
+        return arg.into();
+    }
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{escaped}\"")
+}
+
+/// Indice (en octets) du début du dernier mot, guillemets compris.
+fn word_start(line: &str) -> usize {
+    let (mut start, mut quote, mut escaped) = (0, None, false);
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            _ if escaped => escaped = false,
+            ('\\', Some('\'')) => {}
+            ('\\', _) => escaped = true,
+            (q @ ('\'' | '"'), None) => quote = Some(q),
+            (q, Some(open)) if q == open => quote = None,
+            (c, None) if c.is_whitespace() => start = i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    start
+}
+
+/// Complétion des commandes (premier mot) et des chemins de l'image.
+fn complete<D: BlockDevice>(fs: &mut Fat32<D>, before: &str) -> (usize, Vec<String>) {
+    let start = word_start(before);
+    let partial = &before[start..];
+    // Mot en cours, éventuellement avec un guillemet non refermé
+    let word = ["", "\"", "'"]
+        .iter()
+        .find_map(|close| split_args(&format!("{partial}{close}")).ok())
+        .and_then(|args| args.into_iter().next())
+        .unwrap_or_default();
+    let char_start = before[..start].chars().count();
+
+    if before[..start].trim().is_empty() {
+        let commands = COMMANDS.iter().filter(|c| c.starts_with(word.as_str()));
+        return (char_start, commands.map(|c| c.to_string()).collect());
+    }
+
+    let (dir, prefix) = match word.rfind('/') {
+        Some(i) => word.split_at(i + 1),
+        None => ("", word.as_str()),
+    };
+    let entries = match dir {
+        "" => fs.list_cwd(),
+        "/" => fs.list_root(),
+        _ => fs
+            .resolve_path(dir.trim_end_matches('/'))
+            .and_then(|e| match e.is_dir {
+                true => Ok(fs.dir_cluster_of(&e)),
+                false => Err(Error::NotFound),
+            })
+            .and_then(|cluster| fs.read_dir_cluster(cluster)),
+    };
+    let prefix = prefix.to_lowercase();
+    let mut candidates: Vec<String> = entries
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e.name.to_lowercase().starts_with(&prefix))
+        .filter(|e| prefix.starts_with('.') || (e.name != "." && e.name != ".."))
+        .map(|e| {
+            let slash = if e.is_dir { "/" } else { "" };
+            quote(&format!("{dir}{}{slash}", e.name))
+        })
+        .collect();
+    candidates.sort();
+    (char_start, candidates)
+}
+
+/// Fichier d'historique : `$FAT32_HISTORY`, sinon `~/.fat32_history`.
+fn history_path() -> Option<PathBuf> {
+    match std::env::var_os("FAT32_HISTORY") {
+        Some(path) if path.is_empty() => None,
+        Some(path) => Some(path.into()),
+        None => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".fat32_history")),
+    }
+}
+
+/// Exécute une ligne de commande du shell ou d'un script.
+fn execute<D: BlockDevice>(fs: &mut Fat32<D>, line: &str, out: Output) -> Result<Flow, LineError> {
+    let args = split_args(line).map_err(|msg| (String::from("shell"), CliError::Usage(msg)))?;
+    let Some((cmd, args)) = args.split_first() else {
+        return Ok(Flow::Continue);
+    };
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+
+    let result = match cmd.as_str() {
+        "exit" | "quit" => return Ok(Flow::Exit),
+        "pwd" => fs.current_dir().context(".").map(|path| println!("{path}")),
+        "cd" => {
+            let path = args.first().copied().unwrap_or("/");
+            fs.change_dir(path).context(path)
+        }
+        "help" => {
+            usage();
+            Ok(())
+        }
+        "source" => match args.as_slice() {
+            [script] => return run_script(fs, script, out).map(|()| Flow::Continue),
+            _ => Err(CliError::Usage("needs a script".into())),
+        },
+        _ => run_command(fs, cmd, &args, out),
+    };
+    result
+        .map(|()| Flow::Continue)
+        .map_err(|e| (cmd.clone(), e))
+}
+
+/// Exécute un script ligne par ligne ; la première commande en échec
+/// arrête le script (comme `set -e`). `exit` le termine avec succès.
+pub fn run_script<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    script: &str,
+    out: Output,
+) -> Result<(), LineError> {
+    let text = std::fs::read_to_string(script).map_err(|_| {
+        (
+            String::from("source"),
+            CliError::Fs(Error::Io, script.into()),
+        )
+    })?;
+    for (n, line) in text.lines().enumerate() {
+        match execute(fs, line, out) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Exit) => break,
+            Err((cmd, e)) => return Err((format!("{script}:{}: {cmd}", n + 1), e)),
+        }
+    }
+    Ok(())
+}
+
+pub fn shell<D: BlockDevice>(fs: &mut Fat32<D>, out: Output) {
+    let mut editor = LineEditor::new(history_path());
+    loop {
+        let cwd = fs.current_dir().unwrap_or_else(|_| "?".into());
+        let prompt = format!("fat32:{cwd}> ");
+        // Fin de l'entrée (Ctrl-D, script terminé) ou erreur
+        let line = match editor.read_line(&prompt, &mut |before| complete(fs, before)) {
+            Ok(Some(line)) => line,
+            _ => break,
+        };
+        editor.add_history(&line);
+
+        match execute(fs, &line, out) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Exit) => break,
+            Err((cmd, e)) => e.report(&cmd, out),
+        }
+    }
+}
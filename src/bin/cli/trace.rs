@@ -0,0 +1,59 @@
+//! Option globale `--trace` : journal des accès bloc d'une commande, sur
+//! stderr (stdout reste exploitable, même en JSON).
+
+use fat32_rust::trace::{Region, TraceDevice};
+use fat32_rust::{BlockDevice, Fat32};
+
+/// Étiquette les accès à venir et remet les compteurs à zéro (le montage
+/// et le calcul de la disposition ne sont pas comptés).
+pub fn start<D: BlockDevice>(fs: &mut Fat32<TraceDevice<D>>) {
+    fs.device.layout = fs.layout().ok();
+    fs.device.reset();
+}
+
+pub fn report<D: BlockDevice>(fs: &mut Fat32<TraceDevice<D>>) {
+    let log = fs.device.log().to_vec();
+    let stats = fs.device.stats;
+
+    // Répertoires créés par la commande : disposition recalculée et fusionnée
+    let mut layout = fs.device.layout.take().unwrap_or_default();
+    if let Ok(after) = fs.layout() {
+        let mut dirs = layout.dir_clusters;
+        dirs.extend(after.dir_clusters.iter().copied());
+        layout = after;
+        layout.dir_clusters = dirs;
+    }
+
+    let regions = [Region::Boot, Region::Fat, Region::Dir, Region::Data];
+    let mut per_region = [(0u64, 0u64); 4];
+    for access in &log {
+        let region = layout.region(access.lba);
+        let line = format!(
+            "trace: {} {:>10} +{:<3} {}",
+            if access.write { "W" } else { "R" },
+            access.lba,
+            access.sectors,
+            region.name(),
+        );
+        eprintln!("{}", line.trim_end());
+        if let Some(i) = regions.iter().position(|&r| r == region) {
+            let counter = &mut per_region[i];
+            if access.write {
+                counter.1 += 1;
+            } else {
+                counter.0 += 1;
+            }
+        }
+    }
+
+    eprintln!(
+        "trace: {} read(s) ({} bytes), {} write(s) ({} bytes)",
+        stats.reads, stats.bytes_read, stats.writes, stats.bytes_written
+    );
+    let summary: Vec<String> = regions
+        .iter()
+        .zip(per_region)
+        .map(|(r, (reads, writes))| format!("{} {reads}R/{writes}W", r.name()))
+        .collect();
+    eprintln!("trace: {}", summary.join(", "));
+}
@@ -0,0 +1,34 @@
+//! Échanges de contenu : cat, extract, import.
+
+use crate::{CmdResult, Context};
+use fat32_rust::host::{ImportOptions, export, import_tree, plan_import};
+use fat32_rust::{BlockDevice, Fat32};
+use std::path::Path;
+
+pub fn cmd_cat<D: BlockDevice>(fs: &mut Fat32<D>, path: &str) -> CmdResult {
+    let data = fs.read_file(path).context(path)?;
+    print!("{}", String::from_utf8_lossy(&data));
+    Ok(())
+}
+
+pub fn cmd_extract<D: BlockDevice>(fs: &mut Fat32<D>, src: &str, dest: &str) -> CmdResult {
+    let stats = export(fs, src, Path::new(dest)).context(src)?;
+    println!(
+        "extracted {} file(s), {} dir(s), {} bytes",
+        stats.files, stats.dirs, stats.bytes
+    );
+    Ok(())
+}
+
+pub fn cmd_import<D: BlockDevice>(fs: &mut Fat32<D>, src: &str, dest: &str) -> CmdResult {
+    let src_path = Path::new(src);
+    let plan = plan_import(fs, src_path, dest).context(src)?;
+    println!(
+        "{} file(s), {} dir(s), {} bytes: {} cluster(s) needed, {} free",
+        plan.files, plan.dirs, plan.bytes, plan.clusters_needed, plan.clusters_free
+    );
+
+    import_tree(fs, src_path, dest, &ImportOptions::default()).context(src)?;
+    println!("imported into {dest}");
+    Ok(())
+}
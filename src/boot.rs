@@ -1,12 +1,52 @@
-use crate::Error;
+use crate::geometry::layout_of;
+use crate::{BlockDevice, Error, Fat32};
+
+/// Type de FAT, déterminé uniquement par le nombre de clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FatType {
+    Fat12,
+    Fat16,
+    Fat32,
+}
 
 pub struct BootSector {
+    /// Instruction de saut (EB xx 90 ou E9 xx xx)
+    pub jump: [u8; 3],
+    /// Nom du formateur ("MSWIN4.1", "mkfs.fat"...)
+    pub oem_name: [u8; 8],
     pub bytes_per_sector: u16,
     pub sectors_per_cluster: u8,
     pub reserved_sectors: u16,
     pub num_fats: u8,
+    /// Entrées de la racine fixe (0 en FAT32)
+    pub root_entry_count: u16,
+    /// Nombre total de secteurs du volume (champ 16 bits ou 32 bits)
+    pub total_sectors: u32,
+    pub media: u8,
+    /// Taille d'une FAT en FAT12/16 (0 en FAT32)
+    pub sectors_per_fat_16: u16,
+    pub sectors_per_track: u16,
+    pub num_heads: u16,
+    /// Secteurs précédant le volume sur le disque
+    pub hidden_sectors: u32,
     pub sectors_per_fat: u32,
+    /// Bits 0-3 : FAT active ; bit 7 : miroir désactivé
+    pub ext_flags: u16,
+    /// Version du système de fichiers (0.0 attendu)
+    pub fs_version: u16,
     pub root_cluster: u32,
+    /// Secteur du FSInfo (relatif au début du volume)
+    pub fs_info_sector: u16,
+    /// Secteur du boot sector de secours (0 = pas de copie)
+    pub backup_boot_sector: u16,
+    /// Numéro de lecteur BIOS (0x80 pour un disque dur)
+    pub drive_number: u8,
+    /// 0x29 si les trois champs suivants sont présents
+    pub boot_signature: u8,
+    pub volume_id: u32,
+    pub volume_label: [u8; 11],
+    /// Texte indicatif ("FAT32   "), à ne pas utiliser pour détecter le type
+    pub fs_type: [u8; 8],
 }
 
 impl BootSector {
@@ -15,20 +55,270 @@ impl BootSector {
             return Err(Error::InvalidFs);
         }
 
-        let bytes_per_sector = u16::from_le_bytes([sector[11], sector[12]]);
+        let u16_at = |off: usize| u16::from_le_bytes([sector[off], sector[off + 1]]);
+        let u32_at = |off: usize| {
+            u32::from_le_bytes([
+                sector[off],
+                sector[off + 1],
+                sector[off + 2],
+                sector[off + 3],
+            ])
+        };
+
+        let bytes_per_sector = u16_at(11);
         let sectors_per_cluster = sector[13];
-        let reserved_sectors = u16::from_le_bytes([sector[14], sector[15]]);
+        let reserved_sectors = u16_at(14);
         let num_fats = sector[16];
-        let sectors_per_fat = u32::from_le_bytes([sector[36], sector[37], sector[38], sector[39]]);
-        let root_cluster = u32::from_le_bytes([sector[44], sector[45], sector[46], sector[47]]);
+        let total_sectors_16 = u16_at(19);
+        let total_sectors_32 = u32_at(32);
+        let sectors_per_fat = u32_at(36);
+        let ext_flags = u16_at(40);
+        let root_cluster = u32_at(44);
+        let fs_info_sector = u16_at(48);
+        let backup_boot_sector = u16_at(50);
+
+        // Contrôles de base : un secteur endommagé ne doit pas passer
+        if sector[510] != 0x55 || sector[511] != 0xAA {
+            return Err(Error::InvalidFs);
+        }
+        if !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096)
+            || !sectors_per_cluster.is_power_of_two()
+            || reserved_sectors == 0
+            || num_fats == 0
+            || sectors_per_fat == 0
+            || root_cluster < 2
+        {
+            return Err(Error::InvalidFs);
+        }
+
+        let total_sectors = if total_sectors_16 != 0 {
+            total_sectors_16 as u32
+        } else {
+            total_sectors_32
+        };
+
+        let mut jump = [0u8; 3];
+        jump.copy_from_slice(&sector[0..3]);
+        let mut oem_name = [0u8; 8];
+        oem_name.copy_from_slice(&sector[3..11]);
+        let mut volume_label = [0u8; 11];
+        volume_label.copy_from_slice(&sector[71..82]);
+        let mut fs_type = [0u8; 8];
+        fs_type.copy_from_slice(&sector[82..90]);
 
         Ok(Self {
+            jump,
+            oem_name,
             bytes_per_sector,
             sectors_per_cluster,
             reserved_sectors,
             num_fats,
+            root_entry_count: u16_at(17),
+            total_sectors,
+            media: sector[21],
+            sectors_per_fat_16: u16_at(22),
+            sectors_per_track: u16_at(24),
+            num_heads: u16_at(26),
+            hidden_sectors: u32_at(28),
             sectors_per_fat,
+            ext_flags,
+            fs_version: u16_at(42),
             root_cluster,
+            fs_info_sector,
+            backup_boot_sector,
+            drive_number: sector[64],
+            boot_signature: sector[66],
+            volume_id: u32_at(67),
+            volume_label,
+            fs_type,
         })
     }
+
+    /// Premier secteur de la zone data (après les secteurs réservés, les
+    /// FAT et l'éventuelle racine fixe FAT12/16).
+    pub fn data_start_sector(&self) -> u32 {
+        let fat_size = if self.sectors_per_fat_16 != 0 {
+            self.sectors_per_fat_16 as u32
+        } else {
+            self.sectors_per_fat
+        };
+        let root_dir_sectors =
+            (self.root_entry_count as u32 * 32).div_ceil(self.bytes_per_sector as u32);
+        self.reserved_sectors as u32 + self.num_fats as u32 * fat_size + root_dir_sectors
+    }
+
+    /// Nombre de clusters de la zone data.
+    pub fn cluster_count(&self) -> u32 {
+        self.total_sectors.saturating_sub(self.data_start_sector())
+            / self.sectors_per_cluster.max(1) as u32
+    }
+
+    /// Type de FAT selon la règle de la spécification Microsoft.
+    pub fn fat_type(&self) -> FatType {
+        match self.cluster_count() {
+            0..4085 => FatType::Fat12,
+            4085..65525 => FatType::Fat16,
+            _ => FatType::Fat32,
+        }
+    }
+
+    /// Vrai si toutes les copies de la FAT sont tenues à jour (cas normal).
+    pub fn mirroring_enabled(&self) -> bool {
+        self.ext_flags & 0x0080 == 0
+    }
+
+    /// Index de la FAT active (seulement significatif sans miroir).
+    pub fn active_fat(&self) -> u8 {
+        (self.ext_flags & 0x000F) as u8
+    }
+}
+
+/// Valeur "inconnu" des compteurs FSInfo.
+pub const FSINFO_UNKNOWN: u32 = 0xFFFF_FFFF;
+
+/// Secteur FSInfo : indications sur les clusters libres (non fiables).
+pub struct FsInfo {
+    /// Nombre de clusters libres, ou `FSINFO_UNKNOWN`
+    pub free_count: u32,
+    /// Indice du prochain cluster libre, ou `FSINFO_UNKNOWN`
+    pub next_free: u32,
+}
+
+impl FsInfo {
+    const LEAD_SIG: u32 = 0x4161_5252;
+    const STRUC_SIG: u32 = 0x6141_7272;
+    const TRAIL_SIG: u32 = 0xAA55_0000;
+
+    pub fn parse(sector: &[u8]) -> Result<Self, Error> {
+        if sector.len() < 512 {
+            return Err(Error::InvalidFs);
+        }
+
+        let read = |off: usize| {
+            u32::from_le_bytes([
+                sector[off],
+                sector[off + 1],
+                sector[off + 2],
+                sector[off + 3],
+            ])
+        };
+
+        if read(0) != Self::LEAD_SIG || read(484) != Self::STRUC_SIG || read(508) != Self::TRAIL_SIG
+        {
+            return Err(Error::InvalidFs);
+        }
+
+        Ok(Self {
+            free_count: read(488),
+            next_free: read(492),
+        })
+    }
+
+    /// Compteurs recalculés depuis une copie de la FAT (valeurs brutes, une
+    /// par cluster, entrées réservées 0 et 1 comprises).
+    pub fn from_fat(fat: &[u32]) -> Self {
+        let mut free = (2..fat.len())
+            .filter(|&c| fat[c] & 0x0FFF_FFFF == 0)
+            .peekable();
+        let next_free = free.peek().map_or(FSINFO_UNKNOWN, |&c| c as u32);
+        FsInfo {
+            free_count: free.count() as u32,
+            next_free,
+        }
+    }
+
+    /// Ajuste le compteur de clusters libres de `delta` (s'il est connu)
+    /// et, si fournie, l'indication de prochain cluster libre.
+    pub fn adjust(&mut self, delta: i64, next_free: Option<u32>) {
+        if self.free_count != FSINFO_UNKNOWN {
+            self.free_count = (self.free_count as i64 + delta).max(0) as u32;
+        }
+        if let Some(next) = next_free {
+            self.next_free = next;
+        }
+    }
+
+    /// Réécrit les compteurs (et les signatures) dans un secteur FSInfo.
+    pub fn write_to(&self, sector: &mut [u8]) {
+        sector[0..4].copy_from_slice(&Self::LEAD_SIG.to_le_bytes());
+        sector[484..488].copy_from_slice(&Self::STRUC_SIG.to_le_bytes());
+        sector[488..492].copy_from_slice(&self.free_count.to_le_bytes());
+        sector[492..496].copy_from_slice(&self.next_free.to_le_bytes());
+        sector[508..512].copy_from_slice(&Self::TRAIL_SIG.to_le_bytes());
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Réécrit le boot sector principal à partir de sa copie de secours.
+    ///
+    /// La copie est validée avant toute écriture. Le FSInfo principal n'est
+    /// remplacé par sa copie que s'il est lui-même illisible. La disposition
+    /// du volume monté (FAT, zone data) est ensuite celle de la copie.
+    pub fn restore_boot_sector(&mut self) -> Result<(), Error> {
+        let backup = self.boot.backup_boot_sector as u64;
+        if backup == 0 || backup == 0xFFFF || backup >= self.boot.reserved_sectors as u64 {
+            return Err(Error::InvalidFs);
+        }
+
+        let mut sector = alloc::vec![0u8; self.boot.bytes_per_sector as usize];
+        self.device.read_sector(backup, &mut sector)?;
+        let boot = BootSector::parse(&sector)?;
+        let (fat_start_lba, data_start_lba) = layout_of(&boot)?;
+        self.device.write_sector(0, &sector)?;
+
+        // Troisième secteur de la zone de boot
+        let mut extra = alloc::vec![0u8; sector.len()];
+        self.device.read_sector(backup + 2, &mut extra)?;
+        self.device.write_sector(2, &extra)?;
+
+        if boot.root_cluster != self.boot.root_cluster {
+            self.cwd_cluster = boot.root_cluster;
+        }
+        self.boot = boot;
+        self.fat_start_lba = fat_start_lba;
+        self.data_start_lba = data_start_lba;
+        self.boot_from_backup = false;
+
+        if self.read_fsinfo().is_err() {
+            let mut info = alloc::vec![0u8; sector.len()];
+            self.device.read_sector(backup + 1, &mut info)?;
+            FsInfo::parse(&info)?;
+            self.device
+                .write_sector(self.boot.fs_info_sector as u64, &info)?;
+        }
+        self.flush()
+    }
+
+    /// Lit le secteur FSInfo.
+    pub fn read_fsinfo(&mut self) -> Result<FsInfo, Error> {
+        let mut buf = alloc::vec![0u8; self.boot.bytes_per_sector as usize];
+        self.device
+            .read_sector(self.boot.fs_info_sector as u64, &mut buf)?;
+        FsInfo::parse(&buf)
+    }
+
+    /// Ajuste le compteur de clusters libres du FSInfo de `delta` (s'il
+    /// est connu) et, si fourni, l'indication de prochain cluster libre.
+    /// Sans FSInfo lisible, rien n'est fait : ce ne sont que des indications.
+    pub(crate) fn fsinfo_update(
+        &mut self,
+        delta: i64,
+        next_free: Option<u32>,
+    ) -> Result<(), Error> {
+        let Ok(mut info) = self.read_fsinfo() else {
+            return Ok(());
+        };
+        info.adjust(delta, next_free);
+        self.write_fsinfo(&info)
+    }
+
+    /// Met à jour le secteur FSInfo (le reste du secteur est conservé).
+    pub fn write_fsinfo(&mut self, info: &FsInfo) -> Result<(), Error> {
+        self.mark_dirty()?;
+        let lba = self.boot.fs_info_sector as u64;
+        let mut buf = alloc::vec![0u8; self.boot.bytes_per_sector as usize];
+        self.device.read_sector(lba, &mut buf)?;
+        info.write_to(&mut buf);
+        self.device.write_sector(lba, &buf)
+    }
 }
@@ -0,0 +1,594 @@
+use crate::boot::{FSINFO_UNKNOWN, FsInfo};
+use crate::dir::{RawSlot, is_valid_short_name};
+use crate::fat::{FAT_BAD, FAT_EOC, is_eoc};
+use crate::{BlockDevice, Error, Fat32};
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+
+/// Mode de fonctionnement du vérificateur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMode {
+    /// Signale les problèmes sans rien écrire.
+    ReportOnly,
+    /// Signale puis corrige les problèmes.
+    Repair,
+}
+
+/// Incohérence détectée sur le volume.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Chaîne allouée dans la FAT mais référencée par aucune entrée.
+    LostChain { start: u32, clusters: u32 },
+    /// Cluster déjà utilisé par une autre chaîne.
+    CrossLinked { path: String, cluster: u32 },
+    /// Chaîne invalide : cluster libre, hors limites, défectueux ou boucle.
+    BadChain { path: String, cluster: u32 },
+    /// Taille incohérente avec la longueur de la chaîne de clusters.
+    SizeMismatch {
+        path: String,
+        size: u32,
+        clusters: u32,
+    },
+    /// Entrée `.` ou `..` absente ou pointant au mauvais endroit.
+    BadDotEntry {
+        path: String,
+        name: &'static str,
+        found: Option<u32>,
+        expected: u32,
+    },
+    /// Une copie de la FAT diffère de la FAT active.
+    FatMismatch { copy: u8, entries: u32 },
+    /// Secteur FSInfo illisible (signatures invalides).
+    BadFsInfo,
+    /// Compteur de clusters libres du FSInfo faux.
+    BadFreeCount { stored: u32, actual: u32 },
+    /// Indication de prochain cluster libre hors limites.
+    BadNextFree { stored: u32 },
+    /// Nom 8.3 contenant des caractères interdits.
+    InvalidShortName { path: String },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::LostChain { start, clusters } => {
+                write!(f, "lost chain at cluster {start} ({clusters} clusters)")
+            }
+            Problem::CrossLinked { path, cluster } => {
+                write!(f, "{path}: cross-linked at cluster {cluster}")
+            }
+            Problem::BadChain { path, cluster } => {
+                write!(f, "{path}: invalid cluster chain at cluster {cluster}")
+            }
+            Problem::SizeMismatch {
+                path,
+                size,
+                clusters,
+            } => write!(
+                f,
+                "{path}: size {size} does not match chain of {clusters} clusters"
+            ),
+            Problem::BadDotEntry {
+                path,
+                name,
+                found: Some(found),
+                expected,
+            } => write!(
+                f,
+                "{path}: '{name}' points to cluster {found} instead of {expected}"
+            ),
+            Problem::BadDotEntry { path, name, .. } => {
+                write!(f, "{path}: missing '{name}' entry")
+            }
+            Problem::FatMismatch { copy, entries } => {
+                write!(f, "FAT #{copy} differs from active FAT ({entries} entries)")
+            }
+            Problem::BadFsInfo => write!(f, "FSInfo sector has invalid signatures"),
+            Problem::BadFreeCount { stored, actual } => {
+                write!(f, "FSInfo free count is {stored}, actual {actual}")
+            }
+            Problem::BadNextFree { stored } => {
+                write!(f, "FSInfo next free cluster {stored} is out of range")
+            }
+            Problem::InvalidShortName { path } => write!(f, "{path}: invalid short name"),
+        }
+    }
+}
+
+/// Résultat d'une vérification.
+pub struct CheckReport {
+    pub problems: Vec<Problem>,
+    /// Vrai si des corrections ont été écrites sur le disque.
+    pub repaired: bool,
+}
+
+impl CheckReport {
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// État du parcours : FAT en mémoire et propriétaire de chaque cluster.
+struct Checker<'a, D: BlockDevice> {
+    fs: &'a mut Fat32<D>,
+    repair: bool,
+    /// FAT active (28 bits utiles), tenue à jour pendant les corrections
+    fat: Vec<u32>,
+    /// Identifiant de la chaîne propriétaire de chaque cluster (0 = aucune)
+    owner: Vec<u32>,
+    next_chain_id: u32,
+    cluster_bytes: u32,
+    problems: Vec<Problem>,
+}
+
+/// Répertoire à visiter : chaîne validée, chemin et cluster attendu pour `..`.
+struct PendingDir {
+    chain: Vec<u32>,
+    path: String,
+    is_root: bool,
+    parent: u32,
+}
+
+fn slot_cluster(bytes: &[u8; 32]) -> u32 {
+    let high = u16::from_le_bytes([bytes[20], bytes[21]]) as u32;
+    let low = u16::from_le_bytes([bytes[26], bytes[27]]) as u32;
+    (high << 16) | low
+}
+
+fn set_slot_cluster(bytes: &mut [u8; 32], cluster: u32) {
+    bytes[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+    bytes[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+}
+
+fn slot_size(bytes: &[u8; 32]) -> u32 {
+    u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]])
+}
+
+fn set_slot_size(bytes: &mut [u8; 32], size: u32) {
+    bytes[28..32].copy_from_slice(&size.to_le_bytes());
+}
+
+fn child_path(parent: &str, name: &str) -> String {
+    if parent == "/" {
+        format!("/{name}")
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+impl<D: BlockDevice> Checker<'_, D> {
+    fn max_cluster(&self) -> u32 {
+        self.fat.len() as u32 - 1
+    }
+
+    fn set_fat(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        self.fs.write_fat_entry(cluster, value)?;
+        self.fat[cluster as usize] = value;
+        Ok(())
+    }
+
+    /// Suit une chaîne et retourne sa partie valide, en réservant ses clusters.
+    /// En mode réparation, la chaîne est coupée avant le premier cluster fautif.
+    fn follow_chain(&mut self, path: &str, start: u32) -> Result<Vec<u32>, Error> {
+        let id = self.next_chain_id;
+        self.next_chain_id += 1;
+
+        let max = self.max_cluster();
+        let mut chain: Vec<u32> = Vec::new();
+        let mut current = start;
+
+        loop {
+            let c = current as usize;
+            let problem = if current < 2 || current > max || self.fat[c] == 0 {
+                Some(Problem::BadChain {
+                    path: path.into(),
+                    cluster: current,
+                })
+            } else if self.owner[c] == id {
+                // On revient sur un cluster de la même chaîne → boucle
+                Some(Problem::BadChain {
+                    path: path.into(),
+                    cluster: current,
+                })
+            } else if self.owner[c] != 0 {
+                Some(Problem::CrossLinked {
+                    path: path.into(),
+                    cluster: current,
+                })
+            } else {
+                None
+            };
+
+            if let Some(problem) = problem {
+                self.problems.push(problem);
+                if let (true, Some(&last)) = (self.repair, chain.last()) {
+                    self.set_fat(last, FAT_EOC)?;
+                }
+                break;
+            }
+
+            self.owner[c] = id;
+            chain.push(current);
+
+            let next = self.fat[c];
+            if is_eoc(next) {
+                break;
+            }
+            if next == FAT_BAD || next < 2 || next > max {
+                self.problems.push(Problem::BadChain {
+                    path: path.into(),
+                    cluster: current,
+                });
+                if self.repair {
+                    self.set_fat(current, FAT_EOC)?;
+                }
+                break;
+            }
+            current = next;
+        }
+
+        Ok(chain)
+    }
+
+    /// Libère la fin d'une chaîne à partir de l'indice `keep`.
+    fn truncate_chain(&mut self, chain: &[u32], keep: usize) -> Result<(), Error> {
+        if keep > 0 {
+            self.set_fat(chain[keep - 1], FAT_EOC)?;
+        }
+        for &cluster in &chain[keep..] {
+            self.set_fat(cluster, 0)?;
+            self.owner[cluster as usize] = 0;
+        }
+        Ok(())
+    }
+
+    fn check_dot_entries(&mut self, dir: &PendingDir, slots: &mut [RawSlot]) -> Result<(), Error> {
+        let expected = [
+            (".", *b".          ", dir.chain[0]),
+            ("..", *b"..         ", dir.parent),
+        ];
+
+        for (i, (name, raw, expected)) in expected.into_iter().enumerate() {
+            let Some(slot) = slots.get_mut(i) else {
+                self.problems.push(Problem::BadDotEntry {
+                    path: dir.path.clone(),
+                    name,
+                    found: None,
+                    expected,
+                });
+                continue;
+            };
+
+            if slot.bytes[0..11] != raw || slot.bytes[11] & 0x10 == 0 {
+                self.problems.push(Problem::BadDotEntry {
+                    path: dir.path.clone(),
+                    name,
+                    found: None,
+                    expected,
+                });
+                continue;
+            }
+
+            let found = slot_cluster(&slot.bytes);
+            // `..` vers la racine vaut 0, mais certains outils y mettent root_cluster
+            let root_alias = name == ".." && expected == 0 && found == self.fs.boot.root_cluster;
+            if found != expected && !root_alias {
+                self.problems.push(Problem::BadDotEntry {
+                    path: dir.path.clone(),
+                    name,
+                    found: Some(found),
+                    expected,
+                });
+                if self.repair {
+                    set_slot_cluster(&mut slot.bytes, expected);
+                    self.fs.write_slot(slot)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_dir(&mut self, dir: PendingDir, pending: &mut Vec<PendingDir>) -> Result<(), Error> {
+        let mut slots = self.fs.read_slots(&dir.chain)?;
+
+        let first = if dir.is_root {
+            0
+        } else {
+            self.check_dot_entries(&dir, &mut slots)?;
+            2
+        };
+
+        for slot in slots.iter_mut().skip(first) {
+            if slot.bytes[0] == 0x00 {
+                break;
+            }
+            let attr = slot.bytes[11];
+            if slot.bytes[0] == 0xE5 || attr == 0x0F || attr & 0x08 != 0 {
+                continue;
+            }
+
+            let original = slot.bytes;
+            let mut raw_name = [0u8; 11];
+            raw_name.copy_from_slice(&slot.bytes[0..11]);
+            if raw_name == *b".          " || raw_name == *b"..         " {
+                continue;
+            }
+
+            let name = crate::dir::parse_short_name(&raw_name);
+            let path = child_path(&dir.path, &name);
+
+            if !is_valid_short_name(&raw_name) {
+                self.problems
+                    .push(Problem::InvalidShortName { path: path.clone() });
+                if self.repair {
+                    for (i, c) in slot.bytes[0..11].iter_mut().enumerate() {
+                        if i == 0 && *c == 0x05 {
+                            continue;
+                        }
+                        *c = c.to_ascii_uppercase();
+                        if *c < 0x20 || b"\"*+,./:;<=>?[\\]|".contains(c) || (i == 0 && *c == b' ')
+                        {
+                            *c = b'_';
+                        }
+                    }
+                }
+            }
+
+            let first_cluster = slot_cluster(&slot.bytes);
+            let size = slot_size(&slot.bytes);
+
+            if attr & 0x10 != 0 {
+                if size != 0 {
+                    self.problems.push(Problem::SizeMismatch {
+                        path: path.clone(),
+                        size,
+                        clusters: 0,
+                    });
+                    set_slot_size(&mut slot.bytes, 0);
+                }
+
+                let chain = if first_cluster == 0 {
+                    self.problems.push(Problem::BadChain {
+                        path: path.clone(),
+                        cluster: 0,
+                    });
+                    Vec::new()
+                } else {
+                    self.follow_chain(&path, first_cluster)?
+                };
+
+                if chain.is_empty() {
+                    // Répertoire sans chaîne utilisable → on supprime l'entrée
+                    slot.bytes[0] = 0xE5;
+                } else {
+                    pending.push(PendingDir {
+                        chain,
+                        path,
+                        is_root: false,
+                        parent: if dir.is_root { 0 } else { dir.chain[0] },
+                    });
+                }
+            } else if first_cluster == 0 {
+                if size != 0 {
+                    self.problems.push(Problem::SizeMismatch {
+                        path,
+                        size,
+                        clusters: 0,
+                    });
+                    set_slot_size(&mut slot.bytes, 0);
+                }
+            } else {
+                let chain = self.follow_chain(&path, first_cluster)?;
+                let expected = size.div_ceil(self.cluster_bytes) as usize;
+
+                if chain.len() != expected {
+                    self.problems.push(Problem::SizeMismatch {
+                        path,
+                        size,
+                        clusters: chain.len() as u32,
+                    });
+                }
+
+                if chain.len() > expected {
+                    // Chaîne trop longue → on la coupe à la taille du fichier
+                    if self.repair {
+                        self.truncate_chain(&chain, expected)?;
+                    }
+                    if expected == 0 {
+                        set_slot_cluster(&mut slot.bytes, 0);
+                    }
+                } else if chain.len() < expected {
+                    // Chaîne trop courte → on réduit la taille
+                    if chain.is_empty() {
+                        set_slot_cluster(&mut slot.bytes, 0);
+                    }
+                    set_slot_size(&mut slot.bytes, chain.len() as u32 * self.cluster_bytes);
+                }
+            }
+
+            if self.repair && slot.bytes != original {
+                self.fs.write_slot(slot)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_fat_copies(&mut self) -> Result<(), Error> {
+        // Sans miroir, les autres copies peuvent légitimement diverger
+        if !self.fs.boot.mirroring_enabled() {
+            return Ok(());
+        }
+
+        let active = self.fs.active_fat() as usize;
+        let diffs = self.fs.diff_fats()?;
+        for copy in (0..self.fs.boot.num_fats).filter(|&c| c as usize != active) {
+            let entries = diffs
+                .iter()
+                .filter(|d| d.values[copy as usize] != d.values[active])
+                .count() as u32;
+            if entries != 0 {
+                self.problems.push(Problem::FatMismatch { copy, entries });
+            }
+        }
+
+        if self.repair && !diffs.is_empty() {
+            self.fs.repair_fats(active as u8)?;
+        }
+        Ok(())
+    }
+
+    fn check_lost_chains(&mut self) -> Result<(), Error> {
+        let max = self.max_cluster();
+        let is_lost = |this: &Self, c: u32| {
+            let v = this.fat[c as usize];
+            v != 0 && v != FAT_BAD && this.owner[c as usize] == 0
+        };
+
+        // Têtes de chaîne : clusters perdus vers lesquels aucun autre ne pointe
+        let mut pointed = vec![false; self.fat.len()];
+        for c in 2..=max {
+            let next = self.fat[c as usize];
+            if is_lost(self, c) && (2..=max).contains(&next) {
+                pointed[next as usize] = true;
+            }
+        }
+
+        let mut visited = vec![false; self.fat.len()];
+        let mut lost = Vec::new();
+        // Deux passes : d'abord les vraies têtes, puis les boucles restantes
+        for heads_only in [true, false] {
+            for c in 2..=max {
+                if visited[c as usize] || !is_lost(self, c) || (heads_only && pointed[c as usize]) {
+                    continue;
+                }
+
+                let mut count = 0;
+                let mut current = c;
+                while (2..=max).contains(&current)
+                    && !visited[current as usize]
+                    && is_lost(self, current)
+                {
+                    visited[current as usize] = true;
+                    lost.push(current);
+                    count += 1;
+                    current = self.fat[current as usize];
+                }
+
+                self.problems.push(Problem::LostChain {
+                    start: c,
+                    clusters: count,
+                });
+            }
+        }
+
+        if self.repair {
+            for cluster in lost {
+                self.set_fat(cluster, 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_fsinfo(&mut self) -> Result<(), Error> {
+        let max = self.max_cluster();
+        let free: Vec<u32> = (2..=max).filter(|&c| self.fat[c as usize] == 0).collect();
+        let actual = free.len() as u32;
+
+        let mut dirty = false;
+        match self.fs.read_fsinfo() {
+            Ok(info) => {
+                if info.free_count != FSINFO_UNKNOWN && info.free_count != actual {
+                    self.problems.push(Problem::BadFreeCount {
+                        stored: info.free_count,
+                        actual,
+                    });
+                    dirty = true;
+                }
+                if info.next_free != FSINFO_UNKNOWN && !(2..=max).contains(&info.next_free) {
+                    self.problems.push(Problem::BadNextFree {
+                        stored: info.next_free,
+                    });
+                    dirty = true;
+                }
+            }
+            Err(Error::InvalidFs) => {
+                self.problems.push(Problem::BadFsInfo);
+                dirty = true;
+            }
+            Err(e) => return Err(e),
+        }
+
+        if self.repair && dirty {
+            let info = FsInfo {
+                free_count: actual,
+                next_free: free.first().copied().unwrap_or(FSINFO_UNKNOWN),
+            };
+            self.fs.write_fsinfo(&info)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Vérifie la cohérence du volume (arborescence, FAT, FSInfo).
+    ///
+    /// En mode `Repair`, chaque problème est corrigé au fil de l'eau :
+    /// chaînes perdues libérées, chaînes invalides ou croisées tronquées,
+    /// tailles ajustées, `.`/`..` réécrits, copies de FAT resynchronisées,
+    /// FSInfo recalculé et caractères interdits remplacés par `_`.
+    pub fn check(&mut self, mode: CheckMode) -> Result<CheckReport, Error> {
+        let repair = mode == CheckMode::Repair;
+        let fat: Vec<u32> = self
+            .read_fat_copy(self.active_fat())?
+            .into_iter()
+            .map(|v| v & 0x0FFF_FFFF)
+            .collect();
+        let cluster_bytes = self.bytes_per_sector() * self.sectors_per_cluster();
+        let root = self.boot.root_cluster;
+
+        let mut checker = Checker {
+            owner: vec![0; fat.len()],
+            fat,
+            fs: self,
+            repair,
+            next_chain_id: 1,
+            cluster_bytes,
+            problems: Vec::new(),
+        };
+
+        checker.check_fat_copies()?;
+
+        let root_chain = checker.follow_chain("/", root)?;
+        if root_chain.is_empty() {
+            return Err(Error::InvalidFs);
+        }
+
+        let mut pending = vec![PendingDir {
+            chain: root_chain,
+            path: "/".into(),
+            is_root: true,
+            parent: 0,
+        }];
+        while let Some(dir) = pending.pop() {
+            checker.check_dir(dir, &mut pending)?;
+        }
+
+        checker.check_lost_chains()?;
+        checker.check_fsinfo()?;
+
+        let problems = checker.problems;
+        if repair {
+            // Volume vérifié : l'indicateur « démonté salement » peut tomber
+            self.mark_checked()?;
+            if !problems.is_empty() {
+                self.flush()?;
+            }
+        }
+        Ok(CheckReport {
+            repaired: repair && !problems.is_empty(),
+            problems,
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Adaptateurs composables autour d'un `BlockDevice` : partition (décalage),
+//! lecture seule, taille de secteur logique différente du physique.
+
+use crate::trace::IoStats;
+use crate::{BlockDevice, Error};
+use alloc::{vec, vec::Vec};
+
+/// Vue d'une zone du périphérique : le secteur `lba` correspond au secteur
+/// `base + lba` de `inner`. Tout accès au-delà de `len` secteurs échoue.
+pub struct OffsetDevice<D: BlockDevice> {
+    pub inner: D,
+    pub base: u64,
+    pub len: u64,
+}
+
+impl<D: BlockDevice> OffsetDevice<D> {
+    pub fn new(inner: D, base: u64, len: u64) -> Self {
+        OffsetDevice { inner, base, len }
+    }
+
+    /// Partition primaire `index` (0 à 3) d'une table MBR. Une entrée vide
+    /// donne `NotFound`, un secteur 0 sans signature 0x55AA `InvalidFs`.
+    pub fn mbr_partition(mut inner: D, index: usize) -> Result<Self, Error> {
+        if index > 3 {
+            return Err(Error::NotFound);
+        }
+        let mut mbr = [0u8; 512];
+        inner.read_sector(0, &mut mbr)?;
+        if mbr[510..512] != [0x55, 0xAA] {
+            return Err(Error::InvalidFs);
+        }
+
+        let entry = &mbr[446 + index * 16..446 + (index + 1) * 16];
+        let u32_at =
+            |i: usize| u32::from_le_bytes([entry[i], entry[i + 1], entry[i + 2], entry[i + 3]]);
+        let (kind, start, count) = (entry[4], u32_at(8), u32_at(12));
+        if kind == 0 || count == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(OffsetDevice::new(inner, start as u64, count as u64))
+    }
+
+    fn translate(&self, lba: u64) -> Result<u64, Error> {
+        if lba >= self.len {
+            return Err(Error::Io);
+        }
+        self.base.checked_add(lba).ok_or(Error::Io)
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for OffsetDevice<D> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let lba = self.translate(lba)?;
+        self.inner.read_sector(lba, buf)
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        let lba = self.translate(lba)?;
+        self.inner.write_sector(lba, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        if lba.checked_add(count).is_none_or(|end| end > self.len) {
+            return Err(Error::Io);
+        }
+        self.inner.discard(self.base + lba, count)
+    }
+
+    fn stats(&self) -> Option<IoStats> {
+        self.inner.stats()
+    }
+}
+
+/// Refuse toute écriture (`Error::ReadOnly`), les lectures sont transmises.
+pub struct ReadOnlyDevice<D: BlockDevice> {
+    pub inner: D,
+}
+
+impl<D: BlockDevice> ReadOnlyDevice<D> {
+    pub fn new(inner: D) -> Self {
+        ReadOnlyDevice { inner }
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for ReadOnlyDevice<D> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        self.inner.read_sector(lba, buf)
+    }
+
+    fn write_sector(&mut self, _lba: u64, _buf: &[u8]) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    fn discard(&mut self, _lba: u64, _count: u64) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+
+    fn stats(&self) -> Option<IoStats> {
+        self.inner.stats()
+    }
+}
+
+/// Présente des secteurs logiques (512 octets en général) au-dessus de
+/// secteurs physiques plus grands (4096 octets, par exemple). Une écriture
+/// partielle d'un secteur physique le relit, le modifie puis le réécrit.
+pub struct SectorSizeAdapter<D: BlockDevice> {
+    pub inner: D,
+    logical: usize,
+    physical: usize,
+    /// Tampon d'un secteur physique
+    buf: Vec<u8>,
+}
+
+impl<D: BlockDevice> SectorSizeAdapter<D> {
+    /// `physical` doit être un multiple non nul de `logical`.
+    pub fn new(inner: D, logical: usize, physical: usize) -> Result<Self, Error> {
+        if logical == 0 || physical == 0 || !physical.is_multiple_of(logical) {
+            return Err(Error::InvalidFs);
+        }
+        Ok(SectorSizeAdapter {
+            inner,
+            logical,
+            physical,
+            buf: vec![0; physical],
+        })
+    }
+
+    /// Secteur physique et position dans celui-ci du secteur logique `lba`.
+    fn locate(&self, lba: u64) -> (u64, usize) {
+        let per_physical = (self.physical / self.logical) as u64;
+        let offset = (lba % per_physical) as usize * self.logical;
+        (lba / per_physical, offset)
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for SectorSizeAdapter<D> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        for (i, chunk) in buf.chunks_mut(self.logical).enumerate() {
+            let (physical, offset) = self.locate(lba + i as u64);
+            self.inner.read_sector(physical, &mut self.buf)?;
+            chunk.copy_from_slice(&self.buf[offset..offset + chunk.len()]);
+        }
+        Ok(())
+    }
+
+    /// Les secteurs logiques sont regroupés par secteur physique : un
+    /// secteur physique entièrement couvert est écrit sans être relu.
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        let mut done = 0;
+        while done < buf.len() {
+            let (physical, offset) = self.locate(lba + (done / self.logical) as u64);
+            let len = (self.physical - offset).min(buf.len() - done);
+            let chunk = &buf[done..done + len];
+            if len == self.physical {
+                self.inner.write_sector(physical, chunk)?;
+            } else {
+                self.inner.read_sector(physical, &mut self.buf)?;
+                self.buf[offset..offset + len].copy_from_slice(chunk);
+                self.inner.write_sector(physical, &self.buf)?;
+            }
+            done += len;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    /// Seuls les secteurs physiques entièrement couverts sont transmis.
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        let per_physical = (self.physical / self.logical) as u64;
+        let first = lba.div_ceil(per_physical);
+        let end = (lba + count) / per_physical;
+        if end > first {
+            self.inner.discard(first, end - first)?;
+        }
+        Ok(())
+    }
+
+    fn stats(&self) -> Option<IoStats> {
+        self.inner.stats()
+    }
+}
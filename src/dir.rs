@@ -1,15 +1,51 @@
+use crate::lfn::LfnBuilder;
+use crate::time::DateTime;
 use crate::{BlockDevice, Error, Fat32};
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::ops::Range;
 use core::str;
 
+pub const ATTR_READ_ONLY: u8 = 0x01;
+pub const ATTR_HIDDEN: u8 = 0x02;
+pub const ATTR_SYSTEM: u8 = 0x04;
+pub const ATTR_VOLUME_ID: u8 = 0x08;
+pub const ATTR_DIRECTORY: u8 = 0x10;
+pub const ATTR_ARCHIVE: u8 = 0x20;
+
+#[derive(Debug, Clone)]
 pub struct DirEntry {
+    /// Nom long (LFN) s'il existe, sinon nom 8.3
     pub name: String,
+    /// Nom 8.3
+    pub short_name: String,
     pub first_cluster: u32,
     pub size: u32,
     pub is_dir: bool,
+    /// Octet d'attributs brut (ATTR_*)
+    pub attr: u8,
+    pub created: Option<DateTime>,
+    pub modified: Option<DateTime>,
+    /// Date de dernier accès (l'heure n'est pas stockée)
+    pub accessed: Option<DateTime>,
+}
+
+impl DirEntry {
+    pub fn is_read_only(&self) -> bool {
+        self.attr & ATTR_READ_ONLY != 0
+    }
+
+    /// Vrai si `name` désigne cette entrée (nom long ou 8.3, sans casse).
+    pub fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) || self.short_name.eq_ignore_ascii_case(name)
+    }
 }
 
-fn parse_short_name(raw: &[u8; 11]) -> String {
+pub(crate) fn parse_short_name(raw: &[u8; 11]) -> String {
     let base = str::from_utf8(&raw[0..8]).unwrap_or("").trim_end();
     let ext = str::from_utf8(&raw[8..11]).unwrap_or("").trim_end();
 
@@ -24,8 +60,102 @@ fn parse_short_name(raw: &[u8; 11]) -> String {
     }
 }
 
+/// Convertit un nom "NOM.EXT" en champ 8.3 brut (majuscules, complété
+/// d'espaces). Retourne `None` si le nom ne tient pas en 8.3 ou contient
+/// des caractères interdits.
+pub(crate) fn encode_short_name(name: &str) -> Option<[u8; 11]> {
+    if name == "." || name == ".." || !name.is_ascii() {
+        return None;
+    }
+
+    let (base, ext) = match name.rsplit_once('.') {
+        Some((base, ext)) => (base, ext),
+        None => (name, ""),
+    };
+    if base.is_empty() || base.len() > 8 || ext.len() > 3 {
+        return None;
+    }
+
+    let mut raw = [b' '; 11];
+    raw[..base.len()].copy_from_slice(base.as_bytes());
+    raw[8..8 + ext.len()].copy_from_slice(ext.as_bytes());
+    raw.make_ascii_uppercase();
+
+    // Pas d'espace ni de point à l'intérieur des deux parties
+    let inner_space = base.contains(' ') || ext.contains(' ') || base.contains('.');
+    if inner_space || !is_valid_short_name(&raw) || raw[0] == 0xE5 {
+        return None;
+    }
+    Some(raw)
+}
+
+/// Entrée brute de 32 octets et son emplacement sur le disque.
+#[derive(Debug, Clone)]
+pub struct RawSlot {
+    /// Secteur contenant l'entrée
+    pub lba: u32,
+    /// Position de l'entrée dans le secteur
+    pub offset: usize,
+    pub bytes: [u8; 32],
+}
+
+impl RawSlot {
+    /// 0x00 : fin du répertoire (les entrées suivantes sont ignorées).
+    pub fn is_end(&self) -> bool {
+        self.bytes[0] == 0x00
+    }
+
+    /// 0xE5 : entrée supprimée.
+    pub fn is_deleted(&self) -> bool {
+        self.bytes[0] == 0xE5
+    }
+
+    /// Attribut 0x0F : morceau de nom long (supprimé ou non).
+    pub fn is_lfn(&self) -> bool {
+        !self.is_end() && self.bytes[11] == 0x0F
+    }
+
+    /// Entrée 8.3 décodée (`None` pour une entrée LFN, libre ou invalide).
+    pub fn entry(&self) -> Option<DirEntry> {
+        parse_entry(&self.bytes)
+    }
+
+    /// Recopie l'entrée à sa place dans le secteur `lba` déjà lu.
+    pub(crate) fn store_in(&self, sector: &mut [u8]) {
+        sector[self.offset..self.offset + 32].copy_from_slice(&self.bytes);
+    }
+}
+
+/// Ajoute à `out` les entrées brutes du secteur de répertoire `lba`.
+pub(crate) fn slots_of_sector(lba: u32, sector: &[u8], out: &mut Vec<RawSlot>) {
+    for (i, raw) in sector.chunks_exact(32).enumerate() {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(raw);
+        out.push(RawSlot {
+            lba,
+            offset: i * 32,
+            bytes,
+        });
+    }
+}
+
+/// Vérifie qu'un nom 8.3 brut ne contient que des caractères autorisés.
+pub(crate) fn is_valid_short_name(raw: &[u8; 11]) -> bool {
+    // 0x05 en tête remplace un vrai 0xE5 (caractère KANJI)
+    if raw[0] == b' ' {
+        return false;
+    }
+
+    raw.iter().enumerate().all(|(i, &c)| {
+        if i == 0 && c == 0x05 {
+            return true;
+        }
+        !(c < 0x20 || c.is_ascii_lowercase() || b"\"*+,./:;<=>?[\\]|".contains(&c))
+    })
+}
+
 /// Parse une entrée de 32 octets (short name, pas de LFN).
-fn parse_entry(entry: &[u8]) -> Option<DirEntry> {
+pub(crate) fn parse_entry(entry: &[u8]) -> Option<DirEntry> {
     if entry.len() < 32 {
         return None;
     }
@@ -42,11 +172,16 @@ fn parse_entry(entry: &[u8]) -> Option<DirEntry> {
         return None;
     }
 
-    // 0x0F dans les attributs → Long File Name (qu’on ignore)
+    // 0x0F dans les attributs → Long File Name (traité par l'appelant)
     if entry[11] == 0x0F {
         return None;
     }
 
+    // 0x08 → nom de volume (pas un vrai fichier)
+    if entry[11] & 0x08 != 0 {
+        return None;
+    }
+
     let mut name_raw = [0u8; 11];
     name_raw.copy_from_slice(&entry[0..11]);
     let name = parse_short_name(&name_raw);
@@ -60,19 +195,116 @@ fn parse_entry(entry: &[u8]) -> Option<DirEntry> {
 
     let size = u32::from_le_bytes([entry[28], entry[29], entry[30], entry[31]]);
 
+    let word = |off: usize| u16::from_le_bytes([entry[off], entry[off + 1]]);
+    let mut created = DateTime::from_dos(word(16), word(14));
+    // Centièmes de seconde de création (0..=199)
+    if let Some(c) = created.as_mut() {
+        c.second = (c.second + entry[13] / 100).min(59);
+    }
+
     Some(DirEntry {
+        short_name: name.clone(),
         name,
         first_cluster,
         size,
         is_dir,
+        attr,
+        created,
+        modified: DateTime::from_dos(word(24), word(22)),
+        accessed: DateTime::from_dos(word(18), 0),
     })
 }
 
+/// Décode le contenu brut d'un répertoire (noms longs compris), jusqu'à
+/// l'entrée de fin.
+pub(crate) fn parse_dir_entries(buf: &[u8]) -> Vec<DirEntry> {
+    let mut entries = Vec::new();
+    let mut lfn = LfnBuilder::default();
+    for entry_bytes in buf.chunks_exact(32) {
+        if entry_bytes[0] == 0x00 {
+            break; // fin des entrées
+        }
+
+        if entry_bytes[0] != 0xE5 && entry_bytes[11] == 0x0F {
+            lfn.push(entry_bytes);
+            continue;
+        }
+
+        match parse_entry(entry_bytes) {
+            Some(mut e) => {
+                let mut raw = [0u8; 11];
+                raw.copy_from_slice(&entry_bytes[0..11]);
+                if let Some(long) = lfn.finish(&raw) {
+                    e.name = long;
+                }
+                entries.push(e);
+            }
+            None => lfn.reset(),
+        }
+    }
+    entries
+}
+
+/// Position des entrées (LFN puis 8.3) de `name` parmi les entrées brutes
+/// d'un répertoire.
+pub(crate) fn find_entry_slots(slots: &[RawSlot], name: &str) -> Option<Range<usize>> {
+    let mut lfn = LfnBuilder::default();
+    let mut lfn_start = 0;
+    for (i, slot) in slots.iter().enumerate() {
+        let bytes = &slot.bytes;
+        if bytes[0] == 0x00 {
+            break;
+        }
+        if bytes[0] != 0xE5 && bytes[11] == 0x0F {
+            if bytes[0] & 0x40 != 0 {
+                lfn_start = i;
+            }
+            lfn.push(bytes);
+            continue;
+        }
+
+        let Some(mut entry) = parse_entry(bytes) else {
+            lfn.reset();
+            continue;
+        };
+        let mut raw = [0u8; 11];
+        raw.copy_from_slice(&bytes[0..11]);
+        let start = match lfn.finish(&raw) {
+            Some(long) => {
+                entry.name = long;
+                lfn_start
+            }
+            None => i,
+        };
+        if entry.matches(name) {
+            return Some(start..i + 1);
+        }
+    }
+    None
+}
+
+/// Normalise un chemin comme le ferait `cd` sur un vrai système :
+/// `path` est interprété depuis `base` (chemin absolu) s'il est relatif,
+/// puis `.`, `..` et les `/` répétés sont résolus lexicalement. `..` à la
+/// racine reste à la racine. Le résultat est absolu (`/` pour la racine).
+pub fn normalize_path(base: &str, path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    let base = if path.starts_with('/') { "" } else { base };
+    for part in base.split('/').chain(path.split('/')) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            name => parts.push(name),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
 impl<D: BlockDevice> Fat32<D> {
     /// Lit toutes les entrées d’un répertoire à partir de son premier cluster.
     pub fn read_dir_cluster(&mut self, first_cluster: u32) -> Result<Vec<DirEntry>, Error> {
-        let mut entries = Vec::new();
-
         let mut chain = alloc::vec::Vec::new();
         self.cluster_chain(first_cluster, &mut chain)?;
 
@@ -93,22 +325,59 @@ impl<D: BlockDevice> Fat32<D> {
             }
         }
 
-        let mut i = 0;
-        while i + 32 <= buf.len() {
-            let entry_bytes = &buf[i..i + 32];
+        Ok(parse_dir_entries(&buf))
+    }
 
-            if entry_bytes[0] == 0x00 {
-                break; // fin des entrées
+    /// Lit toutes les entrées brutes des clusters d'une chaîne déjà validée.
+    pub fn read_slots(&mut self, chain: &[u32]) -> Result<Vec<RawSlot>, Error> {
+        let geometry = self.geometry();
+        let mut slots = Vec::new();
+        let mut buf = vec![0u8; geometry.bytes_per_sector as usize];
+        for &cluster in chain {
+            for lba in geometry.cluster_sectors(cluster) {
+                self.device.read_sector(lba as u64, &mut buf)?;
+                slots_of_sector(lba, &buf, &mut slots);
             }
+        }
+        Ok(slots)
+    }
 
-            if let Some(e) = parse_entry(entry_bytes) {
-                entries.push(e);
-            }
+    /// Réécrit une entrée brute à son emplacement.
+    pub(crate) fn write_slot(&mut self, slot: &RawSlot) -> Result<(), Error> {
+        self.mark_dirty()?;
+        let mut buf = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(slot.lba as u64, &mut buf)?;
+        slot.store_in(&mut buf);
+        self.device.write_sector(slot.lba as u64, &buf)
+    }
 
-            i += 32;
-        }
+    /// Toutes les entrées brutes d'un répertoire (libres, supprimées et LFN
+    /// comprises), dans l'ordre du disque.
+    pub fn read_dir_slots(&mut self, dir_cluster: u32) -> Result<Vec<RawSlot>, Error> {
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain)?;
+        self.read_slots(&chain)
+    }
+
+    /// Entrées brutes d'un chemin : ses entrées LFN puis son entrée 8.3.
+    pub fn entry_slots(&mut self, path: &str) -> Result<Vec<RawSlot>, Error> {
+        let (parent, name) = crate::write::split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        self.find_slots(dir, name)
+    }
 
-        Ok(entries)
+    /// Retrouve les entrées brutes (LFN puis 8.3) de `name` dans un
+    /// répertoire.
+    pub(crate) fn find_slots(
+        &mut self,
+        dir_cluster: u32,
+        name: &str,
+    ) -> Result<Vec<RawSlot>, Error> {
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain)?;
+        let slots = self.read_slots(&chain)?;
+        let range = find_entry_slots(&slots, name).ok_or(Error::NotFound)?;
+        Ok(slots[range].to_vec())
     }
 
     pub fn list_root(&mut self) -> Result<Vec<DirEntry>, Error> {
@@ -138,7 +407,7 @@ impl<D: BlockDevice> Fat32<D> {
                     .find(|e| e.name == "..")
                     .ok_or(Error::NotFound)?;
 
-                current_cluster = parent.first_cluster;
+                current_cluster = self.dir_cluster_of(&parent);
                 last_entry = Some(parent);
                 continue;
             }
@@ -146,7 +415,7 @@ impl<D: BlockDevice> Fat32<D> {
             let entries = self.read_dir_cluster(current_cluster)?;
             let entry = entries
                 .into_iter()
-                .find(|e| e.name.eq_ignore_ascii_case(part))
+                .find(|e| e.matches(part))
                 .ok_or(Error::NotFound)?;
 
             current_cluster = entry.first_cluster;
@@ -171,31 +440,71 @@ impl<D: BlockDevice> Fat32<D> {
         }
     }
 
-    /// Change de répertoire courant (cd).
-    pub fn change_dir(&mut self, path: &str) -> Result<(), Error> {
-        // cd /
+    /// Résout un chemin de répertoire en son premier cluster (`/` → racine).
+    pub(crate) fn dir_cluster(&mut self, path: &str) -> Result<u32, Error> {
         if path == "/" {
-            self.cwd_cluster = self.boot.root_cluster;
-            return Ok(());
+            return Ok(self.boot.root_cluster);
         }
-
-        // cd .. depuis la racine → rester à la racine
-        if path == ".." && self.cwd_cluster == self.boot.root_cluster {
-            return Ok(());
+        if path.is_empty() || path == "." {
+            return Ok(self.cwd_cluster);
         }
 
         let entry = self.resolve_path(path)?;
         if !entry.is_dir {
-            return Err(Error::InvalidFs);
+            return Err(Error::NotDirectory);
         }
+        Ok(self.dir_cluster_of(&entry))
+    }
 
-        // cluster 0 => racine
-        if entry.first_cluster == 0 {
-            self.cwd_cluster = self.boot.root_cluster;
+    /// Premier cluster désigné par une entrée, en tenant compte de `..` vers
+    /// la racine (codé 0).
+    pub fn dir_cluster_of(&self, entry: &DirEntry) -> u32 {
+        if entry.is_dir && entry.first_cluster == 0 {
+            self.boot.root_cluster
         } else {
-            self.cwd_cluster = entry.first_cluster;
+            entry.first_cluster
         }
+    }
 
+    /// Change de répertoire courant (cd). Le chemin est normalisé
+    /// (voir `normalize_path`) avant d'être résolu depuis la racine.
+    pub fn change_dir(&mut self, path: &str) -> Result<(), Error> {
+        let target = if path.starts_with('/') {
+            normalize_path("/", path)
+        } else {
+            normalize_path(&self.current_dir()?, path)
+        };
+        self.cwd_cluster = self.dir_cluster(&target)?;
         Ok(())
     }
+
+    /// Chemin absolu du répertoire courant, reconstruit en remontant les
+    /// entrées `..` et en cherchant dans chaque parent le nom du fils.
+    /// Il reste donc juste après un renommage ou un déplacement.
+    pub fn current_dir(&mut self) -> Result<String, Error> {
+        let root = self.boot.root_cluster;
+        let mut names = Vec::new();
+        let mut cluster = self.cwd_cluster;
+        while cluster != root {
+            // Plus de niveaux que de clusters → boucle de `..`
+            if names.len() > self.cluster_count() as usize {
+                return Err(Error::InvalidFs);
+            }
+            let parent = self
+                .read_dir_cluster(cluster)?
+                .into_iter()
+                .find(|e| e.name == "..")
+                .ok_or(Error::InvalidFs)?;
+            let parent = self.dir_cluster_of(&parent);
+            let child = self
+                .read_dir_cluster(parent)?
+                .into_iter()
+                .find(|e| e.is_dir && e.first_cluster == cluster && e.name != ".")
+                .ok_or(Error::InvalidFs)?;
+            names.push(child.name);
+            cluster = parent;
+        }
+        names.reverse();
+        Ok(format!("/{}", names.join("/")))
+    }
 }
@@ -0,0 +1,121 @@
+//! Indicateur d'arrêt propre du volume : bit 27 de FAT[1] (1 = propre) et
+//! bit 0 de l'octet 0x41 du BPB (1 = sale, convention Windows / Linux).
+//!
+//! Le volume est marqué sale à la première écriture et redevient propre
+//! au démontage (`unmount`). Un volume trouvé sale au montage n'a pas été
+//! démonté proprement : `was_dirty` le signale pour lancer fsck d'abord.
+
+use crate::boot::FsInfo;
+use crate::{BlockDevice, Error, Fat32};
+use alloc::vec;
+
+/// Bit « arrêt propre » de FAT[1].
+pub const FAT_CLEAN_SHUTDOWN: u32 = 0x0800_0000;
+/// Bit « aucune erreur d'E/S » de FAT[1] (lu par les autres outils, pas géré ici).
+pub const FAT_NO_IO_ERROR: u32 = 0x0400_0000;
+/// Octet d'état du BPB FAT32 (BS_Reserved1) et son bit « sale ».
+pub const BPB_STATE_OFFSET: usize = 0x41;
+pub const BPB_STATE_DIRTY: u8 = 0x01;
+
+/// FAT[1] avec le bit « arrêt propre » réglé selon `dirty`.
+pub(crate) fn fat1_with_state(fat1: u32, dirty: bool) -> u32 {
+    if dirty {
+        fat1 & !FAT_CLEAN_SHUTDOWN
+    } else {
+        fat1 | FAT_CLEAN_SHUTDOWN
+    }
+}
+
+/// Règle le bit « sale » de l'octet d'état d'un boot sector.
+pub(crate) fn set_bpb_state(boot: &mut [u8], dirty: bool) {
+    if dirty {
+        boot[BPB_STATE_OFFSET] |= BPB_STATE_DIRTY;
+    } else {
+        boot[BPB_STATE_OFFSET] &= !BPB_STATE_DIRTY;
+    }
+}
+
+/// Sale si FAT[1] ou le boot sector principal (quand il est consulté)
+/// l'indique.
+pub(crate) fn dirty_on_disk(fat1: u32, boot: Option<&[u8]>) -> bool {
+    fat1 & FAT_CLEAN_SHUTDOWN == 0
+        || boot.is_some_and(|b| b[BPB_STATE_OFFSET] & BPB_STATE_DIRTY != 0)
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Lit l'indicateur sur le disque : sale si l'un des deux l'indique.
+    /// Monté depuis le boot sector de secours, seul FAT[1] est consulté.
+    pub fn is_dirty_on_disk(&mut self) -> Result<bool, Error> {
+        let fat1 = self.read_fat_entry(1)?;
+        if self.boot_from_backup {
+            return Ok(dirty_on_disk(fat1, None));
+        }
+        let mut boot = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(0, &mut boot)?;
+        Ok(dirty_on_disk(fat1, Some(&boot)))
+    }
+
+    /// Écrit l'indicateur dans FAT[1] et dans le boot sector principal.
+    fn write_dirty_marks(&mut self, dirty: bool) -> Result<(), Error> {
+        let fat1 = self.read_fat_entry(1)?;
+        self.store_fat_entry(1, fat1_with_state(fat1, dirty))?;
+
+        // Secteur 0 endommagé : on n'y écrit pas
+        if self.boot_from_backup {
+            return self.device.flush();
+        }
+        let mut boot = vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(0, &mut boot)?;
+        set_bpb_state(&mut boot, dirty);
+        self.device.write_sector(0, &boot)?;
+        self.device.flush()
+    }
+
+    /// Marque le volume sale avant sa première modification. L'indicateur
+    /// est rendu durable avant toute autre écriture.
+    pub(crate) fn mark_dirty(&mut self) -> Result<(), Error> {
+        if self.dirty {
+            return Ok(());
+        }
+        if !self.was_dirty {
+            self.write_dirty_marks(true)?;
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Le volume vient d'être vérifié : il n'est plus « démonté
+    /// salement ». Sans écriture en cours, l'indicateur est effacé tout
+    /// de suite, sinon au démontage.
+    pub(crate) fn mark_checked(&mut self) -> Result<(), Error> {
+        if self.was_dirty {
+            self.was_dirty = false;
+            if !self.dirty {
+                self.write_dirty_marks(false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Démonte le volume et rend le périphérique.
+    ///
+    /// Si le volume a été modifié : FSInfo est recalculé (clusters libres,
+    /// prochain libre), l'indicateur « sale » est effacé (sauf s'il l'était
+    /// déjà au montage : fsck doit passer d'abord) et tout est vidé sur le
+    /// support. Sans appel à `unmount`, le volume reste marqué sale, comme
+    /// après une coupure de courant.
+    pub fn unmount(mut self) -> Result<D, Error> {
+        if self.dirty {
+            let info = FsInfo::from_fat(&self.read_fat_copy(self.active_fat())?);
+            // Sans FSInfo lisible, rien à mettre à jour
+            if self.read_fsinfo().is_ok() {
+                self.write_fsinfo(&info)?;
+            }
+            if !self.was_dirty {
+                self.write_dirty_marks(false)?;
+            }
+        }
+        self.device.flush()?;
+        Ok(self.device)
+    }
+}
@@ -0,0 +1,134 @@
+//! Intégration aux écosystèmes embarqués (feature `embedded`) :
+//! - `embedded_io::{Read, Write, Seek}` pour `file::File` ;
+//! - `BlockDevice` sur une zone `embedded_storage` (flash NOR / NAND).
+
+use crate::file::File;
+use crate::{BlockDevice, Error};
+use embedded_io::{ErrorKind, SeekFrom};
+use embedded_storage::{ReadStorage, Storage};
+
+impl embedded_io::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::NotFound => ErrorKind::NotFound,
+            Error::AlreadyExists => ErrorKind::AlreadyExists,
+            Error::InvalidName | Error::InvalidSeek | Error::IsDirectory | Error::NotDirectory => {
+                ErrorKind::InvalidInput
+            }
+            Error::InvalidFs | Error::NotRecoverable => ErrorKind::InvalidData,
+            Error::NoSpace => ErrorKind::OutOfMemory,
+            Error::ReadOnly => ErrorKind::PermissionDenied,
+            Error::Io | Error::NotEmpty => ErrorKind::Other,
+        }
+    }
+}
+
+impl<D: BlockDevice> embedded_io::ErrorType for File<'_, D> {
+    type Error = Error;
+}
+
+impl<D: BlockDevice> embedded_io::Read for File<'_, D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        File::read(self, buf)
+    }
+}
+
+impl<D: BlockDevice> embedded_io::Write for File<'_, D> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        File::write(self, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        File::flush(self)
+    }
+}
+
+impl<D: BlockDevice> embedded_io::Seek for File<'_, D> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => (self.size() as u64).checked_add_signed(n),
+            SeekFrom::Current(n) => self.position().checked_add_signed(n),
+        };
+        let target = target.ok_or(Error::InvalidSeek)?;
+        self.set_position(target);
+        Ok(target)
+    }
+}
+
+/// Vérifie qu'un accès de `len` octets au secteur `lba` tient dans la zone
+/// et renvoie son adresse.
+fn storage_offset(lba: u64, len: usize, sector_size: usize, capacity: usize) -> Result<u32, Error> {
+    let start = lba.checked_mul(sector_size as u64).ok_or(Error::Io)?;
+    let end = start.checked_add(len as u64).ok_or(Error::Io)?;
+    if !len.is_multiple_of(sector_size) || end > capacity as u64 {
+        return Err(Error::Io);
+    }
+    u32::try_from(start).map_err(|_| Error::Io)
+}
+
+/// `BlockDevice` sur une zone `embedded_storage::Storage` : le secteur `n`
+/// est à l'adresse `n * sector_size`. L'effacement des pages est à la
+/// charge de l'implémentation `Storage` (voir `RmwNorFlashStorage`).
+pub struct StorageDevice<S: Storage> {
+    pub storage: S,
+    pub sector_size: usize,
+}
+
+impl<S: Storage> StorageDevice<S> {
+    pub fn new(storage: S, sector_size: usize) -> Self {
+        StorageDevice {
+            storage,
+            sector_size,
+        }
+    }
+
+    /// Nombre de secteurs complets de la zone.
+    pub fn sector_count(&self) -> u64 {
+        (self.storage.capacity() / self.sector_size) as u64
+    }
+}
+
+impl<S: Storage> BlockDevice for StorageDevice<S> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let offset = storage_offset(lba, buf.len(), self.sector_size, self.storage.capacity())?;
+        self.storage.read(offset, buf).map_err(|_| Error::Io)
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        let offset = storage_offset(lba, buf.len(), self.sector_size, self.storage.capacity())?;
+        self.storage.write(offset, buf).map_err(|_| Error::Io)
+    }
+}
+
+/// `BlockDevice` en lecture seule sur une zone `embedded_storage::ReadStorage`
+/// (image en flash interne, par exemple) : toute écriture renvoie
+/// `Error::ReadOnly`.
+pub struct ReadStorageDevice<S: ReadStorage> {
+    pub storage: S,
+    pub sector_size: usize,
+}
+
+impl<S: ReadStorage> ReadStorageDevice<S> {
+    pub fn new(storage: S, sector_size: usize) -> Self {
+        ReadStorageDevice {
+            storage,
+            sector_size,
+        }
+    }
+}
+
+impl<S: ReadStorage> BlockDevice for ReadStorageDevice<S> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let offset = storage_offset(lba, buf.len(), self.sector_size, self.storage.capacity())?;
+        self.storage.read(offset, buf).map_err(|_| Error::Io)
+    }
+
+    fn write_sector(&mut self, _lba: u64, _buf: &[u8]) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+
+    fn discard(&mut self, _lba: u64, _count: u64) -> Result<(), Error> {
+        Err(Error::ReadOnly)
+    }
+}
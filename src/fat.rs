@@ -1,38 +1,276 @@
+use crate::dirty::{FAT_CLEAN_SHUTDOWN, FAT_NO_IO_ERROR};
 use crate::{BlockDevice, Error, Fat32};
 
+/// Marqueur de fin de chaîne écrit par le driver.
+pub const FAT_EOC: u32 = 0x0FFF_FFFF;
+
+/// Marqueur de cluster défectueux.
+pub const FAT_BAD: u32 = 0x0FFF_FFF7;
+
 pub struct Fat<'a, D: BlockDevice> {
     pub fs: &'a mut Fat32<D>,
 }
 
+/// Entrée de FAT dont la valeur diffère selon les copies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FatDiff {
+    pub cluster: u32,
+    /// Valeur (28 bits) dans chaque copie, dans l'ordre des FAT
+    pub values: alloc::vec::Vec<u32>,
+}
+
+/// Nombre de fragments (suites de clusters contigus) d'une chaîne.
+pub fn fragment_count(chain: &[u32]) -> u32 {
+    if chain.is_empty() {
+        return 0;
+    }
+    1 + chain.windows(2).filter(|w| w[1] != w[0] + 1).count() as u32
+}
+
+/// Ordre de recherche des clusters libres : de l'indication `next_free`
+/// du FSInfo (2 si absente ou hors du volume) jusqu'au dernier cluster
+/// `max`, puis de 2 jusqu'à l'indication. Chaque cluster est vu une fois.
+pub(crate) fn alloc_scan_order(next_free: Option<u32>, max: u32) -> impl Iterator<Item = u32> {
+    let hint = next_free.filter(|n| (2..=max).contains(n)).unwrap_or(2);
+    (hint..=max).chain(2..hint)
+}
+
+/// Indication de prochain cluster libre après l'allocation de `last`.
+pub(crate) fn next_free_after(last: u32, max: u32) -> u32 {
+    if last == max { 2 } else { last + 1 }
+}
+
+/// Valeur brute (32 bits) de l'entrée de FAT à `offset` dans un secteur.
+pub(crate) fn fat_raw_at(sector: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        sector[offset],
+        sector[offset + 1],
+        sector[offset + 2],
+        sector[offset + 3],
+    ])
+}
+
+/// Écrit une entrée de FAT dans un secteur ; les 4 bits de poids fort
+/// (réservés) sont conservés.
+pub(crate) fn put_fat_entry(sector: &mut [u8], offset: usize, value: u32) {
+    let old = fat_raw_at(sector, offset);
+    let new = (old & 0xF000_0000) | (value & 0x0FFF_FFFF);
+    sector[offset..offset + 4].copy_from_slice(&new.to_le_bytes());
+}
+
+/// Ajoute à `out` les entrées d'un secteur de FAT, sans dépasser `entries`.
+pub(crate) fn decode_fat_sector(sector: &[u8], out: &mut alloc::vec::Vec<u32>, entries: usize) {
+    for raw in sector.chunks_exact(4) {
+        if out.len() == entries {
+            break;
+        }
+        out.push(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]));
+    }
+}
+
+/// Fin de chaîne (End Of Chain).
+pub(crate) fn is_eoc(value: u32) -> bool {
+    value >= 0x0FFF_FFF8
+}
+
+/// Entrées de FAT (cluster, suivant) qui chaînent des clusters alloués
+/// dans l'ordre, le dernier marqué fin de chaîne.
+pub(crate) fn chain_links(clusters: &[u32]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    clusters
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (c, clusters.get(i + 1).copied().unwrap_or(FAT_EOC)))
+}
+
+/// Suites de clusters contigus d'une chaîne : (premier cluster, longueur).
+pub(crate) fn contiguous_runs(chain: &[u32]) -> impl Iterator<Item = (u32, usize)> + '_ {
+    let mut i = 0;
+    core::iter::from_fn(move || {
+        let first = *chain.get(i)?;
+        let run = chain[i..]
+            .iter()
+            .zip(first..)
+            .take_while(|(c, expected)| **c == *expected)
+            .count();
+        i += run;
+        Some((first, run))
+    })
+}
+
 impl<D: BlockDevice> Fat32<D> {
+    /// FAT lue par le driver : la première si le miroir est actif,
+    /// sinon celle désignée par les ext_flags du BPB.
+    pub fn active_fat(&self) -> u8 {
+        self.geometry().active_fat()
+    }
+
+    /// LBA de début d'une copie de la FAT.
+    pub fn fat_copy_lba(&self, copy: u8) -> u32 {
+        self.geometry().fat_copy_lba(copy)
+    }
+
     /// Lit une entrée de FAT32 (numéro de cluster → valeur FAT brute).
     pub fn read_fat_entry(&mut self, cluster: u32) -> Result<u32, Error> {
-        let bytes_per_sector = self.boot.bytes_per_sector as u32;
+        let geometry = self.geometry();
+        let (lba, offset) = geometry.fat_entry_pos(geometry.active_fat(), cluster);
+        let mut buf = alloc::vec![0u8; geometry.bytes_per_sector as usize];
+        self.device.read_sector(lba, &mut buf)?;
+
+        // FAT32 utilise 28 bits significatifs
+        Ok(fat_raw_at(&buf, offset) & 0x0FFF_FFFF)
+    }
+
+    /// Écrit une entrée de FAT dans toutes les copies de la FAT, ou dans
+    /// la seule FAT active si le miroir est désactivé.
+    /// Les 4 bits de poids fort (réservés) sont conservés.
+    pub fn write_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        self.mark_dirty()?;
+        self.store_fat_entry(cluster, value)
+    }
 
-        // Offset en octets de l'entrée dans la FAT
-        let fat_offset = cluster * 4;
-        let sector = self.fat_start_lba + (fat_offset / bytes_per_sector);
-        let offset_in_sector = (fat_offset % bytes_per_sector) as usize;
+    /// `write_fat_entry` sans marquer le volume sale.
+    pub(crate) fn store_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), Error> {
+        let geometry = self.geometry();
+        let mut buf = alloc::vec![0u8; geometry.bytes_per_sector as usize];
+        for copy in geometry.written_fats() {
+            let (lba, offset) = geometry.fat_entry_pos(copy, cluster);
+            self.device.read_sector(lba, &mut buf)?;
+            put_fat_entry(&mut buf, offset, value);
+            self.device.write_sector(lba, &buf)?;
+        }
+        Ok(())
+    }
 
-        // On suppose des secteurs de 512 octets pour l'image de test
-        let mut buf = [0u8; 512];
-        self.device.read_sector(sector as u64, &mut buf)?;
+    /// Lit une copie complète de la FAT (valeurs brutes sur 32 bits,
+    /// une par cluster, y compris les entrées réservées 0 et 1).
+    pub fn read_fat_copy(&mut self, copy: u8) -> Result<alloc::vec::Vec<u32>, Error> {
+        let geometry = self.geometry();
+        let entries = (geometry.cluster_count() + 2) as usize;
 
-        let entry_bytes = &buf[offset_in_sector..offset_in_sector + 4];
-        let raw = u32::from_le_bytes([
-            entry_bytes[0],
-            entry_bytes[1],
-            entry_bytes[2],
-            entry_bytes[3],
-        ]);
+        let mut out = alloc::vec::Vec::with_capacity(entries);
+        let mut buf = alloc::vec![0u8; geometry.bytes_per_sector as usize];
+        let mut lba = geometry.fat_copy_lba(copy);
+        while out.len() < entries {
+            self.device.read_sector(lba as u64, &mut buf)?;
+            decode_fat_sector(&buf, &mut out, entries);
+            lba += 1;
+        }
+        Ok(out)
+    }
 
-        // FAT32 utilise 28 bits significatifs
-        Ok(raw & 0x0FFF_FFFF)
+    /// Compare toutes les copies de la FAT, entrée par entrée.
+    pub fn diff_fats(&mut self) -> Result<alloc::vec::Vec<FatDiff>, Error> {
+        let mut copies = alloc::vec::Vec::new();
+        for copy in 0..self.boot.num_fats {
+            copies.push(self.read_fat_copy(copy)?);
+        }
+
+        let mut diffs = alloc::vec::Vec::new();
+        let Some(first) = copies.first() else {
+            return Ok(diffs);
+        };
+        for cluster in 0..first.len() {
+            // Les bits d'état de FAT[1] ne sont tenus que dans la FAT active
+            // quand le miroir est désactivé : ils ne comptent pas.
+            let mask = match cluster {
+                1 => 0x0FFF_FFFF & !(FAT_CLEAN_SHUTDOWN | FAT_NO_IO_ERROR),
+                _ => 0x0FFF_FFFF,
+            };
+            let values: alloc::vec::Vec<u32> = copies.iter().map(|c| c[cluster] & mask).collect();
+            if values.iter().any(|&v| v != values[0]) {
+                diffs.push(FatDiff {
+                    cluster: cluster as u32,
+                    values,
+                });
+            }
+        }
+        Ok(diffs)
+    }
+
+    /// Recopie la FAT `good` (supposée saine) sur toutes les autres copies.
+    pub fn repair_fats(&mut self, good: u8) -> Result<(), Error> {
+        if good >= self.boot.num_fats {
+            return Err(Error::InvalidFs);
+        }
+        self.mark_dirty()?;
+
+        let mut buf = alloc::vec![0u8; self.boot.bytes_per_sector as usize];
+        for i in 0..self.boot.sectors_per_fat {
+            let src = self.fat_copy_lba(good) + i;
+            self.device.read_sector(src as u64, &mut buf)?;
+            for copy in (0..self.boot.num_fats).filter(|&c| c != good) {
+                let dst = self.fat_copy_lba(copy) + i;
+                self.device.write_sector(dst as u64, &buf)?;
+            }
+        }
+        self.flush()
+    }
+
+    /// Compte les clusters libres en parcourant la FAT active.
+    pub fn free_cluster_count(&mut self) -> Result<u32, Error> {
+        let fat = self.read_fat_copy(self.active_fat())?;
+        Ok(fat
+            .iter()
+            .skip(2)
+            .filter(|&&v| v & 0x0FFF_FFFF == 0)
+            .count() as u32)
     }
 
-    fn is_eoc(cluster: u32) -> bool {
-        // End Of Chain
-        cluster >= 0x0FFF_FFF8
+    /// Alloue `count` clusters libres et les chaîne entre eux (le dernier
+    /// est marqué fin de chaîne). La recherche part de l'indication du
+    /// FSInfo. Rien n'est écrit s'il n'y a pas assez de place.
+    pub fn alloc_clusters(&mut self, count: u32) -> Result<alloc::vec::Vec<u32>, Error> {
+        let mut found = alloc::vec::Vec::new();
+        if count == 0 {
+            return Ok(found);
+        }
+
+        let max = self.cluster_count() + 1;
+        let hint = self.read_fsinfo().ok().map(|info| info.next_free);
+        for cluster in alloc_scan_order(hint, max) {
+            if self.read_fat_entry(cluster)? == 0 {
+                found.push(cluster);
+                if found.len() == count as usize {
+                    break;
+                }
+            }
+        }
+        if found.len() < count as usize {
+            return Err(Error::NoSpace);
+        }
+
+        for (c, next) in chain_links(&found) {
+            self.write_fat_entry(c, next)?;
+        }
+
+        let next_free = next_free_after(found[found.len() - 1], max);
+        self.fsinfo_update(-(count as i64), Some(next_free))?;
+        Ok(found)
+    }
+
+    /// Libère toute une chaîne à partir de `start`. Avec
+    /// `MountOptions::discard`, ses clusters sont ensuite signalés au
+    /// périphérique, par plages contiguës.
+    pub fn free_chain(&mut self, start: u32) -> Result<u32, Error> {
+        if start < 2 {
+            return Ok(0);
+        }
+        let mut chain = alloc::vec::Vec::new();
+        self.cluster_chain(start, &mut chain)?;
+        for &c in &chain {
+            self.write_fat_entry(c, 0)?;
+        }
+        self.fsinfo_update(chain.len() as i64, None)?;
+
+        if self.discard {
+            let spc = self.sectors_per_cluster() as u64;
+            for (first, run) in contiguous_runs(&chain) {
+                let lba = self.cluster_to_lba(first) as u64;
+                // Simple indication : un échec ne remet pas la libération en cause
+                let _ = self.device.discard(lba, run as u64 * spc);
+            }
+        }
+        Ok(chain.len() as u32)
     }
 
     /// Remplit `out` avec la chaîne de clusters à partir de `start`.
@@ -42,10 +280,15 @@ impl<D: BlockDevice> Fat32<D> {
         out: &mut alloc::vec::Vec<u32>,
     ) -> Result<(), Error> {
         let mut current = start;
+        let max_len = self.cluster_count() as usize + 2;
         loop {
+            // Chaîne plus longue que le volume → boucle dans la FAT
+            if out.len() >= max_len {
+                return Err(Error::InvalidFs);
+            }
             out.push(current);
             let next = self.read_fat_entry(current)?;
-            if Self::is_eoc(next) {
+            if is_eoc(next) {
                 break;
             }
             current = next;
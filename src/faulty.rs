@@ -0,0 +1,244 @@
+//! Injection de pannes pour les tests de robustesse : échecs, écritures
+//! déchirées, corruption silencieuse et coupure d'alimentation, déclenchés
+//! à des points scriptés (N-ième écriture, plage de LBA).
+
+use crate::trace::IoStats;
+use crate::{BlockDevice, Error};
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// Moment où une règle se déclenche.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// N-ième appel à `write_sector` (à partir de 1), une seule fois.
+    NthWrite(u64),
+    /// N-ième appel à `read_sector` (à partir de 1), une seule fois.
+    NthRead(u64),
+    /// Toute écriture touchant un secteur de la plage.
+    WriteRange(Range<u64>),
+    /// Toute lecture touchant un secteur de la plage.
+    ReadRange(Range<u64>),
+}
+
+/// Effet d'une règle déclenchée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// L'accès échoue (`Error::Io`) sans rien modifier.
+    Fail,
+    /// Écriture interrompue : seuls les `bytes` premiers octets atteignent
+    /// le support, puis l'alimentation est coupée.
+    Tear { bytes: usize },
+    /// Les données lues ou écrites sont altérées sans erreur : l'octet
+    /// `offset` (modulo la taille du tampon) est combiné avec `xor`.
+    Corrupt { offset: usize, xor: u8 },
+    /// Coupure d'alimentation : l'accès en cours et les écritures non
+    /// vidées sont perdus, tout accès suivant échoue.
+    PowerCut,
+}
+
+/// Panne scriptée : `fault` s'applique quand `trigger` est atteint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultRule {
+    pub trigger: Trigger,
+    pub fault: Fault,
+}
+
+/// Périphérique qui applique des pannes scriptées à `inner`.
+///
+/// Avec un cache d'écriture (`with_write_cache`), les écritures restent en
+/// mémoire jusqu'à `BlockDevice::flush` ; une coupure d'alimentation les fait perdre,
+/// comme sur une carte SD ou une clé USB débranchée.
+pub struct FaultyDevice<D: BlockDevice> {
+    pub inner: D,
+    pub sector_size: usize,
+    rules: Vec<FaultRule>,
+    /// Écritures non vidées, par secteur (`None` = pas de cache)
+    cache: Option<BTreeMap<u64, Vec<u8>>>,
+    powered: bool,
+    /// Nombre d'appels à `read_sector` / `write_sector` depuis la création
+    pub reads: u64,
+    pub writes: u64,
+    /// Nombre de règles déclenchées
+    pub triggered: u64,
+}
+
+impl<D: BlockDevice> FaultyDevice<D> {
+    pub fn new(inner: D, sector_size: usize) -> Self {
+        FaultyDevice {
+            inner,
+            sector_size,
+            rules: Vec::new(),
+            cache: None,
+            powered: true,
+            reads: 0,
+            writes: 0,
+            triggered: 0,
+        }
+    }
+
+    /// Active le cache d'écriture volatil.
+    pub fn with_write_cache(mut self) -> Self {
+        self.cache = Some(BTreeMap::new());
+        self
+    }
+
+    /// Ajoute une panne scriptée.
+    pub fn inject(&mut self, trigger: Trigger, fault: Fault) {
+        self.rules.push(FaultRule { trigger, fault });
+    }
+
+    /// Retire toutes les pannes scriptées.
+    pub fn clear_faults(&mut self) {
+        self.rules.clear();
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Coupe l'alimentation : les écritures non vidées sont perdues.
+    pub fn power_cut(&mut self) {
+        self.powered = false;
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Rétablit l'alimentation (les pannes scriptées restent actives).
+    pub fn restore_power(&mut self) {
+        self.powered = true;
+    }
+
+    /// Nombre de secteurs en cache, pas encore vidés.
+    pub fn pending(&self) -> usize {
+        self.cache.as_ref().map_or(0, BTreeMap::len)
+    }
+
+    /// Rend le périphérique sous-jacent, sans vider le cache.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    /// Première panne déclenchée par cet accès (les règles `Nth*` ne
+    /// servent qu'une fois).
+    fn fault_for(&mut self, write: bool, lba: u64, len: usize) -> Option<Fault> {
+        let count = if write { self.writes } else { self.reads };
+        let sectors = len.div_ceil(self.sector_size.max(1)) as u64;
+        let hit = |trigger: &Trigger| match trigger {
+            Trigger::NthWrite(n) => write && *n == count,
+            Trigger::NthRead(n) => !write && *n == count,
+            Trigger::WriteRange(r) => write && lba < r.end && r.start < lba + sectors,
+            Trigger::ReadRange(r) => !write && lba < r.end && r.start < lba + sectors,
+        };
+        let fault = self.rules.iter().find(|r| hit(&r.trigger))?.fault;
+        self.triggered += 1;
+        Some(fault)
+    }
+
+    /// Écriture effective : dans le cache s'il existe, sinon sur `inner`.
+    fn store(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        let size = self.sector_size;
+        match &mut self.cache {
+            Some(cache) => {
+                for (i, sector) in buf.chunks(size).enumerate() {
+                    cache.insert(lba + i as u64, sector.to_vec());
+                }
+                Ok(())
+            }
+            None => self.inner.write_sector(lba, buf),
+        }
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for FaultyDevice<D> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        if !self.powered {
+            return Err(Error::Io);
+        }
+        self.reads += 1;
+        let fault = self.fault_for(false, lba, buf.len());
+        match fault {
+            Some(Fault::Fail) => return Err(Error::Io),
+            Some(Fault::PowerCut | Fault::Tear { .. }) => {
+                self.power_cut();
+                return Err(Error::Io);
+            }
+            _ => {}
+        }
+
+        self.inner.read_sector(lba, buf)?;
+        if let Some(cache) = &self.cache {
+            for (i, sector) in buf.chunks_mut(self.sector_size).enumerate() {
+                if let Some(data) = cache.get(&(lba + i as u64)) {
+                    sector.copy_from_slice(&data[..sector.len()]);
+                }
+            }
+        }
+        if let (Some(Fault::Corrupt { offset, xor }), false) = (fault, buf.is_empty()) {
+            buf[offset % buf.len()] ^= xor;
+        }
+        Ok(())
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        if !self.powered {
+            return Err(Error::Io);
+        }
+        self.writes += 1;
+        match self.fault_for(true, lba, buf.len()) {
+            None => self.store(lba, buf),
+            Some(Fault::Fail) => Err(Error::Io),
+            Some(Fault::Corrupt { offset, xor }) => {
+                let mut data = buf.to_vec();
+                if !data.is_empty() {
+                    let i = offset % data.len();
+                    data[i] ^= xor;
+                }
+                self.store(lba, &data)
+            }
+            Some(Fault::Tear { bytes }) => {
+                // Le début du tampon atteint le support, le reste garde
+                // l'ancien contenu ; le cache est perdu avec l'alimentation.
+                let bytes = bytes.min(buf.len());
+                let mut data = vec![0u8; buf.len()];
+                self.inner.read_sector(lba, &mut data)?;
+                data[..bytes].copy_from_slice(&buf[..bytes]);
+                self.power_cut();
+                self.inner.write_sector(lba, &data)?;
+                Err(Error::Io)
+            }
+            Some(Fault::PowerCut) => {
+                self.power_cut();
+                Err(Error::Io)
+            }
+        }
+    }
+
+    /// Écrit sur `inner` les écritures en cache, puis vide `inner`.
+    fn flush(&mut self) -> Result<(), Error> {
+        if !self.powered {
+            return Err(Error::Io);
+        }
+        if let Some(cache) = &mut self.cache {
+            for (lba, data) in core::mem::take(cache) {
+                self.inner.write_sector(lba, &data)?;
+            }
+        }
+        self.inner.flush()
+    }
+
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        if !self.powered {
+            return Err(Error::Io);
+        }
+        if let Some(cache) = &mut self.cache {
+            cache.retain(|l, _| !(lba..lba + count).contains(l));
+        }
+        self.inner.discard(lba, count)
+    }
+
+    fn stats(&self) -> Option<IoStats> {
+        self.inner.stats()
+    }
+}
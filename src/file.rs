@@ -1,12 +1,117 @@
+use crate::dir::{ATTR_READ_ONLY, RawSlot};
+use crate::write::set_first_cluster;
 use crate::{BlockDevice, Error, Fat32};
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use core::cmp;
 
+/// Vrai si le fichier peut être modifié : entrée connue, sans attribut
+/// lecture seule.
+pub(crate) fn is_writable(entry: Option<&RawSlot>) -> bool {
+    entry.is_some_and(|slot| slot.bytes[11] & ATTR_READ_ONLY == 0)
+}
+
+/// Écriture de `len` octets à la position `cursor` d'un fichier de `size`
+/// octets, décidée avant toute E/S. L'écart entre la fin et la position
+/// est rempli de zéros dans la même passe, pour que l'entrée ne soit mise
+/// à jour qu'une fois à la fin.
+pub(crate) struct WritePlan {
+    /// Premier octet écrit : la fin du fichier si la position est au-delà
+    pub start: u64,
+    /// Zéros écrits avant les données
+    pub gap: usize,
+    /// Octets écrits à partir de `start`, zéros compris
+    pub total: usize,
+    /// Position après l'écriture
+    pub end: u32,
+    /// Longueur de chaîne nécessaire
+    pub clusters: usize,
+}
+
+/// Morceau d'une écriture contenu dans un seul secteur.
+pub(crate) struct Span {
+    /// Indice du cluster dans la chaîne
+    pub index: usize,
+    /// Secteur dans le cluster
+    pub sector: u32,
+    /// Position dans le secteur
+    pub offset: usize,
+    /// Octets déjà écrits avant ce morceau (depuis `start`)
+    pub done: usize,
+    pub len: usize,
+}
+
+pub(crate) fn plan_write(
+    cursor: u64,
+    size: u32,
+    len: usize,
+    cluster_bytes: usize,
+) -> Result<WritePlan, Error> {
+    let start = cmp::min(cursor, size as u64);
+    let gap = (cursor - start) as usize;
+    let end = u32::try_from(cursor + len as u64).map_err(|_| Error::NoSpace)?;
+    Ok(WritePlan {
+        start,
+        gap,
+        total: gap + len,
+        end,
+        clusters: (end as usize).div_ceil(cluster_bytes),
+    })
+}
+
+impl WritePlan {
+    /// Découpe l'écriture en morceaux d'un secteur au plus.
+    pub fn spans(
+        &self,
+        bytes_per_sector: usize,
+        cluster_bytes: usize,
+    ) -> impl Iterator<Item = Span> + '_ {
+        let mut done = 0;
+        core::iter::from_fn(move || {
+            if done >= self.total {
+                return None;
+            }
+            let pos = self.start as usize + done;
+            let in_cluster = pos % cluster_bytes;
+            let offset = in_cluster % bytes_per_sector;
+            let span = Span {
+                index: pos / cluster_bytes,
+                sector: (in_cluster / bytes_per_sector) as u32,
+                offset,
+                done,
+                len: cmp::min(bytes_per_sector - offset, self.total - done),
+            };
+            done += span.len;
+            Some(span)
+        })
+    }
+
+    /// Remplit la part `span` d'un secteur : zéros de l'écart, puis `buf`.
+    pub fn fill(&self, span: &Span, sector: &mut [u8], buf: &[u8]) {
+        let part = &mut sector[span.offset..span.offset + span.len];
+        for (i, byte) in part.iter_mut().enumerate() {
+            let k = span.done + i;
+            *byte = if k < self.gap { 0 } else { buf[k - self.gap] };
+        }
+    }
+}
+
+/// Reporte dans l'entrée 8.3 la taille et le premier cluster d'un fichier
+/// agrandi.
+pub(crate) fn resize_entry(entry: &mut [u8; 32], first_cluster: u32, size: u32) {
+    set_first_cluster(entry, first_cluster);
+    entry[28..32].copy_from_slice(&size.to_le_bytes());
+}
+
 pub struct File<'fs, D: BlockDevice> {
     pub(crate) fs: &'fs mut Fat32<D>,
     pub(crate) chain: Vec<u32>,
     pub(crate) size: u32,
     pub(crate) cursor: u64,
+    /// Entrée 8.3 du fichier (taille, premier cluster), connue quand il a
+    /// été ouvert par son chemin : sans elle, il n'est pas modifiable.
+    pub(crate) entry: Option<RawSlot>,
+    /// Écritures pas encore rendues durables par `flush`
+    pub(crate) unflushed: bool,
 }
 
 impl<'fs, D: BlockDevice> File<'fs, D> {
@@ -16,9 +121,26 @@ impl<'fs, D: BlockDevice> File<'fs, D> {
             chain,
             size,
             cursor: 0,
+            entry: None,
+            unflushed: false,
         }
     }
 
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Position courante.
+    pub fn position(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Déplace la position courante, éventuellement au-delà de la fin : une
+    /// écriture y comble alors l'écart de zéros.
+    pub fn set_position(&mut self, pos: u64) {
+        self.cursor = pos;
+    }
+
     /// Lit jusqu'à buf.len() octets à partir de la position courante.
     pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
         if buf.is_empty() {
@@ -79,6 +201,70 @@ impl<'fs, D: BlockDevice> File<'fs, D> {
         Ok(written)
     }
 
+    /// Écrit `buf` à la position courante, en agrandissant le fichier (et
+    /// sa chaîne) au besoin. Les écritures ne sont rendues durables que par
+    /// `flush` ou `close` (à défaut, la destruction du fichier tente un
+    /// `flush` dont l'erreur est perdue).
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if !is_writable(self.entry.as_ref()) {
+            return Err(Error::ReadOnly);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let geometry = self.fs.geometry();
+        let bytes_per_sector = geometry.bytes_per_sector as usize;
+        let cluster_bytes = geometry.cluster_bytes();
+        let plan = plan_write(self.cursor, self.size, buf.len(), cluster_bytes)?;
+
+        if plan.clusters > self.chain.len() {
+            let added = self
+                .fs
+                .alloc_clusters((plan.clusters - self.chain.len()) as u32)?;
+            if let Some(&last) = self.chain.last() {
+                self.fs.write_fat_entry(last, added[0])?;
+            }
+            self.chain.extend(added);
+        }
+
+        self.fs.mark_dirty()?;
+        self.unflushed = true;
+        let mut sector = vec![0u8; bytes_per_sector];
+        for span in plan.spans(bytes_per_sector, cluster_bytes) {
+            let lba = (geometry.cluster_to_lba(self.chain[span.index]) + span.sector) as u64;
+            // Secteur partiel : lecture-modification-écriture
+            if span.len < bytes_per_sector {
+                self.fs.device.read_sector(lba, &mut sector)?;
+            }
+            plan.fill(&span, &mut sector, buf);
+            self.fs.device.write_sector(lba, &sector)?;
+        }
+        self.cursor = plan.end as u64;
+
+        if let Some(slot) = self.entry.as_mut().filter(|_| plan.end > self.size) {
+            resize_entry(&mut slot.bytes, self.chain[0], plan.end);
+            self.fs.write_slot(slot)?;
+            self.size = plan.end;
+        }
+        Ok(buf.len())
+    }
+
+    /// Rend durables les écritures faites sur le fichier.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.fs.flush()?;
+        self.unflushed = false;
+        Ok(())
+    }
+
+    /// Ferme le fichier en rendant ses écritures durables.
+    pub fn close(mut self) -> Result<(), Error> {
+        if self.unflushed {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
     pub fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
         let mut out = Vec::new();
         let mut tmp = [0u8; 1024];
@@ -92,3 +278,12 @@ impl<'fs, D: BlockDevice> File<'fs, D> {
         Ok(out)
     }
 }
+
+impl<D: BlockDevice> Drop for File<'_, D> {
+    /// `flush` au mieux : utiliser `close` pour en connaître le résultat.
+    fn drop(&mut self) {
+        if self.unflushed {
+            let _ = self.flush();
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{BlockDevice, Error};
+use alloc::string::String;
+use alloc::vec;
+
+/// Taille de secteur utilisée par le formateur (comme le reste du driver).
+const SECTOR_SIZE: usize = 512;
+
+/// Secteur du boot sector de secours (BPB offset 50).
+const BACKUP_BOOT_SECTOR: u16 = 6;
+
+/// Secteur FSInfo (BPB offset 48).
+const FSINFO_SECTOR: u16 = 1;
+
+/// Cluster de la racine sur un volume fraîchement formaté.
+const ROOT_CLUSTER: u32 = 2;
+
+const MEDIA_FIXED_DISK: u8 = 0xF8;
+
+/// Options de formatage FAT32.
+pub struct FormatOptions {
+    /// Secteurs par cluster (puissance de 2, 1..=128). `None` → choix
+    /// automatique selon la taille du volume (table Microsoft).
+    pub sectors_per_cluster: Option<u8>,
+    /// Nom de volume (11 caractères max). `None` → "NO NAME".
+    pub label: Option<String>,
+    /// Numéro de série du volume.
+    pub volume_id: u32,
+    /// Nombre de secteurs réservés (au moins 8 : boot, FSInfo, secours).
+    pub reserved_sectors: u16,
+    /// Nombre de copies de la FAT.
+    pub num_fats: u8,
+    /// Alignement (en secteurs) du début des FAT et de la zone data.
+    pub align: u32,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            sectors_per_cluster: None,
+            label: None,
+            volume_id: 0,
+            reserved_sectors: 32,
+            num_fats: 2,
+            align: 1,
+        }
+    }
+}
+
+/// Géométrie calculée d'un volume à formater.
+struct Layout {
+    sectors_per_cluster: u8,
+    reserved_sectors: u16,
+    sectors_per_fat: u32,
+    cluster_count: u32,
+}
+
+/// Taille de cluster par défaut (secteurs de 512 octets), cf. spec Microsoft.
+fn default_sectors_per_cluster(total_sectors: u32) -> u8 {
+    match total_sectors {
+        0..=532_480 => 1,
+        532_481..=16_777_216 => 8,
+        16_777_217..=33_554_432 => 16,
+        33_554_433..=67_108_864 => 32,
+        _ => 64,
+    }
+}
+
+fn round_up(value: u32, align: u32) -> u32 {
+    value.div_ceil(align) * align
+}
+
+fn compute_layout(total_sectors: u32, opts: &FormatOptions) -> Result<Layout, Error> {
+    let spc = opts
+        .sectors_per_cluster
+        .unwrap_or_else(|| default_sectors_per_cluster(total_sectors));
+    if spc == 0 || !spc.is_power_of_two() || spc > 128 {
+        return Err(Error::InvalidFs);
+    }
+    if opts.num_fats == 0 || opts.reserved_sectors < 8 || opts.align == 0 {
+        return Err(Error::InvalidFs);
+    }
+
+    let reserved = round_up(opts.reserved_sectors as u32, opts.align);
+    if reserved > u16::MAX as u32 || reserved >= total_sectors {
+        return Err(Error::InvalidFs);
+    }
+
+    // Majorant : on suppose que toute la zone après les secteurs réservés
+    // contient des clusters, ce qui donne une FAT assez grande.
+    let upper_clusters = (total_sectors - reserved) / spc as u32;
+    let entries_per_sector = (SECTOR_SIZE / 4) as u32;
+    let sectors_per_fat = round_up(
+        (upper_clusters + 2).div_ceil(entries_per_sector),
+        opts.align,
+    );
+
+    let fats_size = opts.num_fats as u32 * sectors_per_fat;
+    if reserved + fats_size >= total_sectors {
+        return Err(Error::InvalidFs);
+    }
+
+    let cluster_count = (total_sectors - reserved - fats_size) / spc as u32;
+    // Au-delà, les valeurs entrent en collision avec les marqueurs (BAD/EOC).
+    if cluster_count == 0 || cluster_count > 0x0FFF_FFF5 - 2 {
+        return Err(Error::InvalidFs);
+    }
+
+    Ok(Layout {
+        sectors_per_cluster: spc,
+        reserved_sectors: reserved as u16,
+        sectors_per_fat,
+        cluster_count,
+    })
+}
+
+/// Convertit un nom de volume en champ 8.3 brut (majuscules, complété d'espaces).
+fn encode_label(label: Option<&str>) -> Result<[u8; 11], Error> {
+    let mut raw = *b"NO NAME    ";
+    if let Some(label) = label {
+        if label.is_empty() || label.len() > 11 || !label.is_ascii() {
+            return Err(Error::InvalidFs);
+        }
+        raw = [b' '; 11];
+        for (dst, c) in raw.iter_mut().zip(label.bytes()) {
+            if c < 0x20 || b"\"*+,./:;<=>?[\\]|".contains(&c) {
+                return Err(Error::InvalidFs);
+            }
+            *dst = c.to_ascii_uppercase();
+        }
+    }
+    Ok(raw)
+}
+
+fn put_u16(buf: &mut [u8], offset: usize, value: u16) {
+    buf[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut [u8], offset: usize, value: u32) {
+    buf[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn build_boot_sector(
+    total_sectors: u32,
+    layout: &Layout,
+    opts: &FormatOptions,
+    label: &[u8; 11],
+) -> [u8; SECTOR_SIZE] {
+    let mut s = [0u8; SECTOR_SIZE];
+
+    // Saut vers le code de boot + OEM
+    s[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+    s[3..11].copy_from_slice(b"MSWIN4.1");
+
+    // BPB commun
+    put_u16(&mut s, 11, SECTOR_SIZE as u16);
+    s[13] = layout.sectors_per_cluster;
+    put_u16(&mut s, 14, layout.reserved_sectors);
+    s[16] = opts.num_fats;
+    put_u16(&mut s, 17, 0); // root_entry_count : 0 en FAT32
+    put_u16(&mut s, 19, 0); // total_sectors_16 : 0 en FAT32
+    s[21] = MEDIA_FIXED_DISK;
+    put_u16(&mut s, 22, 0); // fat_size_16 : 0 en FAT32
+    put_u16(&mut s, 24, 32); // secteurs par piste
+    put_u16(&mut s, 26, 64); // têtes
+    put_u32(&mut s, 28, 0); // secteurs cachés
+    put_u32(&mut s, 32, total_sectors);
+
+    // BPB étendu FAT32
+    put_u32(&mut s, 36, layout.sectors_per_fat);
+    put_u16(&mut s, 40, 0); // ext_flags : miroir actif
+    put_u16(&mut s, 42, 0); // version 0.0
+    put_u32(&mut s, 44, ROOT_CLUSTER);
+    put_u16(&mut s, 48, FSINFO_SECTOR);
+    put_u16(&mut s, 50, BACKUP_BOOT_SECTOR);
+    s[64] = 0x80; // numéro de lecteur
+    s[66] = 0x29; // signature du boot étendu
+    put_u32(&mut s, 67, opts.volume_id);
+    s[71..82].copy_from_slice(label);
+    s[82..90].copy_from_slice(b"FAT32   ");
+
+    // Code de boot minimal : hlt ; jmp $-1
+    s[90..93].copy_from_slice(&[0xF4, 0xEB, 0xFD]);
+
+    s[510] = 0x55;
+    s[511] = 0xAA;
+    s
+}
+
+fn build_fsinfo(free_count: u32, next_free: u32) -> [u8; SECTOR_SIZE] {
+    let mut s = [0u8; SECTOR_SIZE];
+    put_u32(&mut s, 0, 0x4161_5252);
+    put_u32(&mut s, 484, 0x6141_7272);
+    put_u32(&mut s, 488, free_count);
+    put_u32(&mut s, 492, next_free);
+    put_u32(&mut s, 508, 0xAA55_0000);
+    s
+}
+
+/// Formate `device` en FAT32 sur `total_sectors` secteurs de 512 octets.
+///
+/// Écrit le boot sector et sa copie de secours, le FSInfo (et sa copie),
+/// toutes les FAT et un répertoire racine vide (avec l'entrée de nom de
+/// volume si `label` est fourni). La zone data n'est pas effacée.
+///
+/// Note : en dessous de 65525 clusters, le volume n'est pas un FAT32
+/// "officiel" (mkfs.fat émet le même avertissement) mais reste lisible.
+pub fn format<D: BlockDevice>(
+    device: &mut D,
+    total_sectors: u32,
+    opts: &FormatOptions,
+) -> Result<(), Error> {
+    let layout = compute_layout(total_sectors, opts)?;
+    let label = encode_label(opts.label.as_deref())?;
+
+    let boot = build_boot_sector(total_sectors, &layout, opts, &label);
+    // La racine occupe le cluster 2, donc le prochain libre est le 3.
+    let fsinfo = build_fsinfo(layout.cluster_count - 1, ROOT_CLUSTER + 1);
+    let zero = [0u8; SECTOR_SIZE];
+
+    // Zone réservée : tout à zéro, sauf boot/FSInfo et leurs copies.
+    for lba in 0..layout.reserved_sectors as u64 {
+        let sector: &[u8] = match lba {
+            0 => &boot,
+            1 => &fsinfo,
+            6 => &boot,
+            7 => &fsinfo,
+            _ => &zero,
+        };
+        device.write_sector(lba, sector)?;
+    }
+
+    // FATs : entrées 0 et 1 réservées, entrée 2 = fin de chaîne (racine).
+    let mut first_fat_sector = [0u8; SECTOR_SIZE];
+    put_u32(
+        &mut first_fat_sector,
+        0,
+        0x0FFF_FF00 | MEDIA_FIXED_DISK as u32,
+    );
+    put_u32(&mut first_fat_sector, 4, 0x0FFF_FFFF);
+    put_u32(&mut first_fat_sector, 8, 0x0FFF_FFFF);
+
+    let mut lba = layout.reserved_sectors as u64;
+    for _ in 0..opts.num_fats {
+        for i in 0..layout.sectors_per_fat {
+            let sector: &[u8] = if i == 0 { &first_fat_sector } else { &zero };
+            device.write_sector(lba, sector)?;
+            lba += 1;
+        }
+    }
+
+    // Répertoire racine vide (le premier cluster data suit les FAT).
+    let mut root = vec![0u8; layout.sectors_per_cluster as usize * SECTOR_SIZE];
+    if opts.label.is_some() {
+        root[0..11].copy_from_slice(&label);
+        root[11] = 0x08; // ATTR_VOLUME_ID
+    }
+    for chunk in root.chunks(SECTOR_SIZE) {
+        device.write_sector(lba, chunk)?;
+        lba += 1;
+    }
+
+    device.flush()
+}
@@ -0,0 +1,97 @@
+//! Disposition d'un volume monté (FAT, zone data), commune à `Fat32` et à
+//! `AsyncFat32` : ces calculs ne font aucune E/S.
+
+use crate::Error;
+use crate::boot::BootSector;
+use core::ops::Range;
+
+/// Début des FAT et de la zone data décrits par `boot`, après
+/// vérification de la FAT active.
+pub(crate) fn layout_of(boot: &BootSector) -> Result<(u32, u32), Error> {
+    if !boot.mirroring_enabled() && boot.active_fat() >= boot.num_fats {
+        return Err(Error::InvalidFs);
+    }
+    let fat_start_lba = boot.reserved_sectors as u32;
+    let data_start_lba = fat_start_lba + (boot.num_fats as u32 * boot.sectors_per_fat);
+    Ok((fat_start_lba, data_start_lba))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Geometry {
+    pub bytes_per_sector: u32,
+    pub sectors_per_cluster: u32,
+    pub fat_start_lba: u32,
+    pub data_start_lba: u32,
+    pub sectors_per_fat: u32,
+    pub num_fats: u8,
+    pub total_sectors: u32,
+    pub mirroring: bool,
+    /// FAT désignée par les ext_flags du BPB
+    pub flagged_fat: u8,
+}
+
+impl Geometry {
+    pub fn new(boot: &BootSector, fat_start_lba: u32, data_start_lba: u32) -> Self {
+        Self {
+            bytes_per_sector: boot.bytes_per_sector as u32,
+            sectors_per_cluster: boot.sectors_per_cluster as u32,
+            fat_start_lba,
+            data_start_lba,
+            sectors_per_fat: boot.sectors_per_fat,
+            num_fats: boot.num_fats,
+            total_sectors: boot.total_sectors,
+            mirroring: boot.mirroring_enabled(),
+            flagged_fat: boot.active_fat(),
+        }
+    }
+
+    pub fn cluster_bytes(&self) -> usize {
+        (self.bytes_per_sector * self.sectors_per_cluster) as usize
+    }
+
+    /// Nombre de clusters de la zone data (numérotés de 2 à count + 1).
+    pub fn cluster_count(&self) -> u32 {
+        let data_sectors = self.total_sectors.saturating_sub(self.data_start_lba);
+        data_sectors / self.sectors_per_cluster.max(1)
+    }
+
+    /// LBA du premier secteur d'un cluster (0 ou 1 → début de la zone data).
+    pub fn cluster_to_lba(&self, cluster: u32) -> u32 {
+        self.data_start_lba + cluster.saturating_sub(2) * self.sectors_per_cluster
+    }
+
+    /// Secteurs d'un cluster.
+    pub fn cluster_sectors(&self, cluster: u32) -> Range<u32> {
+        let first = self.cluster_to_lba(cluster);
+        first..first + self.sectors_per_cluster
+    }
+
+    /// FAT lue par le driver : la première si le miroir est actif, sinon
+    /// celle désignée par les ext_flags.
+    pub fn active_fat(&self) -> u8 {
+        if self.mirroring { 0 } else { self.flagged_fat }
+    }
+
+    /// Copies de la FAT tenues à jour : toutes avec le miroir, sinon la
+    /// seule FAT active.
+    pub fn written_fats(&self) -> Range<u8> {
+        if self.mirroring {
+            0..self.num_fats
+        } else {
+            self.active_fat()..self.active_fat() + 1
+        }
+    }
+
+    /// LBA de début d'une copie de la FAT.
+    pub fn fat_copy_lba(&self, copy: u8) -> u32 {
+        self.fat_start_lba + copy as u32 * self.sectors_per_fat
+    }
+
+    /// Secteur d'une copie de la FAT contenant l'entrée de `cluster`, et
+    /// position de l'entrée dans ce secteur.
+    pub fn fat_entry_pos(&self, copy: u8, cluster: u32) -> (u64, usize) {
+        let fat_offset = cluster * 4;
+        let lba = self.fat_copy_lba(copy) + fat_offset / self.bytes_per_sector;
+        (lba as u64, (fat_offset % self.bytes_per_sector) as usize)
+    }
+}
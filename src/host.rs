@@ -0,0 +1,364 @@
+use crate::dir::DirEntry;
+use crate::format::{FormatOptions, format};
+use crate::std_support::StdBlockDevice;
+use crate::time::DateTime;
+use crate::write::slots_for_name;
+use crate::{BlockDevice, Error, Fat32, lfn};
+use std::collections::BTreeSet;
+use std::fs::FileTimes;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+/// Bilan d'une extraction vers l'hôte.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExportStats {
+    pub files: u32,
+    pub dirs: u32,
+    pub bytes: u64,
+}
+
+/// Refuse les noms qui permettraient de sortir du répertoire cible.
+fn check_host_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', '\0']) {
+        return Err(Error::InvalidName);
+    }
+    Ok(())
+}
+
+fn file_times(entry: &DirEntry) -> FileTimes {
+    let mut times = FileTimes::new();
+    if let Some(m) = entry.modified {
+        times = times.set_modified(m.to_system_time());
+    }
+    if let Some(a) = entry.accessed {
+        times = times.set_accessed(a.to_system_time());
+    }
+    times
+}
+
+/// Copie le contenu d'un fichier de l'image vers `dest`.
+fn export_file<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    entry: &DirEntry,
+    dest: &Path,
+) -> Result<u64, Error> {
+    use std::io::Write;
+
+    let mut out = std::fs::File::create(dest).map_err(|_| Error::Io)?;
+    let mut file = fs.open_entry(entry)?;
+    let mut buf = [0u8; 4096];
+    let mut total = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        out.write_all(&buf[..n]).map_err(|_| Error::Io)?;
+        total += n as u64;
+    }
+
+    out.set_times(file_times(entry)).map_err(|_| Error::Io)?;
+    drop(out);
+
+    if entry.is_read_only() {
+        let mut perms = std::fs::metadata(dest)
+            .map_err(|_| Error::Io)?
+            .permissions();
+        perms.set_readonly(true);
+        std::fs::set_permissions(dest, perms).map_err(|_| Error::Io)?;
+    }
+    Ok(total)
+}
+
+/// Extrait `src` (fichier ou répertoire de l'image) dans `host_dir`.
+///
+/// Un répertoire est copié récursivement : son contenu est placé dans
+/// `host_dir`. Les noms longs sont utilisés, les dates de modification et
+/// l'attribut lecture seule des fichiers sont reportés sur l'hôte. Si un nom
+/// risque de sortir de `host_dir` (`..`, séparateurs), l'extraction est
+/// refusée avant toute écriture.
+pub fn export<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    src: &str,
+    host_dir: &Path,
+) -> Result<ExportStats, Error> {
+    let mut stats = ExportStats::default();
+
+    let is_dir = src == "/" || src == "." || fs.resolve_path(src)?.is_dir;
+    if !is_dir {
+        let entry = fs.resolve_path(src)?;
+        check_host_name(&entry.name)?;
+        std::fs::create_dir_all(host_dir).map_err(|_| Error::Io)?;
+        stats.bytes = export_file(fs, &entry, &host_dir.join(&entry.name))?;
+        stats.files = 1;
+        return Ok(stats);
+    }
+
+    let items: Vec<(std::string::String, DirEntry)> =
+        fs.walk(src)?.collect::<Result<_, Error>>()?;
+    for (_, entry) in &items {
+        check_host_name(&entry.name)?;
+    }
+
+    std::fs::create_dir_all(host_dir).map_err(|_| Error::Io)?;
+
+    // Préfixe des chemins renvoyés par `walk` pour ce point de départ
+    let prefix = match src.trim_end_matches('/') {
+        "." => "",
+        p => p,
+    };
+
+    let mut dirs: Vec<(PathBuf, DirEntry)> = Vec::new();
+    for (path, entry) in items {
+        let rel = path
+            .strip_prefix(prefix)
+            .unwrap_or(&path)
+            .trim_start_matches('/');
+        let dest = rel
+            .split('/')
+            .fold(host_dir.to_path_buf(), |p, c| p.join(c));
+
+        if entry.is_dir {
+            std::fs::create_dir_all(&dest).map_err(|_| Error::Io)?;
+            stats.dirs += 1;
+            dirs.push((dest, entry));
+        } else {
+            stats.bytes += export_file(fs, &entry, &dest)?;
+            stats.files += 1;
+        }
+    }
+
+    // Les dates des répertoires en dernier (créer un enfant les modifie),
+    // en partant des plus profonds. Best effort : toutes les plateformes ne
+    // permettent pas d'ouvrir un répertoire.
+    for (dest, entry) in dirs.iter().rev() {
+        if let Ok(dir) = std::fs::File::open(dest) {
+            let _ = dir.set_times(file_times(entry));
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Bilan prévisionnel d'un import : ce qui sera créé et la place requise.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportPlan {
+    pub files: u32,
+    pub dirs: u32,
+    pub bytes: u64,
+    /// Clusters à allouer (données, nouveaux répertoires, extensions)
+    pub clusters_needed: u32,
+    /// Clusters libres sur le volume
+    pub clusters_free: u32,
+}
+
+/// Options d'import.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportOptions {
+    /// Date appliquée à toutes les entrées créées à la place des dates de
+    /// modification de l'hôte (images reproductibles).
+    pub fixed_time: Option<DateTime>,
+}
+
+/// Élément de l'hôte à créer dans l'image, dans l'ordre d'insertion.
+struct HostItem {
+    host: PathBuf,
+    dest: std::string::String,
+    is_dir: bool,
+    size: u64,
+    modified: DateTime,
+}
+
+fn join_image_path(parent: &str, name: &str) -> std::string::String {
+    if parent.ends_with('/') {
+        std::format!("{parent}{name}")
+    } else {
+        std::format!("{parent}/{name}")
+    }
+}
+
+/// Parcourt un répertoire de l'hôte (trié par nom) et retourne le nombre
+/// d'entrées de 32 octets que ses enfants occuperont.
+fn scan_host(
+    dir: &Path,
+    dest: &str,
+    items: &mut Vec<HostItem>,
+    cluster_bytes: u64,
+    clusters: &mut u64,
+) -> Result<usize, Error> {
+    let mut children: Vec<(std::string::String, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|_| Error::Io)? {
+        let entry = entry.map_err(|_| Error::Io)?;
+        // Les liens symboliques ne sont pas suivis (un lien vers un parent
+        // ferait boucler le parcours) : ils sont ignorés
+        if entry.file_type().map_err(|_| Error::Io)?.is_symlink() {
+            continue;
+        }
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| Error::InvalidName)?;
+        if !lfn::is_valid_long_name(&name) {
+            return Err(Error::InvalidName);
+        }
+        children.push((name, entry.path()));
+    }
+    children.sort();
+
+    // Deux noms ne différant que par la casse ne peuvent pas coexister (le
+    // tri par octets ne les rend pas forcément voisins : `B`, `a`, `b`)
+    let mut folded = BTreeSet::new();
+    for (name, _) in &children {
+        if !folded.insert(name.to_ascii_uppercase()) {
+            return Err(Error::AlreadyExists);
+        }
+    }
+
+    let mut slots = 0;
+    for (name, host) in children {
+        let meta = std::fs::symlink_metadata(&host).map_err(|_| Error::Io)?;
+        let modified = meta
+            .modified()
+            .map(DateTime::from_system_time)
+            .unwrap_or(DateTime::EPOCH);
+        let dest_path = join_image_path(dest, &name);
+        slots += slots_for_name(&name);
+
+        if meta.is_dir() {
+            items.push(HostItem {
+                host: host.clone(),
+                dest: dest_path.clone(),
+                is_dir: true,
+                size: 0,
+                modified,
+            });
+            let inner = 2 + scan_host(&host, &dest_path, items, cluster_bytes, clusters)?;
+            *clusters += (inner as u64 * 32).div_ceil(cluster_bytes);
+        } else if meta.is_file() {
+            if meta.len() > u32::MAX as u64 {
+                return Err(Error::NoSpace);
+            }
+            *clusters += meta.len().div_ceil(cluster_bytes);
+            items.push(HostItem {
+                host,
+                dest: dest_path,
+                is_dir: false,
+                size: meta.len(),
+                modified,
+            });
+        }
+        // Les autres types (sockets, fifos...) sont ignorés aussi
+    }
+    Ok(slots)
+}
+
+fn prepare_import<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    host_dir: &Path,
+    dest: &str,
+) -> Result<(ImportPlan, Vec<HostItem>), Error> {
+    let dest_cluster = fs.dir_cluster(dest)?;
+    let cluster_bytes = (fs.bytes_per_sector() * fs.sectors_per_cluster()) as u64;
+
+    let mut items = Vec::new();
+    let mut clusters = 0u64;
+    let top_slots = scan_host(host_dir, dest, &mut items, cluster_bytes, &mut clusters)?;
+
+    // Conflits avec le contenu actuel de la destination
+    let existing = fs.read_dir_cluster(dest_cluster)?;
+    let top_prefix = join_image_path(dest, "");
+    for item in &items {
+        let name = &item.dest[top_prefix.len()..];
+        if !name.contains('/') && existing.iter().any(|e| e.matches(name)) {
+            return Err(Error::AlreadyExists);
+        }
+    }
+
+    // Extension éventuelle du répertoire de destination
+    let free_tail = fs.trailing_free_slots(dest_cluster)?;
+    let missing = top_slots.saturating_sub(free_tail) as u64;
+    clusters += (missing * 32).div_ceil(cluster_bytes);
+
+    let plan = ImportPlan {
+        files: items.iter().filter(|i| !i.is_dir).count() as u32,
+        dirs: items.iter().filter(|i| i.is_dir).count() as u32,
+        bytes: items.iter().map(|i| i.size).sum(),
+        clusters_needed: u32::try_from(clusters).map_err(|_| Error::NoSpace)?,
+        clusters_free: fs.free_cluster_count()?,
+    };
+    Ok((plan, items))
+}
+
+/// Calcule ce qu'importerait `import_tree`, sans rien écrire.
+pub fn plan_import<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    host_dir: &Path,
+    dest: &str,
+) -> Result<ImportPlan, Error> {
+    prepare_import(fs, host_dir, dest).map(|(plan, _)| plan)
+}
+
+/// Copie le contenu de `host_dir` dans le répertoire `dest` de l'image.
+///
+/// Les répertoires sont créés, les fichiers copiés avec leur date de
+/// modification, des alias 8.3 et des noms longs sont générés. Les entrées
+/// sont insérées dans l'ordre alphabétique ; les liens symboliques et les
+/// fichiers spéciaux sont ignorés. Les noms invalides, les conflits
+/// avec l'existant et le manque de place (`NoSpace`) sont détectés avant
+/// toute écriture.
+pub fn import_tree<D: BlockDevice>(
+    fs: &mut Fat32<D>,
+    host_dir: &Path,
+    dest: &str,
+    opts: &ImportOptions,
+) -> Result<ImportPlan, Error> {
+    let (plan, items) = prepare_import(fs, host_dir, dest)?;
+    if plan.clusters_needed > plan.clusters_free {
+        return Err(Error::NoSpace);
+    }
+
+    for item in items {
+        let time = opts.fixed_time.unwrap_or(item.modified);
+        if item.is_dir {
+            fs.create_dir(&item.dest, time)?;
+        } else {
+            let data = std::fs::read(&item.host).map_err(|_| Error::Io)?;
+            fs.create_file(&item.dest, &data, time)?;
+        }
+    }
+    Ok(plan)
+}
+
+/// Crée l'image `image` de `total_sectors` secteurs, la formate puis y
+/// importe `host_dir` (équivalent de mkfs.fat suivi de mcopy).
+///
+/// Le fichier est recréé à zéro : avec un `volume_id` et un `fixed_time`
+/// fixés, deux constructions depuis la même arborescence donnent des images
+/// identiques octet pour octet. En cas d'échec, l'image partielle est
+/// supprimée.
+pub fn build_image(
+    image: &str,
+    total_sectors: u32,
+    host_dir: &Path,
+    format_opts: &FormatOptions,
+    import_opts: &ImportOptions,
+) -> Result<ImportPlan, Error> {
+    let build = || -> Result<ImportPlan, Error> {
+        let file = std::fs::File::create(image).map_err(|_| Error::Io)?;
+        file.set_len(total_sectors as u64 * 512)
+            .map_err(|_| Error::Io)?;
+        drop(file);
+
+        let mut dev = StdBlockDevice::open(image, 512).map_err(|_| Error::Io)?;
+        format(&mut dev, total_sectors, format_opts)?;
+        let mut fs = Fat32::new(dev)?;
+        let plan = import_tree(&mut fs, host_dir, "/", import_opts)?;
+        // Image marquée propre, FSInfo recalculé
+        fs.unmount()?;
+        Ok(plan)
+    };
+
+    build().inspect_err(|_| {
+        let _ = std::fs::remove_file(image);
+    })
+}
@@ -0,0 +1,184 @@
+use alloc::{string::String, vec, vec::Vec};
+
+/// Nombre de caractères UTF-16 par entrée LFN.
+pub const CHARS_PER_SLOT: usize = 13;
+
+/// Position des 13 caractères UTF-16 dans une entrée LFN.
+const CHAR_OFFSETS: [usize; CHARS_PER_SLOT] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+/// Somme de contrôle du nom 8.3 stockée dans chaque entrée LFN.
+pub fn lfn_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, &c| sum.rotate_right(1).wrapping_add(c))
+}
+
+/// Les 13 caractères UTF-16 bruts d'une entrée LFN (terminateur 0x0000 et
+/// remplissage 0xFFFF compris).
+pub fn slot_chars(slot: &[u8]) -> [u16; CHARS_PER_SLOT] {
+    CHAR_OFFSETS.map(|off| u16::from_le_bytes([slot[off], slot[off + 1]]))
+}
+
+/// Accumule les entrées LFN qui précèdent une entrée 8.3.
+///
+/// Les entrées arrivent dans l'ordre inverse (dernière partie d'abord,
+/// marquée par 0x40). Toute incohérence (ordre, checksum) invalide le nom.
+#[derive(Default)]
+pub(crate) struct LfnBuilder {
+    chars: Vec<u16>,
+    next: u8,
+    checksum: u8,
+    valid: bool,
+}
+
+impl LfnBuilder {
+    pub fn reset(&mut self) {
+        self.chars.clear();
+        self.next = 0;
+        self.valid = false;
+    }
+
+    pub fn push(&mut self, slot: &[u8]) {
+        let ordinal = slot[0];
+        if ordinal & 0x40 != 0 {
+            let count = ordinal & 0x1F;
+            if count == 0 || count > 20 {
+                self.reset();
+                return;
+            }
+            self.chars = vec![0xFFFF; count as usize * CHARS_PER_SLOT];
+            self.next = count;
+            self.checksum = slot[13];
+            self.valid = true;
+        }
+
+        let seq = ordinal & 0x1F;
+        if !self.valid || seq != self.next || slot[13] != self.checksum {
+            self.reset();
+            return;
+        }
+
+        let base = (seq as usize - 1) * CHARS_PER_SLOT;
+        for (i, &off) in CHAR_OFFSETS.iter().enumerate() {
+            self.chars[base + i] = u16::from_le_bytes([slot[off], slot[off + 1]]);
+        }
+        self.next -= 1;
+    }
+
+    /// Termine la séquence pour l'entrée 8.3 `short_name` et retourne le nom
+    /// long s'il est complet et correspond bien à cette entrée.
+    pub fn finish(&mut self, short_name: &[u8; 11]) -> Option<String> {
+        let complete = self.valid && self.next == 0 && self.checksum == lfn_checksum(short_name);
+        let name = complete.then(|| {
+            let len = self
+                .chars
+                .iter()
+                .position(|&c| c == 0x0000 || c == 0xFFFF)
+                .unwrap_or(self.chars.len());
+            char::decode_utf16(self.chars[..len].iter().copied())
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect::<String>()
+        });
+        self.reset();
+        name.filter(|n| !n.is_empty())
+    }
+}
+
+/// Caractères interdits dans un nom long.
+const FORBIDDEN: &[char] = &['"', '*', '/', ':', '<', '>', '?', '\\', '|'];
+
+/// Vérifie qu'un nom peut être stocké comme nom long.
+pub fn is_valid_long_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name.encode_utf16().count() <= 255
+        && !name.ends_with(['.', ' '])
+        && !name.chars().any(|c| c < ' ' || FORBIDDEN.contains(&c))
+}
+
+/// Nombre d'entrées LFN nécessaires pour `name`.
+pub fn slot_count(name: &str) -> usize {
+    name.encode_utf16().count().div_ceil(CHARS_PER_SLOT)
+}
+
+/// Construit les entrées LFN de `name`, dans l'ordre où elles sont écrites
+/// sur le disque (dernière partie d'abord).
+pub(crate) fn build_slots(name: &str, short_name: &[u8; 11]) -> Vec<[u8; 32]> {
+    let mut chars: Vec<u16> = name.encode_utf16().collect();
+    let count = chars.len().div_ceil(CHARS_PER_SLOT);
+    // Terminateur 0x0000 puis remplissage à 0xFFFF
+    if chars.len() < count * CHARS_PER_SLOT {
+        chars.push(0);
+    }
+    chars.resize(count * CHARS_PER_SLOT, 0xFFFF);
+
+    let checksum = lfn_checksum(short_name);
+    (1..=count)
+        .rev()
+        .map(|seq| {
+            let mut slot = [0u8; 32];
+            slot[0] = seq as u8 | if seq == count { 0x40 } else { 0 };
+            slot[11] = 0x0F;
+            slot[13] = checksum;
+            let part = &chars[(seq - 1) * CHARS_PER_SLOT..seq * CHARS_PER_SLOT];
+            for (c, &off) in part.iter().zip(CHAR_OFFSETS.iter()) {
+                slot[off..off + 2].copy_from_slice(&c.to_le_bytes());
+            }
+            slot
+        })
+        .collect()
+}
+
+/// Génère un alias 8.3 unique ("BASENA~1.EXT") pour un nom long. Un nom
+/// qui tient en 8.3 à la casse près ("readme.txt") garde sa forme en
+/// majuscules ("README.TXT") si elle est libre.
+pub(crate) fn short_alias(name: &str, existing: &[[u8; 11]]) -> Option<[u8; 11]> {
+    if let Some(raw) = crate::dir::encode_short_name(name)
+        && !existing.contains(&raw)
+    {
+        return Some(raw);
+    }
+
+    let sanitize = |s: &str| -> Vec<u8> {
+        s.chars()
+            .filter(|&c| c != ' ' && c != '.')
+            .map(|c| {
+                let c = if c.is_ascii() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                };
+                if "+,;=[]".contains(c) || FORBIDDEN.contains(&c) {
+                    b'_'
+                } else {
+                    c as u8
+                }
+            })
+            .collect()
+    };
+
+    let trimmed = name.trim_start_matches('.');
+    let (base, ext) = match trimmed.rsplit_once('.') {
+        Some((b, e)) if !b.is_empty() => (sanitize(b), sanitize(e)),
+        _ => (sanitize(trimmed), Vec::new()),
+    };
+    let base = if base.is_empty() { vec![b'_'] } else { base };
+
+    let mut raw = [b' '; 11];
+    for (dst, &c) in raw[8..].iter_mut().zip(ext.iter()) {
+        *dst = c;
+    }
+
+    for n in 1u32..1_000_000 {
+        let tail = alloc::format!("~{n}");
+        let keep = base.len().min(8 - tail.len());
+        raw[..8].fill(b' ');
+        raw[..keep].copy_from_slice(&base[..keep]);
+        raw[keep..keep + tail.len()].copy_from_slice(tail.as_bytes());
+        if !existing.contains(&raw) {
+            return Some(raw);
+        }
+    }
+    None
+}
@@ -2,18 +2,85 @@
 
 extern crate alloc;
 
+pub mod asynch;
 pub mod boot;
+pub mod check;
+pub mod device;
 pub mod dir;
+pub mod dirty;
+#[cfg(feature = "embedded")]
+pub mod embedded;
 pub mod fat;
+pub mod faulty;
 pub mod file;
+pub mod format;
+mod geometry;
+#[cfg(feature = "std")]
+pub mod host;
+pub mod lfn;
+pub mod overlay;
+pub mod ram;
+pub mod sd;
+pub mod sd_sim;
+pub mod time;
+pub mod trace;
+pub mod undelete;
+pub mod walk;
+pub mod write;
 
 use boot::BootSector;
 use file::File;
+use geometry::{Geometry, layout_of};
 
 /// Abstraction d'accès bloc → image disque, vrai disque, etc.
 pub trait BlockDevice {
     fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error>;
     fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error>;
+
+    /// Rend durables les écritures déjà acceptées (cache du support,
+    /// tampons du système hôte).
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Indique que `count` secteurs à partir de `lba` ne contiennent plus
+    /// rien d'utile (TRIM). Simple indication : leur contenu devient
+    /// indéfini.
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        let _ = (lba, count);
+        Ok(())
+    }
+
+    /// Compteurs d'E/S, pour les périphériques qui en tiennent
+    /// (`trace::TraceDevice`). Un périphérique qui en enveloppe un autre
+    /// transmet l'appel, pour que la trace reste visible sous lui.
+    fn stats(&self) -> Option<trace::IoStats> {
+        None
+    }
+}
+
+/// Un périphérique emprunté en est un aussi : un `OverlayDevice` peut
+/// ainsi être posé temporairement sur le périphérique d'un volume monté.
+impl<D: BlockDevice + ?Sized> BlockDevice for &mut D {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        (**self).read_sector(lba, buf)
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        (**self).write_sector(lba, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        (**self).flush()
+    }
+
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        (**self).discard(lba, count)
+    }
+
+    fn stats(&self) -> Option<trace::IoStats> {
+        (**self).stats()
+    }
 }
 
 #[derive(Debug)]
@@ -21,6 +88,24 @@ pub enum Error {
     Io,
     InvalidFs,
     NotFound,
+    /// Une entrée porte déjà ce nom
+    AlreadyExists,
+    /// Les clusters d'une entrée supprimée ont été réutilisés
+    NotRecoverable,
+    /// Nom refusé (caractères interdits, `.`/`..`, séparateurs)
+    InvalidName,
+    /// Plus assez de clusters libres
+    NoSpace,
+    /// Répertoire non vide
+    NotEmpty,
+    /// Écriture refusée par un périphérique en lecture seule
+    ReadOnly,
+    /// Position de lecture / écriture négative ou hors limites
+    InvalidSeek,
+    /// Opération de fichier sur un répertoire
+    IsDirectory,
+    /// Opération de répertoire sur un fichier
+    NotDirectory,
 }
 
 #[cfg(feature = "std")]
@@ -41,6 +126,12 @@ pub mod std_support {
             let file = OpenOptions::new().read(true).write(true).open(path)?;
             Ok(Self { file, sector_size })
         }
+
+        /// Ouvre l'image sans droit d'écriture : toute écriture échoue.
+        pub fn open_read_only(path: &str, sector_size: u64) -> std::io::Result<Self> {
+            let file = OpenOptions::new().read(true).open(path)?;
+            Ok(Self { file, sector_size })
+        }
     }
 
     impl BlockDevice for StdBlockDevice {
@@ -61,9 +152,61 @@ pub mod std_support {
             self.file.write_all(buf).map_err(|_| Error::Io)?;
             Ok(())
         }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            self.file.sync_data().map_err(|_| Error::Io)
+        }
+
+        /// Perce un trou dans le fichier d'image (`FALLOC_FL_PUNCH_HOLE`, la
+        /// taille est conservée). Sans effet hors de Linux ou si le système
+        /// de fichiers hôte ne sait pas percer de trou.
+        fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+            #[cfg(target_os = "linux")]
+            if count > 0 {
+                use std::os::fd::AsRawFd;
+
+                let offset = lba.checked_mul(self.sector_size).ok_or(Error::Io)?;
+                let len = count.checked_mul(self.sector_size).ok_or(Error::Io)?;
+                let offset = libc::off_t::try_from(offset).map_err(|_| Error::Io)?;
+                let len = libc::off_t::try_from(len).map_err(|_| Error::Io)?;
+                // SAFETY: descripteur valide tant que `self.file` existe
+                let ret = unsafe {
+                    libc::fallocate(
+                        self.file.as_raw_fd(),
+                        libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+                        offset,
+                        len,
+                    )
+                };
+                if ret != 0 {
+                    let err = std::io::Error::last_os_error();
+                    if err.raw_os_error() != Some(libc::EOPNOTSUPP) {
+                        return Err(Error::Io);
+                    }
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = (lba, count);
+            Ok(())
+        }
     }
 }
 
+/// Secteur habituel du boot sector de secours (BPB offset 50).
+pub const DEFAULT_BACKUP_BOOT_SECTOR: u64 = 6;
+
+/// Options de montage.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MountOptions {
+    /// Si le secteur 0 est illisible ou invalide, utiliser le boot sector
+    /// de secours (secteur 6).
+    pub backup_boot_fallback: bool,
+    /// Signaler au périphérique les clusters libérés (`BlockDevice::discard`),
+    /// comme l'option `discard` de Linux. Désactivé par défaut : les données
+    /// supprimées ne seraient plus récupérables par `undelete`.
+    pub discard: bool,
+}
+
 /// Représente un FS FAT32 sur un BlockDevice.
 pub struct Fat32<D: BlockDevice> {
     pub device: D,
@@ -74,27 +217,67 @@ pub struct Fat32<D: BlockDevice> {
     pub data_start_lba: u32,
     /// Répertoire courant (cluster)
     pub cwd_cluster: u32,
+    /// Vrai si le volume a été monté depuis le boot sector de secours
+    pub boot_from_backup: bool,
+    /// Clusters libérés signalés au périphérique (`MountOptions::discard`)
+    pub discard: bool,
+    /// Le volume était marqué sale au montage (pas démonté proprement) :
+    /// mieux vaut lancer `check` avant de le modifier.
+    pub was_dirty: bool,
+    /// Le volume a été marqué sale par ce montage (première écriture faite)
+    pub dirty: bool,
 }
 
 impl<D: BlockDevice> Fat32<D> {
     /// Construit un Fat32 à partir d'un device (lit le secteur 0).
-    pub fn new(mut device: D) -> Result<Self, Error> {
-        let mut sector0 = [0u8; 512];
-        device.read_sector(0, &mut sector0)?;
+    pub fn new(device: D) -> Result<Self, Error> {
+        Self::mount(device, MountOptions::default())
+    }
 
-        let boot = BootSector::parse(&sector0)?;
-        let root_cluster = boot.root_cluster;
+    /// Construit un Fat32 avec des options de montage.
+    pub fn mount(mut device: D, options: MountOptions) -> Result<Self, Error> {
+        let (boot, boot_from_backup) = match Self::read_boot(&mut device, 0) {
+            Ok(boot) => (boot, false),
+            Err(_) if options.backup_boot_fallback => (
+                Self::read_boot(&mut device, DEFAULT_BACKUP_BOOT_SECTOR)?,
+                true,
+            ),
+            Err(e) => return Err(e),
+        };
 
-        let fat_start_lba = boot.reserved_sectors as u32;
-        let data_start_lba = fat_start_lba + (boot.num_fats as u32 * boot.sectors_per_fat);
+        let (fat_start_lba, data_start_lba) = layout_of(&boot)?;
+        let root_cluster = boot.root_cluster;
 
-        Ok(Self {
+        let mut fs = Self {
             device,
             boot,
             fat_start_lba,
             data_start_lba,
             cwd_cluster: root_cluster,
-        })
+            boot_from_backup,
+            discard: options.discard,
+            was_dirty: false,
+            dirty: false,
+        };
+        fs.was_dirty = fs.is_dirty_on_disk()?;
+        Ok(fs)
+    }
+
+    /// Rend durables les écritures faites jusqu'ici. Appelé après chaque
+    /// opération qui modifie le volume.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.device.flush()
+    }
+
+    /// Disposition du volume (voir `geometry.rs`).
+    pub(crate) fn geometry(&self) -> Geometry {
+        Geometry::new(&self.boot, self.fat_start_lba, self.data_start_lba)
+    }
+
+    fn read_boot(device: &mut D, lba: u64) -> Result<BootSector, Error> {
+        let mut sector = [0u8; 512];
+        device.read_sector(lba, &mut sector)?;
+        BootSector::parse(&sector)
     }
 
     pub fn bytes_per_sector(&self) -> u32 {
@@ -105,14 +288,15 @@ impl<D: BlockDevice> Fat32<D> {
         self.boot.sectors_per_cluster as u32
     }
 
-    /// Convertit un numéro de cluster en LBA du premier secteur de ce cluster.
+    /// Nombre de clusters de la zone data (numérotés de 2 à count + 1).
+    pub fn cluster_count(&self) -> u32 {
+        self.geometry().cluster_count()
+    }
+
+    /// Convertit un numéro de cluster en LBA du premier secteur de ce cluster
+    /// (0 ou 1 → début de la zone data).
     pub fn cluster_to_lba(&self, cluster: u32) -> u32 {
-        if cluster < 2 {
-            // cluster 0 ou 1 → racine (ou invalide)
-            self.data_start_lba
-        } else {
-            self.data_start_lba + (cluster - 2) * self.sectors_per_cluster()
-        }
+        self.geometry().cluster_to_lba(cluster)
     }
 
     // `resolve_path` est implémenté dans `dir.rs` (impl Fat32 dans dir.rs)
@@ -120,8 +304,25 @@ impl<D: BlockDevice> Fat32<D> {
     /// Ouvre un fichier à partir de son chemin.
     pub fn open_file(&mut self, path: &str) -> Result<File<'_, D>, Error> {
         let entry = self.resolve_path(path)?;
+        // Emplacement de l'entrée, pour que `File::write` la mette à jour
+        let slot = self
+            .entry_slots(path)
+            .ok()
+            .and_then(|mut slots| slots.pop());
+        let mut file = self.open_entry(&entry)?;
+        file.entry = slot;
+        Ok(file)
+    }
+
+    /// Ouvre un fichier à partir d'une entrée déjà lue (ex. via `walk`).
+    pub fn open_entry(&mut self, entry: &dir::DirEntry) -> Result<File<'_, D>, Error> {
         if entry.is_dir {
-            return Err(Error::InvalidFs);
+            return Err(Error::IsDirectory);
+        }
+
+        // Fichier vide : pas de chaîne de clusters
+        if entry.first_cluster == 0 {
+            return Ok(File::new(self, alloc::vec::Vec::new(), 0));
         }
 
         let mut chain = alloc::vec::Vec::new();
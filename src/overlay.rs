@@ -0,0 +1,275 @@
+//! Périphérique copie-sur-écriture : les lectures viennent d'une base
+//! jamais modifiée, les écritures vont dans un delta (en mémoire ou, avec
+//! `std`, dans un fichier). Le delta peut être exporté, appliqué à la base
+//! ou abandonné.
+
+use crate::trace::IoStats;
+use crate::{BlockDevice, Error};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Stockage des secteurs modifiés, indexés par LBA.
+pub trait DeltaStore {
+    /// Copie le secteur `lba` dans `buf` ; `false` s'il n'a pas été modifié.
+    fn get(&mut self, lba: u64, buf: &mut [u8]) -> Result<bool, Error>;
+    fn put(&mut self, lba: u64, data: &[u8]) -> Result<(), Error>;
+    /// LBA des secteurs modifiés, dans l'ordre croissant.
+    fn sectors(&self) -> Vec<u64>;
+    /// Oublie toutes les modifications.
+    fn clear(&mut self) -> Result<(), Error>;
+    /// Rend le delta durable (fichier synchronisé, par exemple).
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Delta en mémoire (creux : seuls les secteurs écrits sont conservés).
+#[derive(Debug, Default, Clone)]
+pub struct MemoryDelta {
+    sectors: BTreeMap<u64, Vec<u8>>,
+}
+
+impl DeltaStore for MemoryDelta {
+    fn get(&mut self, lba: u64, buf: &mut [u8]) -> Result<bool, Error> {
+        match self.sectors.get(&lba) {
+            Some(data) => {
+                buf.copy_from_slice(data);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn put(&mut self, lba: u64, data: &[u8]) -> Result<(), Error> {
+        self.sectors.insert(lba, data.to_vec());
+        Ok(())
+    }
+
+    fn sectors(&self) -> Vec<u64> {
+        self.sectors.keys().copied().collect()
+    }
+
+    fn clear(&mut self) -> Result<(), Error> {
+        self.sectors.clear();
+        Ok(())
+    }
+}
+
+/// Base en lecture seule + delta des secteurs écrits.
+pub struct OverlayDevice<D: BlockDevice, S: DeltaStore = MemoryDelta> {
+    pub base: D,
+    pub delta: S,
+    pub sector_size: usize,
+}
+
+impl<D: BlockDevice> OverlayDevice<D, MemoryDelta> {
+    /// Overlay avec un delta en mémoire, vide.
+    pub fn new(base: D, sector_size: usize) -> Self {
+        Self::with_delta(base, MemoryDelta::default(), sector_size)
+    }
+}
+
+impl<D: BlockDevice, S: DeltaStore> OverlayDevice<D, S> {
+    /// Overlay sur un delta existant (un fichier rouvert, par exemple).
+    pub fn with_delta(base: D, delta: S, sector_size: usize) -> Self {
+        OverlayDevice {
+            base,
+            delta,
+            sector_size,
+        }
+    }
+
+    /// Nombre de secteurs modifiés.
+    pub fn changed_sectors(&self) -> usize {
+        self.delta.sectors().len()
+    }
+
+    /// Contenu du delta : `(lba, secteur)` dans l'ordre croissant des LBA.
+    pub fn export(&mut self) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        let mut out = Vec::new();
+        for lba in self.delta.sectors() {
+            let mut data = alloc::vec![0u8; self.sector_size];
+            self.delta.get(lba, &mut data)?;
+            out.push((lba, data));
+        }
+        Ok(out)
+    }
+
+    /// Écrit le delta sur la base puis le vide. Renvoie le nombre de
+    /// secteurs appliqués. En cas d'erreur, le delta est conservé.
+    pub fn commit(&mut self) -> Result<usize, Error> {
+        let sectors = self.export()?;
+        for (lba, data) in &sectors {
+            self.base.write_sector(*lba, data)?;
+        }
+        self.base.flush()?;
+        self.delta.clear()?;
+        Ok(sectors.len())
+    }
+
+    /// Abandonne toutes les modifications (sans rapport avec
+    /// `BlockDevice::discard`, qui signale des secteurs libres).
+    pub fn discard_delta(&mut self) -> Result<(), Error> {
+        self.delta.clear()
+    }
+}
+
+impl<D: BlockDevice, S: DeltaStore> BlockDevice for OverlayDevice<D, S> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        self.base.read_sector(lba, buf)?;
+        for (i, sector) in buf.chunks_mut(self.sector_size).enumerate() {
+            self.delta.get(lba + i as u64, sector)?;
+        }
+        Ok(())
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        if !buf.len().is_multiple_of(self.sector_size) {
+            return Err(Error::Io);
+        }
+        for (i, sector) in buf.chunks(self.sector_size).enumerate() {
+            self.delta.put(lba + i as u64, sector)?;
+        }
+        Ok(())
+    }
+
+    /// Seul le delta est rendu durable : la base n'est jamais écrite (et
+    /// `discard` n'a pas d'effet sur elle).
+    fn flush(&mut self) -> Result<(), Error> {
+        self.delta.flush()
+    }
+
+    /// Compteurs de la base (le delta n'en tient pas).
+    fn stats(&self) -> Option<IoStats> {
+        self.base.stats()
+    }
+}
+
+#[cfg(feature = "std")]
+pub use file_delta::FileDelta;
+
+#[cfg(feature = "std")]
+mod file_delta {
+    use super::DeltaStore;
+    use crate::Error;
+    use std::collections::BTreeMap;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::vec::Vec;
+
+    /// Signature en tête d'un fichier delta.
+    const MAGIC: &[u8; 8] = b"FAT32DLT";
+    const HEADER_LEN: u64 = 16;
+
+    /// Delta persistant dans un fichier : un en-tête (signature, taille de
+    /// secteur) puis des enregistrements `LBA (u64 LE) + secteur`. Un
+    /// secteur réécrit est mis à jour sur place.
+    pub struct FileDelta {
+        file: File,
+        sector_size: usize,
+        /// Position des données de chaque secteur dans le fichier
+        index: BTreeMap<u64, u64>,
+    }
+
+    impl FileDelta {
+        /// Ouvre le delta `path`, ou le crée s'il n'existe pas. Un fichier
+        /// d'un autre format ou d'une autre taille de secteur est refusé.
+        pub fn open(path: impl AsRef<Path>, sector_size: usize) -> io::Result<Self> {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+            let len = file.metadata()?.len();
+            if len == 0 {
+                let mut header = [0u8; HEADER_LEN as usize];
+                header[..8].copy_from_slice(MAGIC);
+                header[8..12].copy_from_slice(&(sector_size as u32).to_le_bytes());
+                file.write_all(&header)?;
+            } else {
+                let mut header = [0u8; HEADER_LEN as usize];
+                file.read_exact(&mut header)?;
+                if &header[..8] != MAGIC {
+                    return Err(invalid("not a delta file"));
+                }
+                let stored = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+                if stored as usize != sector_size {
+                    return Err(invalid("sector size mismatch"));
+                }
+            }
+
+            // Reconstruction de l'index ; un dernier enregistrement tronqué
+            // (écriture interrompue) est ignoré.
+            let record = 8 + sector_size as u64;
+            let count = len.saturating_sub(HEADER_LEN) / record;
+            let mut index = BTreeMap::new();
+            let mut lba = [0u8; 8];
+            for i in 0..count {
+                let pos = HEADER_LEN + i * record;
+                file.seek(SeekFrom::Start(pos))?;
+                file.read_exact(&mut lba)?;
+                index.insert(u64::from_le_bytes(lba), pos + 8);
+            }
+            file.set_len(HEADER_LEN + count * record)?;
+
+            Ok(FileDelta {
+                file,
+                sector_size,
+                index,
+            })
+        }
+    }
+
+    impl DeltaStore for FileDelta {
+        fn get(&mut self, lba: u64, buf: &mut [u8]) -> Result<bool, Error> {
+            let Some(&pos) = self.index.get(&lba) else {
+                return Ok(false);
+            };
+            self.file
+                .seek(SeekFrom::Start(pos))
+                .map_err(|_| Error::Io)?;
+            self.file.read_exact(buf).map_err(|_| Error::Io)?;
+            Ok(true)
+        }
+
+        fn put(&mut self, lba: u64, data: &[u8]) -> Result<(), Error> {
+            if data.len() != self.sector_size {
+                return Err(Error::Io);
+            }
+            let pos = match self.index.get(&lba) {
+                Some(&pos) => pos,
+                None => {
+                    let end = self.file.seek(SeekFrom::End(0)).map_err(|_| Error::Io)?;
+                    self.file
+                        .write_all(&lba.to_le_bytes())
+                        .map_err(|_| Error::Io)?;
+                    end + 8
+                }
+            };
+            self.file
+                .seek(SeekFrom::Start(pos))
+                .map_err(|_| Error::Io)?;
+            self.file.write_all(data).map_err(|_| Error::Io)?;
+            self.index.insert(lba, pos);
+            Ok(())
+        }
+
+        fn sectors(&self) -> Vec<u64> {
+            self.index.keys().copied().collect()
+        }
+
+        fn clear(&mut self) -> Result<(), Error> {
+            self.file.set_len(HEADER_LEN).map_err(|_| Error::Io)?;
+            self.index.clear();
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            self.file.sync_data().map_err(|_| Error::Io)
+        }
+    }
+}
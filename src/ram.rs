@@ -0,0 +1,85 @@
+//! Disque en mémoire : une image entière dans un `Vec<u8>` ou dans un
+//! tampon emprunté (`&mut [u8]`), utilisable en no_std.
+
+use crate::{BlockDevice, Error};
+use alloc::{vec, vec::Vec};
+
+/// Périphérique bloc en mémoire.
+///
+/// `B` est le stockage : `Vec<u8>` (par défaut) ou `&mut [u8]` pour une
+/// zone fournie par l'appelant (RAM statique d'un microcontrôleur...).
+pub struct RamDisk<B = Vec<u8>> {
+    data: B,
+    pub sector_size: usize,
+}
+
+impl RamDisk<Vec<u8>> {
+    /// Disque vierge (rempli de zéros) de `sectors` secteurs.
+    pub fn new(sectors: usize, sector_size: usize) -> Self {
+        RamDisk {
+            data: vec![0; sectors * sector_size],
+            sector_size,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>> RamDisk<B> {
+    /// Disque à partir du contenu d'une image. Un dernier secteur
+    /// incomplet est ignoré.
+    pub fn from_bytes(data: B, sector_size: usize) -> Self {
+        RamDisk { data, sector_size }
+    }
+
+    /// Nombre de secteurs complets.
+    pub fn sector_count(&self) -> u64 {
+        (self.data.as_ref().len() / self.sector_size) as u64
+    }
+
+    /// Contenu actuel de l'image.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Rend le stockage (pour sauvegarder l'image, par exemple).
+    pub fn into_inner(self) -> B {
+        self.data
+    }
+
+    /// Plage d'octets d'un accès, ou `Error::Io` s'il déborde.
+    fn range(&self, lba: u64, len: usize) -> Result<core::ops::Range<usize>, Error> {
+        let start = usize::try_from(lba)
+            .ok()
+            .and_then(|lba| lba.checked_mul(self.sector_size))
+            .ok_or(Error::Io)?;
+        let end = start.checked_add(len).ok_or(Error::Io)?;
+        if end > self.sector_count() as usize * self.sector_size {
+            return Err(Error::Io);
+        }
+        Ok(start..end)
+    }
+}
+
+impl<B: AsRef<[u8]> + AsMut<[u8]>> BlockDevice for RamDisk<B> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let range = self.range(lba, buf.len())?;
+        buf.copy_from_slice(&self.data.as_ref()[range]);
+        Ok(())
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        let range = self.range(lba, buf.len())?;
+        self.data.as_mut()[range].copy_from_slice(buf);
+        Ok(())
+    }
+
+    /// Les secteurs libérés sont remis à zéro.
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        let len = usize::try_from(count)
+            .ok()
+            .and_then(|c| c.checked_mul(self.sector_size))
+            .ok_or(Error::Io)?;
+        let range = self.range(lba, len)?;
+        self.data.as_mut()[range].fill(0);
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! Carte SD en mode SPI (implémentation de référence pour les
+//! microcontrôleurs), générique sur le transport SPI.
+//!
+//! Initialisation : CMD0 (reset), CMD8 (tension, distingue v1 / v2),
+//! CMD59 (CRC activés), CMD55 + ACMD41 (sortie de l'état idle), CMD58
+//! (OCR : bit CCS, adressage par bloc des SDHC/SDXC), CMD16 (blocs de 512
+//! octets pour les SDSC). Lecture et écriture bloc par bloc (CMD17 /
+//! CMD24), CRC16 vérifiés sur les données.
+//!
+//! `sd_sim::SdCardSim` simule une carte pour les tests.
+
+use crate::{BlockDevice, Error};
+
+/// Taille de bloc imposée par le pilote.
+pub const SD_BLOCK_SIZE: usize = 512;
+
+/// Jeton de début de bloc de données (CMD17 / CMD24).
+pub const DATA_START_TOKEN: u8 = 0xFE;
+
+/// Bit « idle » de la réponse R1.
+pub const R1_IDLE: u8 = 0x01;
+/// Commande inconnue (carte v1 pour CMD8).
+pub const R1_ILLEGAL_COMMAND: u8 = 0x04;
+/// CRC de la commande incorrect.
+pub const R1_CRC_ERROR: u8 = 0x08;
+
+/// Bit CCS de l'OCR : carte à adressage par bloc.
+pub const OCR_CCS: u32 = 1 << 30;
+/// Argument de CMD8 : 2,7-3,6 V et motif de contrôle 0xAA.
+const CMD8_ARG: u32 = 0x1AA;
+/// Bit HCS d'ACMD41 : l'hôte gère les cartes haute capacité.
+const ACMD41_HCS: u32 = 1 << 30;
+
+/// Nombre maximal d'octets lus en attendant une réponse ou un jeton.
+const MAX_POLLS: u32 = 10_000;
+/// Nombre maximal de tentatives d'ACMD41.
+const MAX_INIT_TRIES: u32 = 1_000;
+
+/// Transport SPI : un octet envoyé, un octet reçu (full duplex).
+pub trait SpiTransport {
+    fn exchange(&mut self, byte: u8) -> Result<u8, Error>;
+    /// Ligne CS : `true` sélectionne la carte (CS bas).
+    fn select(&mut self, selected: bool);
+}
+
+/// Génération de carte détectée à l'initialisation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardKind {
+    /// SD 1.x (pas de CMD8), adressage à l'octet
+    V1,
+    /// SD 2.0 standard capacity, adressage à l'octet
+    V2,
+    /// SDHC / SDXC, adressage par bloc
+    HighCapacity,
+}
+
+/// CRC7 des commandes (polynôme x^7 + x^3 + 1).
+pub fn crc7(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        let mut d = byte;
+        for _ in 0..8 {
+            crc <<= 1;
+            if (d ^ crc) & 0x80 != 0 {
+                crc ^= 0x09;
+            }
+            d <<= 1;
+        }
+    }
+    crc & 0x7F
+}
+
+/// CRC16-CCITT des blocs de données (polynôme 0x1021, valeur initiale 0).
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Trame de commande complète : index, argument, CRC7 et bit de fin.
+pub fn command_frame(cmd: u8, arg: u32) -> [u8; 6] {
+    let a = arg.to_be_bytes();
+    let mut frame = [0x40 | cmd, a[0], a[1], a[2], a[3], 0];
+    frame[5] = (crc7(&frame[..5]) << 1) | 1;
+    frame
+}
+
+/// Carte SD initialisée, vue comme un `BlockDevice` de blocs de 512 octets.
+pub struct SdCard<T: SpiTransport> {
+    pub spi: T,
+    pub kind: CardKind,
+}
+
+impl<T: SpiTransport> SdCard<T> {
+    /// Initialise la carte (voir l'en-tête du module). Une carte absente
+    /// ou qui ne répond pas renvoie `Error::Io`.
+    pub fn init(mut spi: T) -> Result<Self, Error> {
+        // Au moins 74 fronts d'horloge, CS haut, pour passer en mode SPI
+        spi.select(false);
+        for _ in 0..10 {
+            spi.exchange(0xFF)?;
+        }
+
+        let mut card = SdCard {
+            spi,
+            kind: CardKind::V1,
+        };
+        card.transaction(|card| card.init_commands())?;
+        Ok(card)
+    }
+
+    fn init_commands(&mut self) -> Result<(), Error> {
+        if self.command(0, 0)? != R1_IDLE {
+            return Err(Error::Io);
+        }
+
+        let r1 = self.command(8, CMD8_ARG)?;
+        let v2 = if r1 & R1_ILLEGAL_COMMAND != 0 {
+            false
+        } else {
+            let mut r7 = [0u8; 4];
+            self.read_bytes(&mut r7)?;
+            // Tension refusée ou motif non renvoyé : carte inutilisable
+            if r7[2] & 0x0F != 0x01 || r7[3] != 0xAA {
+                return Err(Error::Io);
+            }
+            true
+        };
+
+        if self.command(59, 1)? & !R1_IDLE != 0 {
+            return Err(Error::Io);
+        }
+
+        let hcs = if v2 { ACMD41_HCS } else { 0 };
+        let mut ready = false;
+        for _ in 0..MAX_INIT_TRIES {
+            self.command(55, 0)?;
+            match self.command(41, hcs)? {
+                0 => {
+                    ready = true;
+                    break;
+                }
+                R1_IDLE => continue,
+                _ => return Err(Error::Io),
+            }
+        }
+        if !ready {
+            return Err(Error::Io);
+        }
+
+        self.kind = CardKind::V1;
+        if v2 {
+            if self.command(58, 0)? != 0 {
+                return Err(Error::Io);
+            }
+            let mut ocr = [0u8; 4];
+            self.read_bytes(&mut ocr)?;
+            self.kind = if u32::from_be_bytes(ocr) & OCR_CCS != 0 {
+                CardKind::HighCapacity
+            } else {
+                CardKind::V2
+            };
+        }
+        if self.kind != CardKind::HighCapacity && self.command(16, SD_BLOCK_SIZE as u32)? != 0 {
+            return Err(Error::Io);
+        }
+        Ok(())
+    }
+
+    /// Exécute `f` carte sélectionnée, puis la désélectionne (avec l'octet
+    /// de fin qui libère la ligne MISO), même en cas d'erreur.
+    fn transaction<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        self.spi.select(true);
+        let result = f(self);
+        self.spi.select(false);
+        self.spi.exchange(0xFF)?;
+        result
+    }
+
+    /// Envoie une commande et renvoie la réponse R1.
+    fn command(&mut self, cmd: u8, arg: u32) -> Result<u8, Error> {
+        for byte in command_frame(cmd, arg) {
+            self.spi.exchange(byte)?;
+        }
+        for _ in 0..MAX_POLLS {
+            let r1 = self.spi.exchange(0xFF)?;
+            if r1 & 0x80 == 0 {
+                return Ok(r1);
+            }
+        }
+        Err(Error::Io)
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        for byte in buf {
+            *byte = self.spi.exchange(0xFF)?;
+        }
+        Ok(())
+    }
+
+    /// Adresse d'un bloc : numéro de bloc (SDHC) ou octet (SDSC).
+    fn address(&self, lba: u64) -> Result<u32, Error> {
+        let addr = match self.kind {
+            CardKind::HighCapacity => lba,
+            _ => lba * SD_BLOCK_SIZE as u64,
+        };
+        u32::try_from(addr).map_err(|_| Error::Io)
+    }
+
+    fn read_block(&mut self, lba: u64, block: &mut [u8]) -> Result<(), Error> {
+        let addr = self.address(lba)?;
+        if self.command(17, addr)? != 0 {
+            return Err(Error::Io);
+        }
+        let mut token = 0xFF;
+        for _ in 0..MAX_POLLS {
+            token = self.spi.exchange(0xFF)?;
+            if token != 0xFF {
+                break;
+            }
+        }
+        // Jeton d'erreur (0b000xxxxx) ou pas de réponse
+        if token != DATA_START_TOKEN {
+            return Err(Error::Io);
+        }
+        self.read_bytes(block)?;
+        let mut crc = [0u8; 2];
+        self.read_bytes(&mut crc)?;
+        if u16::from_be_bytes(crc) != crc16(block) {
+            return Err(Error::Io);
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self, lba: u64, block: &[u8]) -> Result<(), Error> {
+        let addr = self.address(lba)?;
+        if self.command(24, addr)? != 0 {
+            return Err(Error::Io);
+        }
+        self.spi.exchange(0xFF)?;
+        self.spi.exchange(DATA_START_TOKEN)?;
+        for &byte in block {
+            self.spi.exchange(byte)?;
+        }
+        for byte in crc16(block).to_be_bytes() {
+            self.spi.exchange(byte)?;
+        }
+
+        // Réponse de données : xxx0_0101 = acceptées
+        let mut response = 0xFF;
+        for _ in 0..MAX_POLLS {
+            response = self.spi.exchange(0xFF)?;
+            if response != 0xFF {
+                break;
+            }
+        }
+        if response & 0x1F != 0x05 {
+            return Err(Error::Io);
+        }
+        // Programmation en cours : la carte tient MISO à 0
+        for _ in 0..MAX_POLLS {
+            if self.spi.exchange(0xFF)? == 0xFF {
+                return Ok(());
+            }
+        }
+        Err(Error::Io)
+    }
+}
+
+impl<T: SpiTransport> BlockDevice for SdCard<T> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        if !buf.len().is_multiple_of(SD_BLOCK_SIZE) {
+            return Err(Error::Io);
+        }
+        for (i, block) in buf.chunks_mut(SD_BLOCK_SIZE).enumerate() {
+            self.transaction(|card| card.read_block(lba + i as u64, block))?;
+        }
+        Ok(())
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        if !buf.len().is_multiple_of(SD_BLOCK_SIZE) {
+            return Err(Error::Io);
+        }
+        for (i, block) in buf.chunks(SD_BLOCK_SIZE).enumerate() {
+            self.transaction(|card| card.write_block(lba + i as u64, block))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+//! Carte SD simulée côté SPI, adossée à une image en mémoire : remplace le
+//! matériel dans les tests du pilote `sd::SdCard`.
+//!
+//! La carte suit le protocole octet par octet (trames de commande, CRC7,
+//! réponses R1 / R3 / R7, jetons et CRC16 des blocs, temps d'occupation)
+//! et refuse ce qu'une vraie carte refuserait : commande de lecture avant
+//! l'initialisation, adresse non alignée d'une SDSC, CRC incorrect...
+
+use crate::Error;
+use crate::sd::{
+    CardKind, DATA_START_TOKEN, OCR_CCS, R1_CRC_ERROR, R1_IDLE, R1_ILLEGAL_COMMAND, SD_BLOCK_SIZE,
+    SpiTransport, crc7, crc16,
+};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// Erreur d'adresse (R1).
+const R1_ADDRESS_ERROR: u8 = 0x20;
+/// Paramètre hors limites (R1).
+const R1_PARAMETER_ERROR: u8 = 0x40;
+/// Réponses de données de CMD24.
+const DATA_ACCEPTED: u8 = 0x05;
+const DATA_CRC_ERROR: u8 = 0x0B;
+/// Jeton d'erreur de lecture : adresse hors limites.
+const READ_ERROR_OUT_OF_RANGE: u8 = 0x08;
+
+/// Où en est la carte dans l'échange en cours.
+enum State {
+    /// Attente d'une trame de commande
+    Command,
+    /// CMD24 acceptée : attente du jeton puis du bloc et de son CRC
+    ReceiveData { offset: usize, block: Vec<u8> },
+}
+
+pub struct SdCardSim {
+    /// Contenu de la carte
+    pub data: Vec<u8>,
+    pub kind: CardKind,
+    /// Nombre d'ACMD41 répondues « encore idle » avant la fin de
+    /// l'initialisation
+    pub init_polls: u32,
+    /// Octets 0xFF avant le jeton de données d'une lecture
+    pub read_latency: usize,
+    /// Octets « occupé » après l'acceptation d'une écriture
+    pub write_busy: usize,
+    /// Index des commandes reçues (les ACMD sont notées 41, précédées de 55)
+    pub commands: Vec<u8>,
+    /// Le prochain bloc lu est envoyé avec un CRC16 faux
+    pub corrupt_next_read: bool,
+    /// Répond 0x00 à tout (ligne MISO bloquée, pas de carte)
+    pub absent: bool,
+    selected: bool,
+    idle: bool,
+    app_command: bool,
+    crc_enabled: bool,
+    frame: Vec<u8>,
+    out: VecDeque<u8>,
+    state: State,
+}
+
+impl SdCardSim {
+    /// Carte de `kind` contenant `data` (taille multiple de 512).
+    pub fn new(data: Vec<u8>, kind: CardKind) -> Self {
+        SdCardSim {
+            data,
+            kind,
+            init_polls: 3,
+            read_latency: 2,
+            write_busy: 3,
+            commands: Vec::new(),
+            corrupt_next_read: false,
+            absent: false,
+            selected: false,
+            idle: true,
+            app_command: false,
+            crc_enabled: false,
+            frame: Vec::new(),
+            out: VecDeque::new(),
+            state: State::Command,
+        }
+    }
+
+    /// Vrai si la carte a terminé son initialisation (sortie de l'état idle).
+    pub fn is_ready(&self) -> bool {
+        !self.idle
+    }
+
+    fn blocks(&self) -> u64 {
+        (self.data.len() / SD_BLOCK_SIZE) as u64
+    }
+
+    fn r1(&self, flags: u8) -> u8 {
+        flags | if self.idle { R1_IDLE } else { 0 }
+    }
+
+    /// Décodage d'une adresse de bloc, selon l'adressage de la carte.
+    fn block_offset(&self, arg: u32) -> Result<usize, u8> {
+        let lba = match self.kind {
+            CardKind::HighCapacity => arg as u64,
+            _ if !(arg as usize).is_multiple_of(SD_BLOCK_SIZE) => return Err(R1_ADDRESS_ERROR),
+            _ => arg as u64 / SD_BLOCK_SIZE as u64,
+        };
+        if lba >= self.blocks() {
+            return Err(R1_PARAMETER_ERROR);
+        }
+        Ok(lba as usize * SD_BLOCK_SIZE)
+    }
+
+    fn execute(&mut self, frame: [u8; 6]) {
+        let cmd = frame[0] & 0x3F;
+        let arg = u32::from_be_bytes([frame[1], frame[2], frame[3], frame[4]]);
+        let app = core::mem::take(&mut self.app_command);
+        self.commands.push(cmd);
+
+        // NCR : un octet avant la réponse
+        self.out.push_back(0xFF);
+
+        // CMD0 et CMD8 sont toujours vérifiés, le reste après CMD59
+        let crc_checked = self.crc_enabled || cmd == 0 || cmd == 8;
+        if crc_checked && frame[5] != (crc7(&frame[..5]) << 1) | 1 {
+            self.out.push_back(self.r1(R1_CRC_ERROR));
+            return;
+        }
+
+        match (app, cmd) {
+            (_, 0) => {
+                self.idle = true;
+                self.crc_enabled = false;
+                self.out.push_back(R1_IDLE);
+            }
+            (_, 8) if self.kind == CardKind::V1 => {
+                self.out.push_back(self.r1(R1_ILLEGAL_COMMAND));
+            }
+            (_, 8) => {
+                self.out.push_back(self.r1(0));
+                self.out.extend([0, 0, (arg >> 8) as u8 & 0x0F, arg as u8]);
+            }
+            (_, 55) => {
+                self.app_command = true;
+                self.out.push_back(self.r1(0));
+            }
+            (true, 41) => {
+                // Une carte haute capacité reste idle si l'hôte ne l'annonce pas
+                let hcs = arg & (1 << 30) != 0;
+                if self.init_polls > 0 {
+                    self.init_polls -= 1;
+                } else if hcs || self.kind != CardKind::HighCapacity {
+                    self.idle = false;
+                }
+                self.out.push_back(self.r1(0));
+            }
+            (_, 58) => {
+                let mut ocr = 0x00FF_8000;
+                if !self.idle {
+                    ocr |= 1 << 31;
+                    if self.kind == CardKind::HighCapacity {
+                        ocr |= OCR_CCS;
+                    }
+                }
+                self.out.push_back(self.r1(0));
+                self.out.extend(u32::to_be_bytes(ocr));
+            }
+            (_, 59) => {
+                self.crc_enabled = arg & 1 != 0;
+                self.out.push_back(self.r1(0));
+            }
+            (_, 16) if self.idle => self.out.push_back(self.r1(R1_ILLEGAL_COMMAND)),
+            (_, 16) => {
+                // Le simulateur ne gère que des blocs de 512 octets
+                let flags = if arg as usize == SD_BLOCK_SIZE {
+                    0
+                } else {
+                    R1_PARAMETER_ERROR
+                };
+                self.out.push_back(self.r1(flags));
+            }
+            (_, 17 | 24) if self.idle => self.out.push_back(self.r1(R1_ILLEGAL_COMMAND)),
+            (_, 17) => match self.block_offset(arg) {
+                Ok(offset) => {
+                    self.out.push_back(0);
+                    self.out
+                        .extend(core::iter::repeat_n(0xFF, self.read_latency));
+                    self.out.push_back(DATA_START_TOKEN);
+                    let block = &self.data[offset..offset + SD_BLOCK_SIZE];
+                    let mut crc = crc16(block);
+                    if core::mem::take(&mut self.corrupt_next_read) {
+                        crc ^= 0x0001;
+                    }
+                    self.out.extend(block.iter().copied());
+                    self.out.extend(crc.to_be_bytes());
+                }
+                Err(R1_PARAMETER_ERROR) => {
+                    // Acceptée, puis jeton d'erreur à la place des données
+                    self.out.push_back(0);
+                    self.out.push_back(READ_ERROR_OUT_OF_RANGE);
+                }
+                Err(flags) => self.out.push_back(self.r1(flags)),
+            },
+            (_, 24) => match self.block_offset(arg) {
+                Ok(offset) => {
+                    self.out.push_back(0);
+                    self.state = State::ReceiveData {
+                        offset,
+                        block: Vec::new(),
+                    };
+                }
+                Err(flags) => self.out.push_back(self.r1(flags)),
+            },
+            _ => self.out.push_back(self.r1(R1_ILLEGAL_COMMAND)),
+        }
+    }
+
+    /// Octet reçu pendant une écriture (jeton, données puis CRC16).
+    fn receive(&mut self, byte: u8) {
+        let State::ReceiveData { offset, block } = &mut self.state else {
+            return;
+        };
+        if block.is_empty() && byte != DATA_START_TOKEN {
+            return; // octets de bourrage avant le jeton
+        }
+        block.push(byte);
+        if block.len() < 1 + SD_BLOCK_SIZE + 2 {
+            return;
+        }
+
+        let data = &block[1..1 + SD_BLOCK_SIZE];
+        let crc = u16::from_be_bytes([block[1 + SD_BLOCK_SIZE], block[2 + SD_BLOCK_SIZE]]);
+        let offset = *offset;
+        if self.crc_enabled && crc != crc16(data) {
+            self.out.push_back(DATA_CRC_ERROR);
+        } else {
+            let data = data.to_vec();
+            self.data[offset..offset + SD_BLOCK_SIZE].copy_from_slice(&data);
+            self.out.push_back(DATA_ACCEPTED);
+            self.out.extend(core::iter::repeat_n(0x00, self.write_busy));
+        }
+        self.state = State::Command;
+    }
+}
+
+impl SpiTransport for SdCardSim {
+    fn exchange(&mut self, byte: u8) -> Result<u8, Error> {
+        if self.absent {
+            return Ok(0x00);
+        }
+        if !self.selected {
+            return Ok(0xFF);
+        }
+        let reply = self.out.pop_front().unwrap_or(0xFF);
+
+        if matches!(self.state, State::ReceiveData { .. }) {
+            self.receive(byte);
+            return Ok(reply);
+        }
+
+        // Début de trame : bits 01 en tête
+        if self.frame.is_empty() && byte & 0xC0 != 0x40 {
+            return Ok(reply);
+        }
+        self.frame.push(byte);
+        if self.frame.len() == 6 {
+            let mut frame = [0u8; 6];
+            frame.copy_from_slice(&self.frame);
+            self.frame.clear();
+            // Une nouvelle commande interrompt la réponse en cours
+            self.out.clear();
+            self.execute(frame);
+        }
+        Ok(reply)
+    }
+
+    /// Désélectionner la carte abandonne la trame ou l'écriture en cours.
+    fn select(&mut self, selected: bool) {
+        self.selected = selected;
+        if !selected {
+            self.frame.clear();
+            self.out.clear();
+            self.state = State::Command;
+        }
+    }
+}
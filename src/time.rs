@@ -0,0 +1,108 @@
+/// Date et heure au format FAT (heure locale, résolution 2 s sur disque).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Plus petite date représentable (1980-01-01 00:00:00).
+    pub const EPOCH: DateTime = DateTime {
+        year: 1980,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// Décode une paire (date, heure) DOS. Retourne `None` si la date est
+    /// nulle ou invalide (champ jamais renseigné).
+    pub fn from_dos(date: u16, time: u16) -> Option<Self> {
+        let dt = Self {
+            year: 1980 + (date >> 9),
+            month: ((date >> 5) & 0x0F) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3F) as u8,
+            second: ((time & 0x1F) * 2) as u8,
+        };
+
+        let valid = (1..=12).contains(&dt.month)
+            && dt.day >= 1
+            && dt.hour < 24
+            && dt.minute < 60
+            && dt.second < 60;
+        valid.then_some(dt)
+    }
+
+    /// Encode en paire (date, heure) DOS, en bornant aux années 1980..=2107.
+    pub fn to_dos(&self) -> (u16, u16) {
+        let year = self.year.clamp(1980, 2107) - 1980;
+        let date = (year << 9) | ((self.month as u16) << 5) | self.day as u16;
+        let time =
+            ((self.hour as u16) << 11) | ((self.minute as u16) << 5) | (self.second as u16 / 2);
+        (date, time)
+    }
+}
+
+/// Nombre de jours depuis le 1970-01-01 (algorithme "days from civil").
+#[cfg(feature = "std")]
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(feature = "std")]
+impl DateTime {
+    /// Convertit en `SystemTime`, en considérant l'heure FAT comme UTC.
+    pub fn to_system_time(&self) -> std::time::SystemTime {
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        let secs =
+            days * 86_400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64;
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs.max(0) as u64)
+    }
+
+    /// Construit une date FAT depuis un `SystemTime` (interprété en UTC),
+    /// bornée à la plage représentable.
+    pub fn from_system_time(time: std::time::SystemTime) -> Self {
+        let secs = time
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let min = days_from_civil(1980, 1, 1) * 86_400;
+        let max = days_from_civil(2107, 12, 31) * 86_400 + 86_399;
+        let secs = secs.clamp(min, max);
+
+        // Inverse de days_from_civil
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Traçage des accès bloc : journal des lectures / écritures avec leur
+//! zone du volume (boot, FAT, répertoire, données) et compteurs d'E/S,
+//! lisibles par `Fat32::stats()`.
+
+use crate::{BlockDevice, Error, Fat32};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+/// Zone du volume touchée par un accès.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Region {
+    /// Secteurs réservés : boot sector, FSInfo, copie de secours
+    Boot,
+    Fat,
+    /// Cluster appartenant à un répertoire
+    Dir,
+    /// Cluster de fichier (ou libre)
+    Data,
+    /// Disposition inconnue ou secteur hors du volume
+    Unknown,
+}
+
+impl Region {
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Boot => "boot",
+            Region::Fat => "fat",
+            Region::Dir => "dir",
+            Region::Data => "data",
+            Region::Unknown => "?",
+        }
+    }
+}
+
+/// Disposition du volume, pour classer un LBA par zone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub fat_start: u64,
+    pub data_start: u64,
+    pub sectors_per_cluster: u64,
+    pub total_sectors: u64,
+    /// Clusters des répertoires (racine comprise)
+    pub dir_clusters: BTreeSet<u32>,
+}
+
+impl Layout {
+    pub fn region(&self, lba: u64) -> Region {
+        if lba >= self.total_sectors || self.sectors_per_cluster == 0 {
+            Region::Unknown
+        } else if lba < self.fat_start {
+            Region::Boot
+        } else if lba < self.data_start {
+            Region::Fat
+        } else {
+            let cluster = (lba - self.data_start) / self.sectors_per_cluster + 2;
+            match u32::try_from(cluster) {
+                Ok(c) if self.dir_clusters.contains(&c) => Region::Dir,
+                _ => Region::Data,
+            }
+        }
+    }
+}
+
+/// Compteurs d'E/S.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IoStats {
+    pub reads: u64,
+    pub writes: u64,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Lectures servies par le cache de `TraceDevice::with_cache`
+    pub cache_hits: u64,
+    pub flushes: u64,
+    /// Secteurs signalés libres (`BlockDevice::discard`)
+    pub discarded: u64,
+}
+
+/// Un accès journalisé.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Access {
+    pub write: bool,
+    pub lba: u64,
+    pub sectors: u32,
+    /// Zone d'après `TraceDevice::layout` au moment de l'accès
+    pub region: Region,
+    /// Lecture servie par le cache
+    pub cached: bool,
+}
+
+/// Compte (et, avec `with_log`, journalise) les accès à `inner`.
+///
+/// `Fat32::stats()` le voit à travers les périphériques qui l'enveloppent
+/// (`OffsetDevice`, `OverlayDevice`...). Le cache optionnel (derniers
+/// secteurs lus, écriture immédiate) sert à mesurer ce qu'un cache
+/// apporterait à une commande.
+pub struct TraceDevice<D: BlockDevice> {
+    pub inner: D,
+    pub sector_size: usize,
+    /// Disposition utilisée pour étiqueter les accès (`Fat32::layout()`)
+    pub layout: Option<Layout>,
+    pub stats: IoStats,
+    log: Option<Vec<Access>>,
+    /// `(lba, contenu)`, le plus récent en dernier
+    cache: Vec<(u64, Vec<u8>)>,
+    cache_capacity: usize,
+}
+
+impl<D: BlockDevice> TraceDevice<D> {
+    /// Compteurs seuls, sans journal ni cache.
+    pub fn new(inner: D, sector_size: usize) -> Self {
+        TraceDevice {
+            inner,
+            sector_size,
+            layout: None,
+            stats: IoStats::default(),
+            log: None,
+            cache: Vec::new(),
+            cache_capacity: 0,
+        }
+    }
+
+    /// Active le journal des accès.
+    pub fn with_log(mut self) -> Self {
+        self.log = Some(Vec::new());
+        self
+    }
+
+    /// Cache des `entries` dernières lectures.
+    pub fn with_cache(mut self, entries: usize) -> Self {
+        self.cache_capacity = entries;
+        self
+    }
+
+    /// Accès journalisés depuis la dernière remise à zéro.
+    pub fn log(&self) -> &[Access] {
+        self.log.as_deref().unwrap_or(&[])
+    }
+
+    /// Remet à zéro les compteurs et vide le journal (pas le cache).
+    pub fn reset(&mut self) {
+        self.stats = IoStats::default();
+        if let Some(log) = &mut self.log {
+            log.clear();
+        }
+    }
+
+    fn record(&mut self, write: bool, lba: u64, len: usize, cached: bool) {
+        let bytes = len as u64;
+        if write {
+            self.stats.writes += 1;
+            self.stats.bytes_written += bytes;
+        } else {
+            self.stats.reads += 1;
+            self.stats.bytes_read += bytes;
+            self.stats.cache_hits += u64::from(cached);
+        }
+        let region = self
+            .layout
+            .as_ref()
+            .map_or(Region::Unknown, |l| l.region(lba));
+        let sectors = len.div_ceil(self.sector_size.max(1)) as u32;
+        if let Some(log) = &mut self.log {
+            log.push(Access {
+                write,
+                lba,
+                sectors,
+                region,
+                cached,
+            });
+        }
+    }
+}
+
+impl<D: BlockDevice> BlockDevice for TraceDevice<D> {
+    fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        let hit = self
+            .cache
+            .iter()
+            .position(|(l, data)| *l == lba && data.len() == buf.len());
+        if let Some(i) = hit {
+            let entry = self.cache.remove(i);
+            buf.copy_from_slice(&entry.1);
+            self.cache.push(entry);
+        } else {
+            self.inner.read_sector(lba, buf)?;
+            if self.cache_capacity > 0 {
+                if self.cache.len() == self.cache_capacity {
+                    self.cache.remove(0);
+                }
+                self.cache.push((lba, buf.to_vec()));
+            }
+        }
+        self.record(false, lba, buf.len(), hit.is_some());
+        Ok(())
+    }
+
+    fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        self.inner.write_sector(lba, buf)?;
+        // Toute entrée recouverte par l'écriture est périmée
+        let size = self.sector_size.max(1);
+        let sectors = buf.len().div_ceil(size) as u64;
+        self.cache.retain(|(l, data)| {
+            let end = l + data.len().div_ceil(size) as u64;
+            end <= lba || *l >= lba + sectors
+        });
+        self.record(true, lba, buf.len(), false);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()?;
+        self.stats.flushes += 1;
+        Ok(())
+    }
+
+    fn discard(&mut self, lba: u64, count: u64) -> Result<(), Error> {
+        self.inner.discard(lba, count)?;
+        let size = self.sector_size.max(1);
+        self.cache.retain(|(l, data)| {
+            let end = l + data.len().div_ceil(size) as u64;
+            end <= lba || *l >= lba + count
+        });
+        self.stats.discarded += count;
+        Ok(())
+    }
+
+    fn stats(&self) -> Option<IoStats> {
+        Some(self.stats)
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Compteurs d'E/S du `TraceDevice` monté, directement ou sous des
+    /// périphériques qui l'enveloppent.
+    pub fn stats(&self) -> Option<IoStats> {
+        self.device.stats()
+    }
+
+    /// Disposition du volume. Parcourt toute l'arborescence pour repérer
+    /// les clusters de répertoire.
+    pub fn layout(&mut self) -> Result<Layout, Error> {
+        let mut dirs = Vec::new();
+        for item in self.walk("/")? {
+            let (_, entry) = item?;
+            if entry.is_dir && entry.first_cluster >= 2 {
+                dirs.push(entry.first_cluster);
+            }
+        }
+
+        let mut dir_clusters = BTreeSet::new();
+        let mut chain = Vec::new();
+        for start in core::iter::once(self.boot.root_cluster).chain(dirs) {
+            chain.clear();
+            self.cluster_chain(start, &mut chain)?;
+            dir_clusters.extend(chain.iter().copied());
+        }
+
+        Ok(Layout {
+            fat_start: self.fat_start_lba as u64,
+            data_start: self.data_start_lba as u64,
+            sectors_per_cluster: self.sectors_per_cluster() as u64,
+            total_sectors: self.boot.total_sectors as u64,
+            dir_clusters,
+        })
+    }
+}
@@ -0,0 +1,172 @@
+use crate::dir::{
+    DirEntry, RawSlot, encode_short_name, is_valid_short_name, parse_entry, parse_short_name,
+};
+use crate::fat::FAT_EOC;
+use crate::{BlockDevice, Error, Fat32};
+use alloc::{string::String, vec::Vec};
+
+/// Entrée supprimée (premier octet 0xE5) encore présente dans un répertoire.
+#[derive(Debug, Clone)]
+pub struct DeletedEntry {
+    /// Nom 8.3 avec le premier caractère deviné
+    pub name: String,
+    /// Vrai si le premier caractère provient d'une entrée LFN supprimée
+    /// (sinon il vaut `_`)
+    pub first_char_from_lfn: bool,
+    pub first_cluster: u32,
+    pub size: u32,
+    pub is_dir: bool,
+    /// Vrai si les clusters contigus nécessaires sont encore tous libres
+    pub recoverable: bool,
+    /// Emplacement de l'entrée sur le disque
+    pub(crate) lba: u32,
+    pub(crate) offset: usize,
+    pub(crate) dir_cluster: u32,
+}
+
+/// Devine le premier caractère à partir du premier caractère du nom long.
+fn guess_first_char(lfn_first: Option<u16>) -> Option<u8> {
+    let c = lfn_first?;
+    if c > 0x7F {
+        return None;
+    }
+
+    let mut raw = [b' '; 11];
+    raw[0] = (c as u8).to_ascii_uppercase();
+    is_valid_short_name(&raw).then_some(raw[0])
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Clusters à réallouer pour une entrée : premier cluster et nombre.
+    fn recovery_span(&self, entry: &DeletedEntry) -> (u32, u32) {
+        if entry.first_cluster == 0 {
+            return (0, 0);
+        }
+        let cluster_bytes = self.bytes_per_sector() * self.sectors_per_cluster();
+        let count = entry.size.div_ceil(cluster_bytes).max(1);
+        (entry.first_cluster, count)
+    }
+
+    /// Vrai si les `count` clusters à partir de `first` existent et sont libres.
+    fn span_is_free(&mut self, first: u32, count: u32) -> Result<bool, Error> {
+        if count == 0 {
+            return Ok(true);
+        }
+        let max = self.cluster_count() + 1;
+        if first < 2 || first.saturating_add(count - 1) > max {
+            return Ok(false);
+        }
+        for cluster in first..first + count {
+            if self.read_fat_entry(cluster)? != 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Liste les entrées supprimées d'un répertoire.
+    pub fn list_deleted(&mut self, dir: &str) -> Result<Vec<DeletedEntry>, Error> {
+        let dir_cluster = self.dir_cluster(dir)?;
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain)?;
+        let slots = self.read_slots(&chain)?;
+
+        let mut out = Vec::new();
+        // Premier caractère du nom long qui précède directement l'entrée
+        let mut lfn_first: Option<u16> = None;
+
+        for RawSlot { lba, offset, bytes } in slots {
+            if bytes[0] == 0x00 {
+                break;
+            }
+
+            let attr = bytes[11];
+            if bytes[0] != 0xE5 {
+                lfn_first = None;
+                continue;
+            }
+            if attr == 0x0F {
+                lfn_first = Some(u16::from_le_bytes([bytes[1], bytes[2]]));
+                continue;
+            }
+            if attr & 0x08 != 0 {
+                lfn_first = None;
+                continue;
+            }
+
+            let guess = guess_first_char(lfn_first.take());
+            let mut raw = [0u8; 11];
+            raw.copy_from_slice(&bytes[0..11]);
+            raw[0] = guess.unwrap_or(b'_');
+
+            let high = u16::from_le_bytes([bytes[20], bytes[21]]) as u32;
+            let low = u16::from_le_bytes([bytes[26], bytes[27]]) as u32;
+
+            let mut entry = DeletedEntry {
+                name: parse_short_name(&raw),
+                first_char_from_lfn: guess.is_some(),
+                first_cluster: (high << 16) | low,
+                size: u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]),
+                is_dir: attr & 0x10 != 0,
+                recoverable: false,
+                lba,
+                offset,
+                dir_cluster,
+            };
+            let (first, count) = self.recovery_span(&entry);
+            entry.recoverable = self.span_is_free(first, count)?;
+            out.push(entry);
+        }
+
+        Ok(out)
+    }
+
+    /// Restaure une entrée supprimée sous le nom 8.3 `new_name`.
+    ///
+    /// FAT32 efface la chaîne de clusters à la suppression : on la
+    /// reconstruit en supposant le fichier contigu, ce qui n'est possible
+    /// que si ces clusters sont encore tous libres.
+    pub fn recover(&mut self, entry: &DeletedEntry, new_name: &str) -> Result<DirEntry, Error> {
+        let raw_name = encode_short_name(new_name).ok_or(Error::InvalidName)?;
+        let name = parse_short_name(&raw_name);
+
+        let existing = self.read_dir_cluster(entry.dir_cluster)?;
+        if existing.iter().any(|e| e.matches(&name)) {
+            return Err(Error::AlreadyExists);
+        }
+
+        // L'entrée doit toujours être là, toujours supprimée
+        let mut sector = alloc::vec![0u8; self.bytes_per_sector() as usize];
+        self.device.read_sector(entry.lba as u64, &mut sector)?;
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&sector[entry.offset..entry.offset + 32]);
+        if bytes[0] != 0xE5 || bytes[11] == 0x0F {
+            return Err(Error::NotFound);
+        }
+
+        let (first, count) = self.recovery_span(entry);
+        if !self.span_is_free(first, count)? {
+            return Err(Error::NotRecoverable);
+        }
+
+        for cluster in first..first + count {
+            let next = if cluster + 1 == first + count {
+                FAT_EOC
+            } else {
+                cluster + 1
+            };
+            self.write_fat_entry(cluster, next)?;
+        }
+        self.fsinfo_update(-(count as i64), None)?;
+
+        bytes[0..11].copy_from_slice(&raw_name);
+        self.write_slot(&RawSlot {
+            lba: entry.lba,
+            offset: entry.offset,
+            bytes,
+        })?;
+        self.flush()?;
+
+        parse_entry(&bytes).ok_or(Error::InvalidFs)
+    }
+}
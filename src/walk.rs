@@ -0,0 +1,147 @@
+use crate::dir::DirEntry;
+use crate::{BlockDevice, Error, Fat32};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// Répertoire en cours de parcours.
+struct Frame {
+    path: String,
+    cluster: u32,
+    depth: usize,
+    entries: vec::IntoIter<DirEntry>,
+}
+
+/// Parcours en profondeur d'une arborescence (voir `Fat32::walk`).
+///
+/// Chaque élément est `(chemin complet, entrée)`. Après avoir reçu un
+/// répertoire, l'appelant peut appeler `prune()` pour ne pas y descendre.
+pub struct Walk<'a, D: BlockDevice> {
+    fs: &'a mut Fat32<D>,
+    stack: Vec<Frame>,
+    max_depth: Option<usize>,
+    /// Ancêtres du répertoire de départ (jusqu'à la racine)
+    ancestors: Vec<u32>,
+    /// Dernier répertoire renvoyé, dans lequel on descendra au prochain appel
+    pending: Option<(String, u32, usize)>,
+}
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        String::from(name)
+    } else if parent.ends_with('/') {
+        format!("{parent}{name}")
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+impl<'a, D: BlockDevice> Walk<'a, D> {
+    /// Limite la profondeur : 1 = uniquement le contenu du répertoire de
+    /// départ, 0 = aucune entrée.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        if depth == 0 {
+            self.stack.clear();
+        }
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Ne descend pas dans le dernier répertoire renvoyé.
+    pub fn prune(&mut self) {
+        self.pending = None;
+    }
+
+    /// Vrai si `cluster` est déjà un répertoire ouvert dans la pile (cycle).
+    fn is_ancestor(&self, cluster: u32) -> bool {
+        let root = self.fs.boot.root_cluster;
+        let cluster = if cluster == 0 { root } else { cluster };
+        cluster == root
+            || self.ancestors.contains(&cluster)
+            || self.stack.iter().any(|f| f.cluster == cluster)
+    }
+
+    fn open(&mut self, path: String, cluster: u32, depth: usize) -> Result<(), Error> {
+        let entries = self.fs.read_dir_cluster(cluster)?;
+        self.stack.push(Frame {
+            path,
+            cluster,
+            depth,
+            entries: entries.into_iter(),
+        });
+        Ok(())
+    }
+}
+
+impl<D: BlockDevice> Iterator for Walk<'_, D> {
+    type Item = Result<(String, DirEntry), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((path, cluster, depth)) = self.pending.take()
+            && !self.is_ancestor(cluster)
+            && let Err(e) = self.open(path, cluster, depth)
+        {
+            return Some(Err(e));
+        }
+
+        loop {
+            let frame = self.stack.last_mut()?;
+            let Some(entry) = frame.entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+
+            let path = join(&frame.path, &entry.name);
+            let depth = frame.depth + 1;
+
+            let can_descend = self.max_depth.is_none_or(|max| depth < max);
+            if entry.is_dir && can_descend {
+                self.pending = Some((path.clone(), entry.first_cluster, depth));
+            }
+            return Some(Ok((path, entry)));
+        }
+    }
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Clusters des ancêtres d'un répertoire, en remontant les entrées `..`.
+    fn ancestor_clusters(&mut self, mut cluster: u32) -> Result<Vec<u32>, Error> {
+        let root = self.boot.root_cluster;
+        let mut out = Vec::new();
+        while cluster != root && !out.contains(&cluster) {
+            out.push(cluster);
+            let entries = self.read_dir_cluster(cluster)?;
+            cluster = match entries.iter().find(|e| e.name == "..") {
+                Some(e) if e.first_cluster != 0 => e.first_cluster,
+                _ => root,
+            };
+        }
+        Ok(out)
+    }
+
+    /// Parcourt récursivement (en profondeur) l'arborescence sous `path`.
+    ///
+    /// `.` et `..` sont ignorés, et un sous-répertoire qui pointe vers un
+    /// de ses ancêtres est renvoyé sans qu'on y descende.
+    pub fn walk(&mut self, path: &str) -> Result<Walk<'_, D>, Error> {
+        let cluster = self.dir_cluster(path)?;
+        let base = match path.trim_end_matches('/') {
+            "" if path.starts_with('/') => String::from("/"),
+            "" | "." => String::new(),
+            p => String::from(p),
+        };
+
+        let ancestors = self.ancestor_clusters(cluster)?;
+
+        let mut walk = Walk {
+            fs: self,
+            stack: Vec::new(),
+            max_depth: None,
+            ancestors,
+            pending: None,
+        };
+        walk.open(base, cluster, 0)?;
+        Ok(walk)
+    }
+}
@@ -0,0 +1,501 @@
+use crate::dir::{
+    ATTR_ARCHIVE, ATTR_DIRECTORY, DirEntry, RawSlot, encode_short_name, parse_entry,
+    parse_short_name,
+};
+use crate::time::DateTime;
+use crate::{BlockDevice, Error, Fat32, lfn};
+use alloc::{string::String, vec, vec::Vec};
+
+/// Sépare un chemin en (répertoire parent, nom). Un chemin relatif sans
+/// `/` a pour parent le répertoire courant (`.`).
+pub(crate) fn split_path(path: &str) -> Result<(&str, &str), Error> {
+    let trimmed = path.trim_end_matches('/');
+    let (parent, name) = match trimmed.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((parent, name)) => (parent, name),
+        None => (".", trimmed),
+    };
+    if !lfn::is_valid_long_name(name) {
+        return Err(Error::InvalidName);
+    }
+    Ok((parent, name))
+}
+
+/// Vrai si `name` ne peut pas être stocké tel quel en 8.3 (casse comprise).
+fn needs_lfn(name: &str) -> bool {
+    match encode_short_name(name) {
+        Some(raw) => parse_short_name(&raw) != name,
+        None => true,
+    }
+}
+
+/// Nombre d'entrées de 32 octets occupées par un nom (8.3 + LFN).
+pub fn slots_for_name(name: &str) -> usize {
+    if needs_lfn(name) {
+        1 + lfn::slot_count(name)
+    } else {
+        1
+    }
+}
+
+/// Construit une entrée 8.3 complète.
+pub(crate) fn build_short_entry(
+    short: &[u8; 11],
+    attr: u8,
+    first_cluster: u32,
+    size: u32,
+    time: DateTime,
+) -> [u8; 32] {
+    let (date, clock) = time.to_dos();
+    let mut e = [0u8; 32];
+    e[0..11].copy_from_slice(short);
+    e[11] = attr;
+    e[14..16].copy_from_slice(&clock.to_le_bytes());
+    e[16..18].copy_from_slice(&date.to_le_bytes());
+    e[18..20].copy_from_slice(&date.to_le_bytes());
+    e[20..22].copy_from_slice(&((first_cluster >> 16) as u16).to_le_bytes());
+    e[22..24].copy_from_slice(&clock.to_le_bytes());
+    e[24..26].copy_from_slice(&date.to_le_bytes());
+    e[26..28].copy_from_slice(&(first_cluster as u16).to_le_bytes());
+    e[28..32].copy_from_slice(&size.to_le_bytes());
+    e
+}
+
+/// Recherche `needed` entrées libres consécutives ; retourne l'indice de
+/// la première, ou la longueur de la série libre en fin de répertoire.
+pub(crate) fn find_free_run(slots: &[RawSlot], needed: usize) -> Result<usize, usize> {
+    let mut run = 0;
+    for (i, slot) in slots.iter().enumerate() {
+        if slot.bytes[0] == 0x00 || slot.bytes[0] == 0xE5 {
+            run += 1;
+            if run == needed {
+                return Ok(i + 1 - needed);
+            }
+        } else {
+            run = 0;
+        }
+    }
+    Err(run)
+}
+
+/// Entrées à écrire pour ajouter `name` à un répertoire dont `slots` sont
+/// les entrées actuelles : LFN éventuelles puis `template`, dont le nom
+/// 8.3 est remplacé par celui généré pour `name`.
+pub(crate) fn entry_slots_for(
+    slots: &[RawSlot],
+    name: &str,
+    template: [u8; 32],
+) -> Result<Vec<[u8; 32]>, Error> {
+    let short = match needs_lfn(name) {
+        false => encode_short_name(name).ok_or(Error::InvalidName)?,
+        true => {
+            let existing: Vec<[u8; 11]> = slots
+                .iter()
+                .filter(|s| s.bytes[0] != 0x00 && s.bytes[0] != 0xE5 && s.bytes[11] != 0x0F)
+                .map(|s| {
+                    let mut raw = [0u8; 11];
+                    raw.copy_from_slice(&s.bytes[0..11]);
+                    raw
+                })
+                .collect();
+            lfn::short_alias(name, &existing).ok_or(Error::AlreadyExists)?
+        }
+    };
+
+    let mut new_slots = if needs_lfn(name) {
+        lfn::build_slots(name, &short)
+    } else {
+        Vec::new()
+    };
+    let mut short_entry = template;
+    short_entry[0..11].copy_from_slice(&short);
+    new_slots.push(short_entry);
+    Ok(new_slots)
+}
+
+/// Ajout d'une entrée dans un répertoire, décidé avant toute écriture.
+pub(crate) struct InsertPlan {
+    /// Entrées à écrire : LFN éventuelles puis 8.3
+    pub new_slots: Vec<[u8; 32]>,
+    /// Indice de la première, parmi les entrées actuelles suivies de
+    /// celles des clusters ajoutés
+    pub start: usize,
+    /// Clusters vides à ajouter au répertoire
+    pub grow: u32,
+}
+
+impl InsertPlan {
+    /// Entrée ajoutée, sous le nom demandé.
+    pub fn entry(&self, name: &str) -> Result<DirEntry, Error> {
+        let last = self.new_slots.last().ok_or(Error::InvalidFs)?;
+        let mut entry = parse_entry(last).ok_or(Error::InvalidFs)?;
+        entry.name = name.into();
+        Ok(entry)
+    }
+}
+
+/// Prépare l'ajout de `name` (voir `entry_slots_for`) dans un répertoire
+/// dont `slots` sont les entrées actuelles : des entrées libres sont
+/// réutilisées, sinon le répertoire est agrandi de clusters de
+/// `per_cluster` entrées.
+pub(crate) fn plan_insert(
+    slots: &[RawSlot],
+    name: &str,
+    template: [u8; 32],
+    per_cluster: usize,
+) -> Result<InsertPlan, Error> {
+    let new_slots = entry_slots_for(slots, name, template)?;
+    let needed = new_slots.len();
+    let (start, grow) = match find_free_run(slots, needed) {
+        Ok(start) => (start, 0),
+        Err(tail) => (
+            slots.len() - tail,
+            (needed - tail).div_ceil(per_cluster) as u32,
+        ),
+    };
+    Ok(InsertPlan {
+        new_slots,
+        start,
+        grow,
+    })
+}
+
+/// Contenu du secteur `index` d'un cluster qui commence par `data` (le
+/// reste est à zéro).
+pub(crate) fn fill_cluster_sector(data: &[u8], index: usize, sector: &mut [u8]) {
+    let len = sector.len();
+    let start = (index * len).min(data.len());
+    let end = ((index + 1) * len).min(data.len());
+    sector.fill(0);
+    sector[..end - start].copy_from_slice(&data[start..end]);
+}
+
+/// Valeur de `..` désignant le répertoire `dir` (la racine est codée 0).
+pub(crate) fn parent_ref(dir: u32, root_cluster: u32) -> u32 {
+    if dir == root_cluster { 0 } else { dir }
+}
+
+/// Entrées `.` et `..` d'un nouveau répertoire.
+pub(crate) fn dir_skeleton(cluster: u32, parent_ref: u32, time: DateTime) -> [u8; 64] {
+    let mut content = [0u8; 64];
+    content[..32].copy_from_slice(&build_short_entry(
+        b".          ",
+        ATTR_DIRECTORY,
+        cluster,
+        0,
+        time,
+    ));
+    content[32..].copy_from_slice(&build_short_entry(
+        b"..         ",
+        ATTR_DIRECTORY,
+        parent_ref,
+        0,
+        time,
+    ));
+    content
+}
+
+/// Remplace le premier cluster d'une entrée 8.3.
+pub(crate) fn set_first_cluster(entry: &mut [u8; 32], cluster: u32) {
+    entry[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+    entry[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+}
+
+/// Contenu d'un répertoire vide : rien d'autre que `.` et `..`.
+pub(crate) fn is_empty_dir(children: &[DirEntry]) -> bool {
+    children.iter().all(|e| e.name == "." || e.name == "..")
+}
+
+/// Renommage dans le même répertoire qui ne change que la casse :
+/// l'ancienne entrée correspond aussi au nouveau nom.
+pub(crate) fn renames_in_place(src: (u32, &str), dst: (u32, &str)) -> bool {
+    src.0 == dst.0 && src.1.eq_ignore_ascii_case(dst.1)
+}
+
+impl<D: BlockDevice> Fat32<D> {
+    /// Écrit `data` au début d'un cluster (le reste est mis à zéro).
+    pub(crate) fn write_cluster_data(&mut self, cluster: u32, data: &[u8]) -> Result<(), Error> {
+        self.mark_dirty()?;
+        let geometry = self.geometry();
+        let mut sector = vec![0u8; geometry.bytes_per_sector as usize];
+        for (i, lba) in geometry.cluster_sectors(cluster).enumerate() {
+            fill_cluster_sector(data, i, &mut sector);
+            self.device.write_sector(lba as u64, &sector)?;
+        }
+        Ok(())
+    }
+
+    /// Nombre d'entrées libres à la fin d'un répertoire (réutilisables
+    /// sans l'agrandir).
+    #[cfg(feature = "std")]
+    pub(crate) fn trailing_free_slots(&mut self, dir_cluster: u32) -> Result<usize, Error> {
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain)?;
+        let slots = self.read_slots(&chain)?;
+        Ok(find_free_run(&slots, usize::MAX).unwrap_or_else(|run| run))
+    }
+
+    /// Ajoute une entrée (et ses entrées LFN) dans un répertoire, en
+    /// réutilisant des entrées libres ou en agrandissant le répertoire.
+    /// Le nom 8.3 de `template` est remplacé par celui généré pour `name`.
+    pub(crate) fn insert_entry(
+        &mut self,
+        dir_cluster: u32,
+        name: &str,
+        template: [u8; 32],
+    ) -> Result<DirEntry, Error> {
+        if self
+            .read_dir_cluster(dir_cluster)?
+            .iter()
+            .any(|e| e.matches(name))
+        {
+            return Err(Error::AlreadyExists);
+        }
+
+        let mut chain = Vec::new();
+        self.cluster_chain(dir_cluster, &mut chain)?;
+        let mut slots = self.read_slots(&chain)?;
+
+        let per_cluster = self.geometry().cluster_bytes() / 32;
+        let plan = plan_insert(&slots, name, template, per_cluster)?;
+        if plan.grow > 0 {
+            // Agrandit le répertoire avec des clusters vides
+            let added = self.alloc_clusters(plan.grow)?;
+            for &c in &added {
+                self.write_cluster_data(c, &[])?;
+            }
+            self.write_fat_entry(chain[chain.len() - 1], added[0])?;
+            slots.extend(self.read_slots(&added)?);
+        }
+
+        for (slot, bytes) in slots[plan.start..].iter_mut().zip(&plan.new_slots) {
+            slot.bytes = *bytes;
+            self.write_slot(slot)?;
+        }
+        plan.entry(name)
+    }
+
+    /// Marque comme supprimées les entrées de `name` dans un répertoire.
+    fn erase_slots(&mut self, slots: &mut [RawSlot]) -> Result<(), Error> {
+        for slot in slots.iter_mut() {
+            slot.bytes[0] = 0xE5;
+            self.write_slot(slot)?;
+        }
+        Ok(())
+    }
+
+    /// Crée un fichier contenant `data` (le chemin parent doit exister).
+    ///
+    /// Les clusters sont alloués et remplis avant l'ajout de l'entrée : une
+    /// interruption ne laisse au pire qu'une chaîne perdue.
+    pub fn create_file(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        time: DateTime,
+    ) -> Result<DirEntry, Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        if self.read_dir_cluster(dir)?.iter().any(|e| e.matches(name)) {
+            return Err(Error::AlreadyExists);
+        }
+        let size = u32::try_from(data.len()).map_err(|_| Error::NoSpace)?;
+
+        let cluster_bytes = self.geometry().cluster_bytes();
+        let clusters = self.alloc_clusters(data.len().div_ceil(cluster_bytes) as u32)?;
+        for (&c, chunk) in clusters.iter().zip(data.chunks(cluster_bytes)) {
+            self.write_cluster_data(c, chunk)?;
+        }
+
+        let first = clusters.first().copied().unwrap_or(0);
+        let template = build_short_entry(&[b' '; 11], ATTR_ARCHIVE, first, size, time);
+        match self.insert_entry(dir, name, template) {
+            Ok(entry) => {
+                self.flush()?;
+                Ok(entry)
+            }
+            Err(e) => {
+                self.free_chain(first)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Crée un répertoire vide (avec ses entrées `.` et `..`).
+    pub fn create_dir(&mut self, path: &str, time: DateTime) -> Result<DirEntry, Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        if self.read_dir_cluster(dir)?.iter().any(|e| e.matches(name)) {
+            return Err(Error::AlreadyExists);
+        }
+
+        let cluster = self.alloc_clusters(1)?[0];
+        let content = dir_skeleton(cluster, parent_ref(dir, self.boot.root_cluster), time);
+        self.write_cluster_data(cluster, &content)?;
+
+        let template = build_short_entry(&[b' '; 11], ATTR_DIRECTORY, cluster, 0, time);
+        match self.insert_entry(dir, name, template) {
+            Ok(entry) => {
+                self.flush()?;
+                Ok(entry)
+            }
+            Err(e) => {
+                self.free_chain(cluster)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Crée un répertoire et ses parents manquants (comme `mkdir -p`).
+    /// Un répertoire déjà existant n'est pas une erreur.
+    pub fn create_dir_all(&mut self, path: &str, time: DateTime) -> Result<(), Error> {
+        let mut prefix = String::new();
+        if path.starts_with('/') {
+            prefix.push('/');
+        }
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            prefix.push_str(part);
+            match self.resolve_path(&prefix) {
+                Ok(e) if e.is_dir => {}
+                Ok(_) => return Err(Error::AlreadyExists),
+                Err(Error::NotFound) => {
+                    self.create_dir(&prefix, time)?;
+                }
+                Err(e) => return Err(e),
+            }
+            prefix.push('/');
+        }
+        Ok(())
+    }
+
+    /// Supprime un fichier : ses entrées sont marquées 0xE5 puis sa chaîne
+    /// est libérée (une interruption ne laisse qu'une chaîne perdue).
+    pub fn remove_file(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        let mut slots = self.find_slots(dir, name)?;
+        let entry = parse_entry(&slots[slots.len() - 1].bytes).ok_or(Error::InvalidFs)?;
+        if entry.is_dir {
+            return Err(Error::IsDirectory);
+        }
+
+        self.erase_slots(&mut slots)?;
+        self.free_chain(entry.first_cluster)?;
+        self.flush()
+    }
+
+    /// Supprime un répertoire vide (hors `.` et `..`).
+    pub fn remove_dir(&mut self, path: &str) -> Result<(), Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        let mut slots = self.find_slots(dir, name)?;
+        let entry = parse_entry(&slots[slots.len() - 1].bytes).ok_or(Error::InvalidFs)?;
+        if !entry.is_dir {
+            return Err(Error::NotDirectory);
+        }
+        if entry.first_cluster < 2 {
+            return Err(Error::InvalidFs);
+        }
+        if !is_empty_dir(&self.read_dir_cluster(entry.first_cluster)?) {
+            return Err(Error::NotEmpty);
+        }
+
+        self.erase_slots(&mut slots)?;
+        self.free_chain(entry.first_cluster)?;
+        if self.cwd_cluster == entry.first_cluster {
+            self.cwd_cluster = self.boot.root_cluster;
+        }
+        self.flush()
+    }
+
+    /// Supprime un répertoire et tout son contenu (comme `rm -r`).
+    pub fn remove_dir_all(&mut self, path: &str) -> Result<(), Error> {
+        let items: Vec<(String, DirEntry)> = self.walk(path)?.collect::<Result<_, _>>()?;
+        // Parcours en profondeur inversé : le contenu avant son répertoire
+        for (item, entry) in items.iter().rev() {
+            if entry.is_dir {
+                self.remove_dir(item)?;
+            } else {
+                self.remove_file(item)?;
+            }
+        }
+        self.remove_dir(path)
+    }
+
+    /// Renomme ou déplace une entrée. La destination ne doit pas exister ;
+    /// les dates, attributs et clusters sont conservés. Un répertoire
+    /// déplacé voit son entrée `..` mise à jour.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<DirEntry, Error> {
+        let (src_parent, src_name) = split_path(from)?;
+        let (dst_parent, dst_name) = split_path(to)?;
+        let src_dir = self.dir_cluster(src_parent)?;
+        let dst_dir = self.dir_cluster(dst_parent)?;
+
+        let mut slots = self.find_slots(src_dir, src_name)?;
+        let template = slots[slots.len() - 1].bytes;
+        let entry = parse_entry(&template).ok_or(Error::InvalidFs)?;
+
+        if entry.is_dir && src_dir != dst_dir {
+            // Interdit de déplacer un répertoire dans sa propre descendance
+            let mut cluster = dst_dir;
+            while cluster != self.boot.root_cluster {
+                if cluster == entry.first_cluster {
+                    return Err(Error::InvalidName);
+                }
+                let parent = self
+                    .read_dir_cluster(cluster)?
+                    .into_iter()
+                    .find(|e| e.name == "..")
+                    .ok_or(Error::InvalidFs)?;
+                cluster = self.dir_cluster_of(&parent);
+            }
+        }
+
+        // Même répertoire, simple changement de casse : l'ancienne entrée
+        // correspond aussi au nouveau nom, on la retire d'abord.
+        let same_entry = renames_in_place((src_dir, src_name), (dst_dir, dst_name));
+        let original: Vec<[u8; 32]> = slots.iter().map(|s| s.bytes).collect();
+        if same_entry {
+            self.erase_slots(&mut slots)?;
+        }
+        let moved = match self.insert_entry(dst_dir, dst_name, template) {
+            Ok(e) => e,
+            Err(e) => {
+                if same_entry {
+                    for (slot, bytes) in slots.iter_mut().zip(original) {
+                        slot.bytes = bytes;
+                        self.write_slot(slot)?;
+                    }
+                }
+                return Err(e);
+            }
+        };
+        if !same_entry {
+            self.erase_slots(&mut slots)?;
+        }
+
+        if entry.is_dir && src_dir != dst_dir {
+            let mut dot_dot = self.find_slots(entry.first_cluster, "..")?;
+            let slot = dot_dot.last_mut().ok_or(Error::InvalidFs)?;
+            set_first_cluster(&mut slot.bytes, parent_ref(dst_dir, self.boot.root_cluster));
+            self.write_slot(slot)?;
+        }
+        self.flush()?;
+        Ok(moved)
+    }
+
+    /// Met à jour la date de modification (et d'accès) d'une entrée.
+    pub fn set_modified(&mut self, path: &str, time: DateTime) -> Result<(), Error> {
+        let (parent, name) = split_path(path)?;
+        let dir = self.dir_cluster(parent)?;
+        let mut slots = self.find_slots(dir, name)?;
+        let (date, clock) = time.to_dos();
+
+        let slot = slots.last_mut().ok_or(Error::InvalidFs)?;
+        slot.bytes[18..20].copy_from_slice(&date.to_le_bytes());
+        slot.bytes[22..24].copy_from_slice(&clock.to_le_bytes());
+        slot.bytes[24..26].copy_from_slice(&date.to_le_bytes());
+        self.write_slot(slot)?;
+        self.flush()
+    }
+}
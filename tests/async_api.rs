@@ -0,0 +1,298 @@
+use fat32_rust::asynch::{AsyncBlockDevice, AsyncFat32, Blocking};
+use fat32_rust::check::CheckMode;
+use fat32_rust::ram::RamDisk;
+use fat32_rust::trace::TraceDevice;
+use fat32_rust::{BlockDevice, Error, Fat32, MountOptions};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+mod common;
+
+use common::WHEN;
+
+/// Exécuteur minimal : repoll jusqu'à la fin (les futures de test ne
+/// dépendent d'aucun événement extérieur).
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+/// Rend la main une fois avant de se terminer, comme une attente de DMA.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// RAM « asynchrone » : chaque accès suspend la tâche une fois.
+struct AsyncRam {
+    disk: RamDisk,
+    pending: u64,
+}
+
+impl AsyncBlockDevice for AsyncRam {
+    async fn read_sector(&mut self, lba: u64, buf: &mut [u8]) -> Result<(), Error> {
+        YieldOnce(false).await;
+        self.pending += 1;
+        self.disk.read_sector(lba, buf)
+    }
+
+    async fn write_sector(&mut self, lba: u64, buf: &[u8]) -> Result<(), Error> {
+        YieldOnce(false).await;
+        self.pending += 1;
+        self.disk.write_sector(lba, buf)
+    }
+}
+
+fn sample() -> RamDisk {
+    let mut fs = common::mounted();
+    fs.create_dir("/Docs", WHEN).unwrap();
+    fs.create_file("/Docs/Rapport annuel.txt", &[b'r'; 3000], WHEN)
+        .unwrap();
+    fs.create_file("/empty", b"", WHEN).unwrap();
+    fs.unmount().unwrap()
+}
+
+#[test]
+fn reads_match_sync_api() {
+    let dev = AsyncRam {
+        disk: sample(),
+        pending: 0,
+    };
+    block_on(async {
+        let mut fs = AsyncFat32::new(dev).await.unwrap();
+        assert!(!fs.was_dirty);
+
+        let names: Vec<String> = fs
+            .list_dir("/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["Docs", "empty"]);
+
+        let data = fs
+            .read_file("/docs/../Docs/rapport annuel.txt")
+            .await
+            .unwrap();
+        assert_eq!(data, [b'r'; 3000]);
+        assert!(fs.read_file("/empty").await.unwrap().is_empty());
+        assert!(matches!(
+            fs.read_file("/Docs").await,
+            Err(Error::IsDirectory)
+        ));
+        assert!(matches!(fs.list_dir("/nope").await, Err(Error::NotFound)));
+
+        let mut file = fs.open_file("/Docs/Rapport annuel.txt").await.unwrap();
+        let mut buf = [0u8; 700];
+        assert_eq!(file.read(&mut buf).await.unwrap(), 700);
+        assert_eq!(file.size(), 3000);
+        assert!(fs.device.pending > 0);
+    });
+}
+
+#[test]
+fn writes_are_visible_to_sync_api() {
+    let dev = block_on(async {
+        let mut fs = AsyncFat32::new(Blocking(sample())).await.unwrap();
+        fs.create_file("/Docs/Nouveau fichier.bin", &[9u8; 5000], WHEN)
+            .await
+            .unwrap();
+        assert!(matches!(
+            fs.create_file("/docs/NOUVEAU FICHIER.BIN", b"", WHEN).await,
+            Err(Error::AlreadyExists)
+        ));
+        // Assez d'entrées pour agrandir la racine
+        for i in 0..50 {
+            let name = format!("/fichier numéro {i}.txt");
+            fs.create_file(&name, name.as_bytes(), WHEN).await.unwrap();
+        }
+        fs.remove_file("/empty").await.unwrap();
+        assert!(fs.dirty);
+        fs.unmount().await.unwrap().0
+    });
+
+    let mut fs = Fat32::new(dev).unwrap();
+    assert!(!fs.was_dirty);
+    assert_eq!(
+        fs.read_file("/Docs/Nouveau fichier.bin").unwrap(),
+        [9u8; 5000]
+    );
+    assert_eq!(
+        fs.read_file("/fichier numéro 49.txt").unwrap(),
+        "/fichier numéro 49.txt".as_bytes()
+    );
+    assert!(matches!(fs.resolve_path("/empty"), Err(Error::NotFound)));
+    let mut root = Vec::new();
+    fs.cluster_chain(fs.boot.root_cluster, &mut root).unwrap();
+    assert!(root.len() > 1);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn allocation_follows_fsinfo_hint() {
+    block_on(async {
+        let mut fs = AsyncFat32::new(Blocking(sample())).await.unwrap();
+        let a = fs.create_file("/a.bin", &[1u8; 9000], WHEN).await.unwrap();
+        let mut freed = Vec::new();
+        fs.cluster_chain(a.first_cluster, &mut freed).await.unwrap();
+        fs.remove_file("/a.bin").await.unwrap();
+
+        // Les clusters libérés ne sont pas repris tout de suite
+        let b = fs.create_file("/b.bin", &[2u8; 100], WHEN).await.unwrap();
+        assert!(b.first_cluster > *freed.last().unwrap());
+
+        let info = fs.read_fsinfo().await.unwrap();
+        assert_eq!(info.next_free, b.first_cluster + 1);
+        let fat = fs.read_fat_copy(fs.active_fat()).await.unwrap();
+        let free = fat
+            .iter()
+            .skip(2)
+            .filter(|&&v| v & 0x0FFF_FFFF == 0)
+            .count();
+        assert_eq!(info.free_count as usize, free);
+
+        // Au démontage, le FSInfo est recalculé comme par l'API synchrone
+        let dev = fs.unmount().await.unwrap().0;
+        let mut fs = Fat32::new(dev).unwrap();
+        let info = fs.read_fsinfo().unwrap();
+        assert_eq!(info.next_free, freed[0]);
+        assert_eq!(info.free_count, fs.free_cluster_count().unwrap());
+    });
+}
+
+#[test]
+fn directories_can_be_created_moved_and_removed() {
+    let dev = block_on(async {
+        let mut fs = AsyncFat32::new(Blocking(sample())).await.unwrap();
+        fs.create_dir("/Archives", WHEN).await.unwrap();
+        fs.create_dir("/Archives/2023", WHEN).await.unwrap();
+        assert!(matches!(
+            fs.create_dir("/archives", WHEN).await,
+            Err(Error::AlreadyExists)
+        ));
+
+        // Déplacement d'un fichier, puis d'un répertoire vers un autre parent
+        fs.rename("/Docs/Rapport annuel.txt", "/Archives/2023/Rapport.txt")
+            .await
+            .unwrap();
+        fs.rename("/Archives/2023", "/Docs/Ancien 2023")
+            .await
+            .unwrap();
+        assert!(matches!(
+            fs.rename("/Docs", "/Docs/Ancien 2023/Docs").await,
+            Err(Error::InvalidName)
+        ));
+        // Simple changement de casse
+        fs.rename("/empty", "/EMPTY").await.unwrap();
+
+        assert!(matches!(
+            fs.remove_dir("/Docs/Ancien 2023").await,
+            Err(Error::NotEmpty)
+        ));
+        assert!(matches!(
+            fs.remove_dir("/EMPTY").await,
+            Err(Error::NotDirectory)
+        ));
+        assert!(matches!(
+            fs.remove_file("/Archives").await,
+            Err(Error::IsDirectory)
+        ));
+        fs.remove_dir("/Archives").await.unwrap();
+        fs.unmount().await.unwrap().0
+    });
+
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(
+        fs.read_file("/Docs/Ancien 2023/Rapport.txt").unwrap(),
+        [b'r'; 3000]
+    );
+    // `..` du répertoire déplacé désigne son nouveau parent
+    fs.change_dir("/Docs/Ancien 2023").unwrap();
+    fs.change_dir("..").unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/Docs");
+    let names: Vec<String> = fs
+        .list_root()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(names, ["Docs", "EMPTY"]);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn files_can_be_written_at_any_position() {
+    let dev = block_on(async {
+        let mut fs = AsyncFat32::new(Blocking(sample())).await.unwrap();
+        let mut file = fs.open_file("/empty").await.unwrap();
+        file.write(b"debut").await.unwrap();
+        // Au-delà de la fin : l'écart est rempli de zéros
+        file.set_position(5000);
+        file.write(&[7u8; 100]).await.unwrap();
+        file.set_position(2);
+        file.write(b"BU").await.unwrap();
+        assert_eq!(file.position(), 4);
+        assert_eq!(file.size(), 5100);
+        file.close().await.unwrap();
+        fs.unmount().await.unwrap().0
+    });
+
+    let mut expected = b"deBUt".to_vec();
+    expected.resize(5000, 0);
+    expected.extend_from_slice(&[7u8; 100]);
+    let mut fs = Fat32::new(dev).unwrap();
+    assert!(!fs.was_dirty);
+    assert_eq!(fs.read_file("/empty").unwrap(), expected);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn damaged_primary_mounts_from_backup() {
+    let damaged = || {
+        let mut disk = sample();
+        disk.write_sector(0, &[0xFF; 512]).unwrap();
+        Blocking(TraceDevice::new(disk, 512))
+    };
+    let options = MountOptions {
+        backup_boot_fallback: true,
+        discard: true,
+    };
+
+    let mut disk = block_on(async {
+        assert!(AsyncFat32::new(damaged()).await.is_err());
+
+        let mut fs = AsyncFat32::mount(damaged(), options).await.unwrap();
+        assert!(fs.boot_from_backup);
+        assert!(!fs.was_dirty);
+        assert_eq!(
+            fs.read_file("/Docs/Rapport annuel.txt").await.unwrap(),
+            [b'r'; 3000]
+        );
+        fs.remove_file("/Docs/Rapport annuel.txt").await.unwrap();
+        // Clusters libérés signalés au périphérique
+        assert!(fs.device.0.stats.discarded > 0);
+        fs.unmount().await.unwrap().0.inner
+    });
+
+    // Le secteur 0 endommagé n'a pas été réécrit
+    let mut sector = [0u8; 512];
+    disk.read_sector(0, &mut sector).unwrap();
+    assert_eq!(sector, [0xFF; 512]);
+}
@@ -0,0 +1,89 @@
+use fat32_rust::{BlockDevice, Fat32, MountOptions, ram::RamDisk};
+
+mod common;
+
+use common::WHEN;
+
+/// Formate une image en mémoire et écrase son secteur 0.
+fn image_with_damaged_boot() -> RamDisk {
+    let mut dev = common::formatted();
+    dev.write_sector(0, &[0xFF; 512]).unwrap();
+    dev
+}
+
+const FALLBACK: MountOptions = MountOptions {
+    backup_boot_fallback: true,
+    discard: false,
+};
+
+#[test]
+fn damaged_primary_fails_without_fallback() {
+    let dev = image_with_damaged_boot();
+    assert!(Fat32::new(dev).is_err());
+}
+
+#[test]
+fn damaged_primary_mounts_from_backup() {
+    let dev = image_with_damaged_boot();
+    let mut fs = Fat32::mount(dev, FALLBACK).unwrap();
+
+    assert!(fs.boot_from_backup);
+    assert_eq!(fs.boot.root_cluster, 2);
+    assert!(fs.list_root().unwrap().is_empty());
+}
+
+#[test]
+fn restore_boot_sector_rewrites_primary() {
+    let dev = image_with_damaged_boot();
+    let mut fs = Fat32::mount(dev, FALLBACK).unwrap();
+    fs.restore_boot_sector().unwrap();
+    assert!(!fs.boot_from_backup);
+
+    let mut primary = [0u8; 512];
+    let mut backup = [0u8; 512];
+    fs.device.read_sector(0, &mut primary).unwrap();
+    fs.device.read_sector(6, &mut backup).unwrap();
+    assert_eq!(primary, backup);
+
+    let fs = Fat32::new(fs.device).unwrap();
+    assert!(!fs.boot_from_backup);
+}
+
+#[test]
+fn restore_refuses_invalid_backup() {
+    let mut dev = image_with_damaged_boot();
+    let mut boot = [0u8; 512];
+    dev.read_sector(6, &mut boot).unwrap();
+    dev.write_sector(0, &boot).unwrap();
+    dev.write_sector(6, &[0u8; 512]).unwrap();
+
+    let mut fs = Fat32::new(dev).unwrap();
+    assert!(fs.restore_boot_sector().is_err());
+
+    let mut primary = [0u8; 512];
+    fs.device.read_sector(0, &mut primary).unwrap();
+    assert_eq!(primary, boot);
+}
+
+#[test]
+fn restore_adopts_backup_layout() {
+    let mut fs = common::mounted();
+    fs.create_file("/keep.txt", b"layout", WHEN).unwrap();
+    let (fat_start, data_start) = (fs.fat_start_lba, fs.data_start_lba);
+    let mut dev = fs.unmount().unwrap();
+
+    // Boot sector principal valide mais décalé (secteurs réservés)
+    let mut boot = [0u8; 512];
+    dev.read_sector(0, &mut boot).unwrap();
+    boot[14..16].copy_from_slice(&30u16.to_le_bytes());
+    dev.write_sector(0, &boot).unwrap();
+
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.fat_start_lba, 30);
+    fs.restore_boot_sector().unwrap();
+    assert_eq!(
+        (fs.fat_start_lba, fs.data_start_lba),
+        (fat_start, data_start)
+    );
+    assert_eq!(fs.read_file("/keep.txt").unwrap(), b"layout");
+}
@@ -0,0 +1,145 @@
+use fat32_rust::check::{CheckMode, Problem};
+use fat32_rust::{BlockDevice, Fat32, ram::RamDisk};
+
+mod common;
+
+const EOC: u32 = 0x0FFF_FFFF;
+
+/// Écrit une entrée 8.3 brute dans le slot `index` d'un répertoire.
+fn put_entry(
+    fs: &mut Fat32<RamDisk>,
+    dir_cluster: u32,
+    index: usize,
+    name: &[u8; 11],
+    attr: u8,
+    cluster: u32,
+    size: u32,
+) {
+    let lba = fs.cluster_to_lba(dir_cluster) as u64 + (index / 16) as u64;
+    let off = (index % 16) * 32;
+    let mut buf = [0u8; 512];
+    fs.device.read_sector(lba, &mut buf).unwrap();
+    let e = &mut buf[off..off + 32];
+    e.fill(0);
+    e[0..11].copy_from_slice(name);
+    e[11] = attr;
+    e[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+    e[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+    e[28..32].copy_from_slice(&size.to_le_bytes());
+    fs.device.write_sector(lba, &buf).unwrap();
+}
+
+#[test]
+fn fresh_volume_is_clean() {
+    let mut fs = common::mounted();
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.is_clean(), "{:?}", report.problems);
+}
+
+#[test]
+fn lost_chain_is_reported_then_freed() {
+    let mut fs = common::mounted();
+    fs.write_fat_entry(5, 6).unwrap();
+    fs.write_fat_entry(6, EOC).unwrap();
+
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.problems.contains(&Problem::LostChain {
+        start: 5,
+        clusters: 2
+    }));
+    assert!(!report.repaired);
+    assert_ne!(fs.read_fat_entry(5).unwrap(), 0);
+
+    let report = fs.check(CheckMode::Repair).unwrap();
+    assert!(report.repaired);
+    assert_eq!(fs.read_fat_entry(5).unwrap(), 0);
+    assert_eq!(fs.read_fat_entry(6).unwrap(), 0);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn cross_link_and_size_mismatch_are_repaired() {
+    let mut fs = common::mounted();
+    // A : 3 clusters pour 100 octets ; B partage le cluster 4 de A
+    fs.write_fat_entry(3, 4).unwrap();
+    fs.write_fat_entry(4, 5).unwrap();
+    fs.write_fat_entry(5, EOC).unwrap();
+    put_entry(&mut fs, 2, 0, b"A       TXT", 0x20, 3, 100);
+    put_entry(&mut fs, 2, 1, b"B       TXT", 0x20, 4, 512);
+
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.problems.iter().any(|p| matches!(
+        p,
+        Problem::SizeMismatch { path, clusters: 3, .. } if path == "/A.TXT"
+    )));
+    assert!(
+        report
+            .problems
+            .iter()
+            .any(|p| matches!(p, Problem::CrossLinked { .. }))
+    );
+
+    fs.check(CheckMode::Repair).unwrap();
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.is_clean(), "{:?}", report.problems);
+    assert_eq!(fs.read_file("/A.TXT").unwrap().len(), 100);
+}
+
+#[test]
+fn bad_dot_entries_and_short_names_are_repaired() {
+    let mut fs = common::mounted();
+    fs.write_fat_entry(3, EOC).unwrap();
+    put_entry(&mut fs, 2, 0, b"sub        ", 0x10, 3, 0);
+    put_entry(&mut fs, 3, 0, b".          ", 0x10, 3, 0);
+    put_entry(&mut fs, 3, 1, b"..         ", 0x10, 9, 0);
+
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.problems.contains(&Problem::InvalidShortName {
+        path: "/sub".into()
+    }));
+    assert!(report.problems.iter().any(|p| matches!(
+        p,
+        Problem::BadDotEntry {
+            name: "..",
+            found: Some(9),
+            expected: 0,
+            ..
+        }
+    )));
+
+    fs.check(CheckMode::Repair).unwrap();
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+    fs.change_dir("/SUB").unwrap();
+    fs.change_dir("..").unwrap();
+}
+
+#[test]
+fn fat_copy_and_fsinfo_mismatch_are_repaired() {
+    let mut fs = common::mounted();
+    // Écrit directement dans la seconde FAT seulement
+    let second_fat = (fs.fat_start_lba + fs.boot.sectors_per_fat) as u64;
+    let mut buf = [0u8; 512];
+    fs.device.read_sector(second_fat, &mut buf).unwrap();
+    buf[40..44].copy_from_slice(&EOC.to_le_bytes());
+    fs.device.write_sector(second_fat, &buf).unwrap();
+
+    // FSInfo volontairement faux
+    let mut info = fs.read_fsinfo().unwrap();
+    info.free_count -= 7;
+    fs.write_fsinfo(&info).unwrap();
+
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(report.problems.contains(&Problem::FatMismatch {
+        copy: 1,
+        entries: 1
+    }));
+    assert!(
+        report
+            .problems
+            .iter()
+            .any(|p| matches!(p, Problem::BadFreeCount { .. }))
+    );
+
+    fs.check(CheckMode::Repair).unwrap();
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
@@ -0,0 +1,188 @@
+use fat32_rust::{Fat32, std_support::StdBlockDevice};
+use std::process::Command;
+
+mod common;
+
+use common::WHEN;
+
+/// Image contenant "/Notes v2.txt" et "/DIR".
+fn sample_image(name: &str) -> String {
+    let path = common::image_file(name, 32768);
+    let mut fs = Fat32::new(StdBlockDevice::open(&path, 512).unwrap()).unwrap();
+    fs.create_file("/Notes v2.txt", b"hello", WHEN).unwrap();
+    fs.create_dir("/DIR", WHEN).unwrap();
+    fs.unmount().unwrap();
+    path
+}
+
+fn cli(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(args)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.code().unwrap(), stdout)
+}
+
+#[test]
+fn json_ls_and_stat() {
+    let image = sample_image("cli_json");
+
+    let (code, out) = cli(&["--json", &image, "ls", "/"]);
+    assert_eq!(code, 0);
+    assert!(out.starts_with("[{\"name\":\"Notes v2.txt\",\"short_name\":\"NOTESV~1.TXT\""));
+    assert!(out.contains("\"modified\":\"2024-02-29T12:00:00\""));
+    assert!(out.contains("\"name\":\"DIR\",\"short_name\":\"DIR\",\"is_dir\":true"));
+
+    let (code, out) = cli(&[&image, "--json", "stat", "/notes v2.txt"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("\"size\":5"));
+    assert!(out.contains("\"chain_length\":1,\"fragments\":1,\"clusters\":[3]"));
+}
+
+#[test]
+fn exit_codes_follow_error_variants() {
+    let image = sample_image("cli_exit");
+
+    let (code, out) = cli(&["--json", &image, "stat", "/missing"]);
+    assert_eq!(code, 5);
+    assert_eq!(
+        out.trim(),
+        r#"{"command":"stat","error":"NotFound","context":"/missing","exit_code":5}"#
+    );
+
+    assert_eq!(cli(&[&image, "bogus"]).0, 2);
+    assert_eq!(cli(&["/nonexistent/image.img", "ls"]).0, 3);
+    assert_eq!(cli(&[&image, "fsck"]).0, 0);
+
+    // Chaîne perdue : fsck signale un problème (code 1)
+    let mut fs = Fat32::new(StdBlockDevice::open(&image, 512).unwrap()).unwrap();
+    fs.write_fat_entry(100, 0x0FFF_FFFF).unwrap();
+    let (code, out) = cli(&["--json", &image, "fsck"]);
+    assert_eq!(code, 1);
+    assert!(out.contains("\"kind\":\"LostChain\",\"cluster\":100"));
+}
+
+#[test]
+fn dry_run_leaves_image_untouched() {
+    let image = sample_image("cli_dry_run");
+    let before = std::fs::read(&image).unwrap();
+
+    let (code, out) = cli(&[&image, "rm", "--dry-run", "/Notes v2.txt"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("free 1 cluster(s): 3"));
+    assert!(out.contains("remove entry NOTESV~1.TXT"));
+    let (code, out) = cli(&[&image, "mkdir", "--dry-run", "-p", "/DIR/a/b"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("allocate 2 cluster(s)"));
+    assert_eq!(std::fs::read(&image).unwrap(), before);
+
+    assert_eq!(cli(&[&image, "rmdir", "/missing"]).0, 5);
+    assert_eq!(cli(&[&image, "mv", "/Notes v2.txt", "/DIR"]).0, 0);
+    assert_eq!(cli(&[&image, "rm", "/DIR"]).0, 2);
+    assert_eq!(cli(&[&image, "rmdir", "/DIR"]).0, 10);
+    assert_eq!(cli(&[&image, "rmdir", "/DIR/Notes v2.txt"]).0, 14);
+    assert_eq!(cli(&[&image, "fsck"]).0, 0);
+}
+
+#[test]
+fn run_script_stops_on_first_failure() {
+    let image = sample_image("cli_run");
+    let script = common::tmp_path("cli_run.txt");
+    std::fs::write(
+        &script,
+        "# commentaire\nmkdir \"/My Dir\"\ncd '/My Dir'\ntouch a\\ b.txt\npwd\nrm /missing\ntouch never\n",
+    )
+    .unwrap();
+
+    let (code, out) = cli(&[&image, "run", &script]);
+    assert_eq!(code, 5);
+    assert_eq!(out, "/My Dir\n");
+    let (_, out) = cli(&[&image, "ls", "/My Dir"]);
+    assert!(out.contains("a b.txt"));
+    assert!(!out.contains("never"));
+}
+
+#[test]
+fn raw_inspection_commands() {
+    let image = sample_image("cli_raw");
+
+    let mut fs = Fat32::new(StdBlockDevice::open(&image, 512).unwrap()).unwrap();
+    let slots = fs.entry_slots("/Notes v2.txt").unwrap();
+    assert_eq!(slots.len(), 2);
+    assert!(slots[0].is_lfn() && !slots[1].is_lfn());
+    assert_eq!(slots[1].entry().unwrap().short_name, "NOTESV~1.TXT");
+
+    let (code, out) = cli(&[&image, "dirent", "/Notes v2.txt"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("LFN: sequence 1 (last), checksum"));
+    assert!(out.contains("chars \"Notes v2.txt\""));
+    assert!(out.contains("8.3: name \"NOTESV~1TXT\", attr 0x20 (----A)"));
+    assert!(out.contains("cluster 3 (high 0x0000, low 0x0003), size 5"));
+
+    let (code, out) = cli(&[&image, "fat", "3"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("entry 3 at sector 32 offset 12: 0x0fffffff (end of chain)"));
+    assert!(out.contains("chain: 3 -> EOC"));
+
+    let (code, out) = cli(&[&image, "cluster", "3"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("|hello...........|"));
+    let (_, out) = cli(&[&image, "sector", "0"]);
+    assert!(out.starts_with("sector 0: boot sector\n00000000  eb 58 90"));
+    assert_eq!(cli(&[&image, "cluster", "1"]).0, 2);
+}
+
+#[test]
+fn overlay_keeps_image_untouched() {
+    let image = sample_image("cli_overlay");
+    let delta = common::tmp_path("cli_overlay.delta");
+    let _ = std::fs::remove_file(&delta);
+    let before = std::fs::read(&image).unwrap();
+
+    let with_overlay = |args: &[&str]| {
+        let mut all = vec!["--overlay", delta.as_str(), image.as_str()];
+        all.extend_from_slice(args);
+        cli(&all)
+    };
+    assert_eq!(with_overlay(&["mkdir", "/Scratch"]).0, 0);
+    assert_eq!(with_overlay(&["rm", "/Notes v2.txt"]).0, 0);
+    assert_eq!(std::fs::read(&image).unwrap(), before);
+
+    // Le delta persiste d'une invocation à l'autre
+    let (code, out) = with_overlay(&["ls", "/"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("Scratch") && !out.contains("Notes"));
+    let (_, out) = with_overlay(&["overlay", "status"]);
+    assert!(out.contains("sector(s) changed"));
+
+    assert_eq!(with_overlay(&["overlay", "discard"]).0, 0);
+    assert!(
+        cli(&["--overlay", &delta, &image, "ls", "/"])
+            .1
+            .contains("Notes")
+    );
+
+    assert_eq!(with_overlay(&["mkdir", "/Kept"]).0, 0);
+    let (code, out) = with_overlay(&["overlay", "commit"]);
+    assert_eq!(code, 0);
+    assert!(out.contains("written to"));
+    assert!(cli(&[&image, "ls", "/"]).1.contains("Kept"));
+    assert_eq!(cli(&[&image, "fsck"]).0, 0);
+    assert_eq!(cli(&[&image, "overlay"]).0, 2);
+}
+
+#[test]
+fn trace_goes_to_stderr() {
+    let image = sample_image("cli_trace");
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(["--trace", "--json", &image, "ls", "/"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().starts_with('['));
+    let log = String::from_utf8(output.stderr).unwrap();
+    assert!(log.lines().all(|l| l.starts_with("trace: ")));
+    assert!(log.contains(" dir"));
+    assert!(log.contains("read(s)") && log.contains("0 write(s)"));
+}
@@ -0,0 +1,68 @@
+//! Outils partagés par les tests d'intégration (chaque fichier de test
+//! n'en utilise qu'une partie).
+#![allow(dead_code)]
+
+use fat32_rust::Fat32;
+use fat32_rust::format::{FormatOptions, format};
+use fat32_rust::ram::RamDisk;
+use fat32_rust::std_support::StdBlockDevice;
+use fat32_rust::time::DateTime;
+use std::path::PathBuf;
+
+/// Date appliquée aux entrées créées par les tests.
+pub const WHEN: DateTime = DateTime {
+    year: 2024,
+    month: 2,
+    day: 29,
+    hour: 12,
+    minute: 0,
+    second: 0,
+};
+
+/// Volume FAT32 vide de 16 Mo (32768 secteurs de 512 octets), en mémoire.
+pub fn formatted() -> RamDisk {
+    let mut dev = RamDisk::new(32768, 512);
+    format(&mut dev, 32768, &FormatOptions::default()).unwrap();
+    dev
+}
+
+/// `formatted()`, monté.
+pub fn mounted() -> Fat32<RamDisk> {
+    Fat32::new(formatted()).unwrap()
+}
+
+/// Chemin `name` dans le répertoire temporaire de cargo.
+pub fn tmp_path(name: &str) -> String {
+    format!("{}/{name}", env!("CARGO_TARGET_TMPDIR"))
+}
+
+/// Fichier `<name>.img` de `sectors` secteurs de 512 octets, rempli de
+/// zéros (recréé à chaque appel).
+pub fn blank_file(name: &str, sectors: u32) -> String {
+    let path = tmp_path(&format!("{name}.img"));
+    let file = std::fs::File::create(&path).unwrap();
+    file.set_len(sectors as u64 * 512).unwrap();
+    path
+}
+
+/// `blank_file` formaté en FAT32 avec les options par défaut.
+pub fn image_file(name: &str, sectors: u32) -> String {
+    let path = blank_file(name, sectors);
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+    format(&mut dev, sectors, &FormatOptions::default()).unwrap();
+    path
+}
+
+/// `image_file`, monté.
+pub fn mounted_file(name: &str, sectors: u32) -> Fat32<StdBlockDevice> {
+    let path = image_file(name, sectors);
+    Fat32::new(StdBlockDevice::open(&path, 512).unwrap()).unwrap()
+}
+
+/// Répertoire hôte vide `name` (recréé à chaque appel).
+pub fn host_dir(name: &str) -> PathBuf {
+    let path = PathBuf::from(tmp_path(name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
@@ -0,0 +1,37 @@
+use fat32_rust::dir::normalize_path;
+
+mod common;
+
+use common::WHEN;
+
+#[test]
+fn normalize() {
+    assert_eq!(normalize_path("/", ""), "/");
+    assert_eq!(normalize_path("/a", "b//c/./"), "/a/b/c");
+    assert_eq!(normalize_path("/a/b", "../../.."), "/");
+    assert_eq!(normalize_path("/a", "/x/../y"), "/y");
+    assert_eq!(normalize_path("/a", "b/../c"), "/a/c");
+}
+
+#[test]
+fn cd_tracks_path() {
+    let mut fs = common::mounted();
+    fs.create_dir_all("/Program Files/app", WHEN).unwrap();
+    fs.create_dir("/b", WHEN).unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/");
+
+    fs.change_dir("program files//APP/.").unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/Program Files/app");
+
+    // `a/../b` est résolu lexicalement, comme avec `cd`
+    fs.change_dir("/Program Files/app/../../b").unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/b");
+    fs.change_dir("../../..").unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/");
+
+    // Le chemin suit un déplacement du répertoire courant
+    fs.change_dir("/Program Files/app").unwrap();
+    fs.rename("/Program Files", "/b/Apps").unwrap();
+    assert_eq!(fs.current_dir().unwrap(), "/b/Apps/app");
+    assert_eq!(fs.list_cwd().unwrap().len(), 2);
+}
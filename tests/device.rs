@@ -0,0 +1,136 @@
+use fat32_rust::device::{OffsetDevice, ReadOnlyDevice, SectorSizeAdapter};
+use fat32_rust::format::{FormatOptions, format};
+use fat32_rust::ram::RamDisk;
+use fat32_rust::trace::TraceDevice;
+use fat32_rust::{BlockDevice, Error, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+/// Disque de 40000 secteurs avec une table MBR : une partition FAT32
+/// (type 0x0C) de 32768 secteurs à partir du secteur 2048.
+fn partitioned_disk() -> RamDisk {
+    let mut disk = RamDisk::new(40000, 512);
+    let mut mbr = [0u8; 512];
+    mbr[446 + 4] = 0x0C;
+    mbr[446 + 8..446 + 12].copy_from_slice(&2048u32.to_le_bytes());
+    mbr[446 + 12..446 + 16].copy_from_slice(&32768u32.to_le_bytes());
+    mbr[510] = 0x55;
+    mbr[511] = 0xAA;
+    disk.write_sector(0, &mbr).unwrap();
+    disk
+}
+
+#[test]
+fn partition_is_formatted_and_mounted_in_place() {
+    let mut part = OffsetDevice::mbr_partition(partitioned_disk(), 0).unwrap();
+    assert_eq!((part.base, part.len), (2048, 32768));
+    format(&mut part, 32768, &FormatOptions::default()).unwrap();
+
+    let mut fs = Fat32::new(part).unwrap();
+    fs.create_file("/hello.txt", b"partition", WHEN).unwrap();
+    let disk = fs.device.inner;
+
+    // Le boot sector est au début de la partition, le MBR est intact
+    assert_eq!(
+        &disk.as_bytes()[2048 * 512 + 510..2048 * 512 + 512],
+        &[0x55, 0xAA]
+    );
+    assert_eq!(disk.as_bytes()[446 + 4], 0x0C);
+
+    let mut fs = Fat32::new(OffsetDevice::mbr_partition(disk, 0).unwrap()).unwrap();
+    assert_eq!(fs.read_file("/hello.txt").unwrap(), b"partition");
+
+    let mut buf = [0u8; 512];
+    assert!(matches!(
+        fs.device.read_sector(32768, &mut buf),
+        Err(Error::Io)
+    ));
+    let disk = fs.device.inner;
+    assert!(matches!(
+        OffsetDevice::mbr_partition(disk, 1),
+        Err(Error::NotFound)
+    ));
+}
+
+#[test]
+fn read_only_device_rejects_writes() {
+    let mut fs = common::mounted();
+    fs.create_file("/keep.txt", b"keep", WHEN).unwrap();
+    let before = fs.device.as_bytes().to_vec();
+
+    let mut fs = Fat32::new(ReadOnlyDevice::new(fs.device)).unwrap();
+    assert_eq!(fs.read_file("/keep.txt").unwrap(), b"keep");
+    assert!(matches!(
+        fs.create_file("/new.txt", b"x", WHEN),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(fs.remove_file("/keep.txt"), Err(Error::ReadOnly)));
+    assert_eq!(fs.device.inner.as_bytes(), &before[..]);
+}
+
+#[test]
+fn logical_sectors_on_4k_physical_sectors() {
+    let physical = RamDisk::new(32768 / 8, 4096);
+    let mut dev = SectorSizeAdapter::new(physical, 512, 4096).unwrap();
+    format(&mut dev, 32768, &FormatOptions::default()).unwrap();
+
+    let mut fs = Fat32::new(dev).unwrap();
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 241) as u8).collect();
+    fs.create_dir("/dir", WHEN).unwrap();
+    fs.create_file("/dir/data.bin", &data, WHEN).unwrap();
+
+    // Même image vue directement en secteurs de 512 octets
+    let bytes = fs.device.inner.into_inner();
+    let mut fs = Fat32::new(RamDisk::from_bytes(bytes, 512)).unwrap();
+    assert_eq!(fs.read_file("/dir/data.bin").unwrap(), data);
+
+    assert!(SectorSizeAdapter::new(RamDisk::new(1, 4096), 512, 1000).is_err());
+}
+
+#[test]
+fn whole_physical_sectors_skip_the_read() {
+    let physical = TraceDevice::new(RamDisk::new(4, 4096), 4096);
+    let mut dev = SectorSizeAdapter::new(physical, 512, 4096).unwrap();
+
+    // Deux secteurs physiques entiers : aucune relecture
+    dev.write_sector(8, &[0x11; 8192]).unwrap();
+    assert_eq!((dev.inner.stats.reads, dev.inner.stats.writes), (0, 2));
+
+    // À cheval sur deux secteurs physiques partiels : chacun est relu
+    dev.inner.reset();
+    dev.write_sector(4, &[0x22; 4096]).unwrap();
+    assert_eq!((dev.inner.stats.reads, dev.inner.stats.writes), (2, 2));
+
+    let mut buf = vec![0u8; 512 * 16];
+    dev.read_sector(0, &mut buf).unwrap();
+    assert!(buf[..4 * 512].iter().all(|&b| b == 0));
+    assert!(buf[4 * 512..12 * 512].iter().all(|&b| b == 0x22));
+    assert!(buf[12 * 512..].iter().all(|&b| b == 0x11));
+}
+
+#[test]
+fn std_device_flush_and_discard() {
+    use fat32_rust::std_support::StdBlockDevice;
+
+    let path = common::tmp_path("discard.img");
+    std::fs::write(&path, vec![0xAAu8; 64 * 1024]).unwrap();
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+    dev.write_sector(3, &[0x11; 512]).unwrap();
+    dev.flush().unwrap();
+    dev.discard(8, 64).unwrap();
+
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 64 * 1024);
+    let mut buf = [0u8; 512];
+    dev.read_sector(3, &mut buf).unwrap();
+    assert_eq!(buf, [0x11; 512]);
+    dev.read_sector(72, &mut buf).unwrap();
+    assert_eq!(buf, [0xAA; 512]);
+
+    // Trou percé sous Linux (lu comme des zéros)
+    if cfg!(target_os = "linux") {
+        dev.read_sector(8, &mut buf).unwrap();
+        assert_eq!(buf, [0u8; 512]);
+    }
+}
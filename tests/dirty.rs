@@ -0,0 +1,78 @@
+use fat32_rust::boot::FsInfo;
+use fat32_rust::check::CheckMode;
+use fat32_rust::dirty::{BPB_STATE_DIRTY, BPB_STATE_OFFSET, FAT_CLEAN_SHUTDOWN};
+use fat32_rust::ram::RamDisk;
+use fat32_rust::{BlockDevice, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+/// (FAT[1] sans le bit « propre », octet d'état du BPB marqué sale)
+fn marks(fs: &mut Fat32<RamDisk>) -> (bool, bool) {
+    let fat1 = fs.read_fat_entry(1).unwrap();
+    let mut boot = [0u8; 512];
+    fs.device.read_sector(0, &mut boot).unwrap();
+    (
+        fat1 & FAT_CLEAN_SHUTDOWN == 0,
+        boot[BPB_STATE_OFFSET] & BPB_STATE_DIRTY != 0,
+    )
+}
+
+#[test]
+fn first_write_marks_dirty_and_unmount_clears() {
+    let mut fs = common::mounted();
+    assert!(!fs.was_dirty);
+    fs.list_root().unwrap();
+    assert_eq!(marks(&mut fs), (false, false));
+
+    fs.create_file("/a.txt", b"abc", WHEN).unwrap();
+    assert!(fs.dirty);
+    assert_eq!(marks(&mut fs), (true, true));
+
+    let dev = fs.unmount().unwrap();
+    let mut fs = Fat32::new(dev).unwrap();
+    assert!(!fs.was_dirty);
+    assert_eq!(marks(&mut fs), (false, false));
+    assert_eq!(fs.read_file("/a.txt").unwrap(), b"abc");
+}
+
+#[test]
+fn unclean_volume_is_reported_until_checked() {
+    let mut fs = common::mounted();
+    fs.create_dir("/d", WHEN).unwrap();
+    // Pas d'unmount : comme une coupure de courant
+    let mut fs = Fat32::new(fs.device).unwrap();
+    assert!(fs.was_dirty);
+
+    // Un démontage sans vérification laisse le volume sale
+    fs.create_file("/d/x", b"x", WHEN).unwrap();
+    let mut fs = Fat32::new(fs.unmount().unwrap()).unwrap();
+    assert!(fs.was_dirty);
+
+    // La vérification seule ne touche à rien...
+    fs.check(CheckMode::ReportOnly).unwrap();
+    assert_eq!(marks(&mut fs), (true, true));
+    // ... la réparation efface l'indicateur
+    assert!(fs.check(CheckMode::Repair).unwrap().is_clean());
+    assert!(!fs.was_dirty);
+    assert_eq!(marks(&mut fs), (false, false));
+    assert!(!Fat32::new(fs.device).unwrap().was_dirty);
+}
+
+#[test]
+fn unmount_rewrites_fsinfo() {
+    let mut fs = common::mounted();
+    fs.create_file("/big", &[7u8; 20000], WHEN).unwrap();
+    fs.write_fsinfo(&FsInfo {
+        free_count: 3,
+        next_free: 2,
+    })
+    .unwrap();
+
+    let mut fs = Fat32::new(fs.unmount().unwrap()).unwrap();
+    let info = fs.read_fsinfo().unwrap();
+    assert_eq!(info.free_count, fs.free_cluster_count().unwrap());
+    assert_eq!(fs.read_fat_entry(info.next_free).unwrap() & 0x0FFF_FFFF, 0);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
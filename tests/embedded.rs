@@ -0,0 +1,91 @@
+#![cfg(feature = "embedded")]
+
+use embedded_io::{Read, Seek, SeekFrom, Write};
+use embedded_storage::{ReadStorage, Storage};
+use fat32_rust::check::CheckMode;
+use fat32_rust::embedded::{ReadStorageDevice, StorageDevice};
+use fat32_rust::format::{FormatOptions, format};
+use fat32_rust::{Error, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+/// Zone de flash simulée, adressée à l'octet.
+struct Flash(Vec<u8>);
+
+impl ReadStorage for Flash {
+    type Error = ();
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), ()> {
+        let start = offset as usize;
+        let src = self.0.get(start..start + bytes.len()).ok_or(())?;
+        bytes.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Storage for Flash {
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), ()> {
+        let start = offset as usize;
+        self.0
+            .get_mut(start..start + bytes.len())
+            .ok_or(())?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+fn flash_volume() -> Fat32<StorageDevice<Flash>> {
+    let mut dev = StorageDevice::new(Flash(vec![0; 32768 * 512]), 512);
+    let sectors = dev.sector_count() as u32;
+    format(&mut dev, sectors, &FormatOptions::default()).unwrap();
+    Fat32::new(dev).unwrap()
+}
+
+#[test]
+fn file_implements_embedded_io() {
+    let mut fs = flash_volume();
+    fs.create_file("/config.txt", b"", WHEN).unwrap();
+
+    let mut file = fs.open_file("/config.txt").unwrap();
+    file.write_all(b"mode=fast\n").unwrap();
+    file.write_all(b"level=3\n").unwrap();
+    assert_eq!(file.seek(SeekFrom::End(-2)).unwrap(), 16);
+    file.write_all(b"9\n").unwrap();
+    file.flush().unwrap();
+
+    file.rewind().unwrap();
+    let mut buf = [0u8; 18];
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"mode=fast\nlevel=9\n");
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+    assert!(matches!(
+        file.seek(SeekFrom::Current(-100)),
+        Err(Error::InvalidSeek)
+    ));
+    assert_eq!(
+        embedded_io::Error::kind(&Error::InvalidSeek),
+        embedded_io::ErrorKind::InvalidInput
+    );
+    file.close().unwrap();
+
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn read_storage_is_read_only() {
+    let fs = flash_volume();
+    let image = fs.unmount().unwrap().storage;
+
+    let mut fs = Fat32::new(ReadStorageDevice::new(image, 512)).unwrap();
+    assert!(fs.list_root().unwrap().is_empty());
+    assert!(matches!(
+        fs.create_file("/x", b"x", WHEN),
+        Err(Error::ReadOnly)
+    ));
+}
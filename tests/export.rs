@@ -0,0 +1,143 @@
+use fat32_rust::host::export;
+use fat32_rust::lfn::lfn_checksum;
+use fat32_rust::time::DateTime;
+use fat32_rust::{BlockDevice, Error, Fat32, std_support::StdBlockDevice};
+use std::path::PathBuf;
+
+mod common;
+
+const EOC: u32 = 0x0FFF_FFFF;
+
+fn tmp(name: &str) -> PathBuf {
+    let path = PathBuf::from(common::tmp_path(name));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+/// Entrée 8.3 brute (précédée si besoin de son entrée LFN, 13 caractères max).
+fn entry(long: Option<&str>, short: &[u8; 11], attr: u8, cluster: u32, size: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    if let Some(long) = long {
+        let mut lfn = [0u8; 32];
+        lfn[0] = 0x41;
+        lfn[11] = 0x0F;
+        lfn[13] = lfn_checksum(short);
+        let mut chars: Vec<u16> = long.encode_utf16().collect();
+        chars.push(0);
+        chars.resize(13, 0xFFFF);
+        let offsets = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+        for (c, off) in chars.iter().zip(offsets) {
+            lfn[off..off + 2].copy_from_slice(&c.to_le_bytes());
+        }
+        out.extend_from_slice(&lfn);
+    }
+
+    let mut e = [0u8; 32];
+    e[0..11].copy_from_slice(short);
+    e[11] = attr;
+    let (date, time) = DateTime {
+        year: 2024,
+        month: 5,
+        day: 17,
+        hour: 10,
+        minute: 20,
+        second: 30,
+    }
+    .to_dos();
+    e[22..24].copy_from_slice(&time.to_le_bytes());
+    e[24..26].copy_from_slice(&date.to_le_bytes());
+    e[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+    e[28..32].copy_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&e);
+    out
+}
+
+/// Image avec "/Long Name.txt" (lecture seule) et "/SUB/FILE.TXT".
+fn sample_image(name: &str, long_name: &str) -> Fat32<StdBlockDevice> {
+    let mut fs = common::mounted_file(name, 32768);
+
+    let write_cluster = |fs: &mut Fat32<StdBlockDevice>, cluster: u32, bytes: &[u8]| {
+        fs.write_fat_entry(cluster, EOC).unwrap();
+        let mut sector = [0u8; 512];
+        sector[..bytes.len()].copy_from_slice(bytes);
+        let lba = fs.cluster_to_lba(cluster) as u64;
+        fs.device.write_sector(lba, &sector).unwrap();
+    };
+
+    let mut root = entry(Some(long_name), b"LONGNA~1TXT", 0x21, 3, 5);
+    root.extend(entry(None, b"SUB        ", 0x10, 4, 0));
+    write_cluster(&mut fs, 2, &root);
+    write_cluster(&mut fs, 3, b"hello");
+
+    let mut sub = entry(None, b".          ", 0x10, 4, 0);
+    sub.extend(entry(None, b"..         ", 0x10, 0, 0));
+    sub.extend(entry(None, b"FILE    TXT", 0x20, 5, 6));
+    write_cluster(&mut fs, 4, &sub);
+    write_cluster(&mut fs, 5, b"inside");
+    fs
+}
+
+#[test]
+fn long_names_are_listed_and_resolved() {
+    let mut fs = sample_image("export_lfn", "Long Name.txt");
+    let root = fs.list_root().unwrap();
+    let e = root
+        .iter()
+        .find(|e| e.short_name == "LONGNA~1.TXT")
+        .unwrap();
+    assert_eq!(e.name, "Long Name.txt");
+    assert!(e.is_read_only());
+
+    assert_eq!(fs.read_file("/long name.txt").unwrap(), b"hello");
+    assert_eq!(fs.read_file("/LONGNA~1.TXT").unwrap(), b"hello");
+}
+
+#[test]
+fn export_copies_tree_with_times_and_read_only() {
+    let mut fs = sample_image("export_tree", "Long Name.txt");
+    let dest = tmp("export_tree_out");
+
+    let stats = export(&mut fs, "/", &dest).unwrap();
+    assert_eq!((stats.files, stats.dirs, stats.bytes), (2, 1, 11));
+
+    let long = dest.join("Long Name.txt");
+    assert_eq!(std::fs::read(&long).unwrap(), b"hello");
+    assert_eq!(std::fs::read(dest.join("SUB/FILE.TXT")).unwrap(), b"inside");
+
+    let meta = std::fs::metadata(&long).unwrap();
+    assert!(meta.permissions().readonly());
+    let expected = DateTime {
+        year: 2024,
+        month: 5,
+        day: 17,
+        hour: 10,
+        minute: 20,
+        second: 30,
+    };
+    assert_eq!(meta.modified().unwrap(), expected.to_system_time());
+}
+
+#[test]
+fn export_of_subdirectory_and_single_file() {
+    let mut fs = sample_image("export_sub", "Long Name.txt");
+    let dest = tmp("export_sub_out");
+
+    export(&mut fs, "/SUB", &dest).unwrap();
+    assert_eq!(std::fs::read(dest.join("FILE.TXT")).unwrap(), b"inside");
+
+    let stats = export(&mut fs, "/SUB/FILE.TXT", &dest.join("single")).unwrap();
+    assert_eq!(stats.files, 1);
+    assert!(dest.join("single/FILE.TXT").exists());
+}
+
+#[test]
+fn export_refuses_path_traversal_names() {
+    let mut fs = sample_image("export_evil", "../evil");
+    let dest = tmp("export_evil_out");
+
+    assert!(matches!(
+        export(&mut fs, "/", &dest),
+        Err(Error::InvalidName)
+    ));
+    assert!(!dest.exists());
+}
@@ -1,9 +1,35 @@
-use fat32_rust::{Fat32, std_support::StdBlockDevice};
+use fat32_rust::Fat32;
+use fat32_rust::ram::RamDisk;
+use fat32_rust::time::DateTime;
+
+mod common;
+
+/// Même contenu que l'image de test décrite dans le README, construite en
+/// mémoire : README.TXT à la racine et DIR1/FILE1.TXT.
+fn test_image() -> Fat32<RamDisk> {
+    let when = DateTime {
+        year: 2024,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+    let mut fs = common::mounted();
+    fs.create_file("/README.TXT", b"Hello from FAT32 root\n", when)
+        .unwrap();
+    fs.create_dir("/DIR1", when).unwrap();
+    fs.create_file("/DIR1/FILE1.TXT", b"Ceci est un fichier dans DIR1\n", when)
+        .unwrap();
+
+    // Remonté depuis les octets, comme une image chargée en mémoire
+    let bytes = fs.device.into_inner();
+    Fat32::new(RamDisk::from_bytes(bytes, 512)).unwrap()
+}
 
 #[test]
 fn list_root_contains_readme_and_dir1() {
-    let dev = StdBlockDevice::open("images/test_fat32.img", 512).unwrap();
-    let mut fs = Fat32::new(dev).unwrap();
+    let mut fs = test_image();
 
     let entries = fs.list_root().unwrap();
 
@@ -13,8 +39,7 @@ fn list_root_contains_readme_and_dir1() {
 
 #[test]
 fn read_root_readme() {
-    let dev = StdBlockDevice::open("images/test_fat32.img", 512).unwrap();
-    let mut fs = Fat32::new(dev).unwrap();
+    let mut fs = test_image();
 
     let data = fs.read_file("/README.TXT").unwrap();
     let s = String::from_utf8_lossy(&data);
@@ -24,8 +49,7 @@ fn read_root_readme() {
 
 #[test]
 fn cd_and_relative_read() {
-    let dev = StdBlockDevice::open("images/test_fat32.img", 512).unwrap();
-    let mut fs = Fat32::new(dev).unwrap();
+    let mut fs = test_image();
 
     fs.change_dir("/DIR1").unwrap();
     let data = fs.read_file("FILE1.TXT").unwrap();
@@ -36,8 +60,7 @@ fn cd_and_relative_read() {
 
 #[test]
 fn cd_dotdot_goes_back_to_root() {
-    let dev = StdBlockDevice::open("images/test_fat32.img", 512).unwrap();
-    let mut fs = Fat32::new(dev).unwrap();
+    let mut fs = test_image();
 
     fs.change_dir("/DIR1").unwrap();
     fs.change_dir("..").unwrap();
@@ -48,8 +71,7 @@ fn cd_dotdot_goes_back_to_root() {
 
 #[test]
 fn ls_cwd_after_cd_dir1_contains_file1() {
-    let dev = StdBlockDevice::open("images/test_fat32.img", 512).unwrap();
-    let mut fs = Fat32::new(dev).unwrap();
+    let mut fs = test_image();
 
     fs.change_dir("/DIR1").unwrap();
     let entries = fs.list_cwd().unwrap();
@@ -0,0 +1,120 @@
+use fat32_rust::{BlockDevice, Fat32, ram::RamDisk};
+
+mod common;
+
+/// Formate une image en mémoire puis modifie les ext_flags du BPB.
+fn image_with_ext_flags(ext_flags: u16) -> RamDisk {
+    let mut dev = common::formatted();
+
+    let mut boot = [0u8; 512];
+    dev.read_sector(0, &mut boot).unwrap();
+    boot[40..42].copy_from_slice(&ext_flags.to_le_bytes());
+    dev.write_sector(0, &boot).unwrap();
+    dev
+}
+
+#[test]
+fn mirrored_writes_reach_every_copy() {
+    let dev = image_with_ext_flags(0x0000);
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.active_fat(), 0);
+
+    fs.write_fat_entry(10, 0x0FFF_FFFF).unwrap();
+    assert!(fs.diff_fats().unwrap().is_empty());
+    assert_eq!(fs.read_fat_copy(1).unwrap()[10], 0x0FFF_FFFF);
+}
+
+#[test]
+fn active_fat_is_used_when_mirroring_is_disabled() {
+    // Miroir désactivé, FAT #1 active
+    let dev = image_with_ext_flags(0x0081);
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.active_fat(), 1);
+
+    fs.write_fat_entry(10, 11).unwrap();
+    assert_eq!(fs.read_fat_entry(10).unwrap(), 11);
+    assert_eq!(fs.read_fat_copy(0).unwrap()[10], 0);
+
+    let diffs = fs.diff_fats().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].cluster, 10);
+    assert_eq!(diffs[0].values, vec![0, 11]);
+
+    fs.repair_fats(1).unwrap();
+    assert!(fs.diff_fats().unwrap().is_empty());
+    assert_eq!(fs.read_fat_copy(0).unwrap()[10], 11);
+}
+
+#[test]
+fn invalid_active_fat_is_rejected() {
+    let dev = image_with_ext_flags(0x0085);
+    assert!(Fat32::new(dev).is_err());
+}
+
+/// Volume FAT32 minimal en secteurs de 4096 octets (le formateur ne fait
+/// que du 512) : 8 secteurs réservés, 2 FAT d'un secteur, racine vide.
+fn image_4k() -> RamDisk {
+    const SECTOR: usize = 4096;
+    let mut dev = RamDisk::new(256, SECTOR);
+
+    let mut boot = vec![0u8; SECTOR];
+    boot[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+    boot[11..13].copy_from_slice(&(SECTOR as u16).to_le_bytes());
+    boot[13] = 1; // secteurs par cluster
+    boot[14..16].copy_from_slice(&8u16.to_le_bytes());
+    boot[16] = 2; // FATs
+    boot[21] = 0xF8;
+    boot[32..36].copy_from_slice(&256u32.to_le_bytes());
+    boot[36..40].copy_from_slice(&1u32.to_le_bytes());
+    boot[44..48].copy_from_slice(&2u32.to_le_bytes());
+    boot[48..50].copy_from_slice(&1u16.to_le_bytes());
+    boot[50..52].copy_from_slice(&6u16.to_le_bytes());
+    boot[510] = 0x55;
+    boot[511] = 0xAA;
+    dev.write_sector(0, &boot).unwrap();
+    dev.write_sector(6, &boot).unwrap();
+
+    let mut info = vec![0u8; SECTOR];
+    info[0..4].copy_from_slice(&0x4161_5252u32.to_le_bytes());
+    info[484..488].copy_from_slice(&0x6141_7272u32.to_le_bytes());
+    info[488..492].copy_from_slice(&245u32.to_le_bytes());
+    info[492..496].copy_from_slice(&3u32.to_le_bytes());
+    info[508..512].copy_from_slice(&0xAA55_0000u32.to_le_bytes());
+    dev.write_sector(1, &info).unwrap();
+
+    let mut fat = vec![0u8; SECTOR];
+    for (i, v) in [0x0FFF_FFF8u32, 0x0FFF_FFFF, 0x0FFF_FFFF]
+        .iter()
+        .enumerate()
+    {
+        fat[i * 4..i * 4 + 4].copy_from_slice(&v.to_le_bytes());
+    }
+    dev.write_sector(8, &fat).unwrap();
+    dev.write_sector(9, &fat).unwrap();
+    dev
+}
+
+#[test]
+fn large_sectors_are_read_whole() {
+    let mut fs = Fat32::new(image_4k()).unwrap();
+    assert_eq!(fs.cluster_count(), 246);
+    assert_eq!(fs.free_cluster_count().unwrap(), 245);
+    assert!(fs.diff_fats().unwrap().is_empty());
+
+    // Entrée au-delà des 512 premiers octets du secteur de FAT
+    fs.write_fat_entry(200, 0x0FFF_FFFF).unwrap();
+    assert_eq!(fs.read_fat_entry(200).unwrap(), 0x0FFF_FFFF);
+    assert_eq!(fs.free_cluster_count().unwrap(), 244);
+
+    let mut fat1 = vec![0u8; 4096];
+    fs.device.read_sector(9, &mut fat1).unwrap();
+    fat1[800..804].fill(0);
+    fs.device.write_sector(9, &fat1).unwrap();
+    let diffs = fs.diff_fats().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].cluster, 200);
+
+    fs.repair_fats(0).unwrap();
+    assert!(fs.diff_fats().unwrap().is_empty());
+    assert_eq!(fs.read_fat_copy(1).unwrap()[200], 0x0FFF_FFFF);
+}
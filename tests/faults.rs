@@ -0,0 +1,241 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::faulty::{Fault, FaultyDevice, Trigger};
+use fat32_rust::{BlockDevice, Error, Fat32, ram::RamDisk};
+
+mod common;
+
+use common::WHEN;
+
+const KEEP: &[u8] = b"must survive every crash";
+
+/// Image de départ : un fichier témoin et deux répertoires.
+fn base_image() -> Vec<u8> {
+    let mut fs = common::mounted();
+    fs.create_file("/keep.txt", KEEP, WHEN).unwrap();
+    fs.create_dir_all("/src/sub", WHEN).unwrap();
+    fs.create_file("/src/Old name.txt", b"moved", WHEN).unwrap();
+    fs.create_dir("/dst", WHEN).unwrap();
+    fs.device.into_inner()
+}
+
+fn big_data() -> Vec<u8> {
+    (0..20000u32).map(|i| (i * 7 % 251) as u8).collect()
+}
+
+/// Position de l'écriture de l'opération "write", après la fin du fichier.
+const GAP_END: u64 = 700;
+
+type Op = fn(&mut Fat32<FaultyDevice<RamDisk>>) -> Result<(), Error>;
+type Verify = fn(&mut Fat32<RamDisk>);
+
+/// Opérations rejouées sous panne, avec leur vérification après réparation :
+/// l'opération est soit absente, soit complète.
+const OPS: [(&str, Op, Verify); 4] = [
+    (
+        "create",
+        |fs| fs.create_file("/new.txt", b"hello", WHEN).map(|_| ()),
+        |fs| match fs.read_file("/new.txt") {
+            Ok(data) => assert_eq!(data, b"hello"),
+            Err(e) => assert!(matches!(e, Error::NotFound)),
+        },
+    ),
+    (
+        "write",
+        |fs| {
+            // Écart comblé de zéros, chaîne agrandie sur plusieurs clusters,
+            // taille de l'entrée mise à jour
+            let mut file = fs.open_file("/src/Old name.txt")?;
+            file.set_position(GAP_END);
+            file.write(&big_data())?;
+            file.close()
+        },
+        |fs| {
+            let data = fs.read_file("/src/Old name.txt").unwrap();
+            if data != b"moved" {
+                assert_eq!(&data[..5], b"moved");
+                assert!(data[5..GAP_END as usize].iter().all(|&b| b == 0));
+                assert_eq!(&data[GAP_END as usize..], big_data());
+            }
+        },
+    ),
+    (
+        "rename",
+        |fs| {
+            fs.rename("/src/Old name.txt", "/dst/New name.txt")
+                .map(|_| ())
+        },
+        |fs| {
+            // Coupure entre l'ajout et l'effacement : deux entrées partagent
+            // la chaîne, fsck en tronque une mais l'autre reste intacte.
+            let copies = ["/src/Old name.txt", "/dst/New name.txt"];
+            assert!(
+                copies
+                    .iter()
+                    .any(|p| fs.read_file(p).is_ok_and(|d| d == b"moved")),
+                "file lost"
+            );
+        },
+    ),
+    (
+        "rename dir",
+        |fs| fs.rename("/src/sub", "/dst/sub").map(|_| ()),
+        |fs| {
+            assert!(
+                fs.resolve_path("/src/sub").is_ok() || fs.resolve_path("/dst/sub").is_ok(),
+                "directory lost"
+            );
+        },
+    ),
+];
+
+fn mount_faulty(image: &[u8]) -> Fat32<FaultyDevice<RamDisk>> {
+    let disk = RamDisk::from_bytes(image.to_vec(), 512);
+    Fat32::new(FaultyDevice::new(disk, 512)).unwrap()
+}
+
+/// Remonte l'image après la panne, répare, puis vérifie qu'elle est saine.
+fn recover(disk: RamDisk, name: &str, point: u64, fault: Fault, verify: Verify) {
+    let context = format!("{name}: {fault:?} at write {point}");
+    let mut fs = Fat32::new(disk).unwrap_or_else(|e| panic!("{context}: mount: {e:?}"));
+    fs.check(CheckMode::Repair)
+        .unwrap_or_else(|e| panic!("{context}: repair: {e:?}"));
+    let report = fs.check(CheckMode::ReportOnly).unwrap();
+    assert!(
+        report.is_clean(),
+        "{context}: still {:?} after repair",
+        report.problems
+    );
+    assert_eq!(fs.read_file("/keep.txt").unwrap(), KEEP, "{context}");
+    verify(&mut fs);
+}
+
+#[test]
+fn every_write_can_fail() {
+    let image = base_image();
+    let faults = [Fault::Fail, Fault::PowerCut, Fault::Tear { bytes: 100 }];
+
+    for (name, op, verify) in OPS {
+        // Passe à blanc : nombre d'écritures de l'opération
+        let mut fs = mount_faulty(&image);
+        let before = fs.device.writes;
+        op(&mut fs).unwrap();
+        let writes = fs.device.writes - before;
+        assert!(writes > 1, "{name}");
+
+        for point in 1..=writes {
+            for fault in faults {
+                let mut fs = mount_faulty(&image);
+                let base = fs.device.writes;
+                fs.device.inject(Trigger::NthWrite(base + point), fault);
+                assert!(op(&mut fs).is_err(), "{name}: {fault:?} at write {point}");
+                assert_eq!(fs.device.triggered, 1);
+                recover(fs.device.into_inner(), name, point, fault, verify);
+            }
+        }
+    }
+}
+
+#[test]
+fn power_cut_drops_unflushed_writes() {
+    let image = base_image();
+    let disk = RamDisk::from_bytes(image.clone(), 512);
+    let mut fs = Fat32::new(FaultyDevice::new(disk, 512).with_write_cache()).unwrap();
+
+    // Chaque opération se termine par un flush : rien ne reste en cache
+    fs.create_file("/flushed.txt", b"on disk", WHEN).unwrap();
+    assert_eq!(fs.device.pending(), 0);
+
+    let first = fs.resolve_path("/keep.txt").unwrap().first_cluster;
+    let lba = fs.cluster_to_lba(first) as u64;
+    fs.device.write_sector(lba, &[0u8; 512]).unwrap();
+    assert_eq!(fs.device.pending(), 1);
+    // Le cache est visible tant que l'alimentation tient
+    assert!(fs.read_file("/keep.txt").unwrap().iter().all(|&b| b == 0));
+
+    fs.device.power_cut();
+    assert!(matches!(fs.read_file("/keep.txt"), Err(Error::Io)));
+
+    let mut fs = Fat32::new(fs.device.into_inner()).unwrap();
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+    assert_eq!(fs.read_file("/flushed.txt").unwrap(), b"on disk");
+    assert_eq!(fs.read_file("/keep.txt").unwrap(), KEEP);
+}
+
+#[test]
+fn closed_files_survive_power_cut() {
+    let disk = RamDisk::from_bytes(base_image(), 512);
+    let mut fs = Fat32::new(FaultyDevice::new(disk, 512).with_write_cache()).unwrap();
+
+    let mut file = fs.open_file("/keep.txt").unwrap();
+    file.set_position(KEEP.len() as u64);
+    file.write(b", closed").unwrap();
+    file.close().unwrap();
+    assert_eq!(fs.device.pending(), 0);
+
+    // Sans `close`, la destruction du fichier vide aussi le cache
+    let mut file = fs.open_file("/keep.txt").unwrap();
+    file.set_position(KEEP.len() as u64 + 8);
+    file.write(b" twice").unwrap();
+    drop(file);
+    assert_eq!(fs.device.pending(), 0);
+
+    fs.device.power_cut();
+    let mut fs = Fat32::new(fs.device.into_inner()).unwrap();
+    let data = fs.read_file("/keep.txt").unwrap();
+    assert_eq!(&data[..KEEP.len()], KEEP);
+    assert_eq!(&data[KEEP.len()..], b", closed twice");
+}
+
+#[test]
+fn cached_operation_is_all_or_nothing() {
+    // Avec un cache vidé seulement en fin d'opération, une coupure à
+    // n'importe quelle écriture laisse l'image d'origine, saine sans fsck.
+    let image = base_image();
+    let mut fs = mount_faulty(&image);
+    let before = fs.device.writes;
+    fs.rename("/src/sub", "/dst/sub").unwrap();
+    let writes = fs.device.writes - before;
+
+    for point in 1..=writes {
+        let disk = RamDisk::from_bytes(image.clone(), 512);
+        let mut fs = Fat32::new(FaultyDevice::new(disk, 512).with_write_cache()).unwrap();
+        let base = fs.device.writes;
+        fs.device
+            .inject(Trigger::NthWrite(base + point), Fault::PowerCut);
+        assert!(fs.rename("/src/sub", "/dst/sub").is_err());
+
+        let disk = fs.device.into_inner();
+        assert_eq!(disk.as_bytes(), image, "write {point}");
+    }
+}
+
+#[test]
+fn lba_range_faults() {
+    let image = base_image();
+    let mut fs = mount_faulty(&image);
+    let entry = fs.resolve_path("/keep.txt").unwrap();
+    let lba = fs.cluster_to_lba(entry.first_cluster) as u64;
+
+    // Corruption silencieuse à la lecture : le support reste intact
+    fs.device.inject(
+        Trigger::ReadRange(lba..lba + 1),
+        Fault::Corrupt {
+            offset: 0,
+            xor: 0x20,
+        },
+    );
+    assert_eq!(
+        fs.read_file("/keep.txt").unwrap(),
+        b"Must survive every crash"
+    );
+    fs.device.clear_faults();
+    assert_eq!(fs.read_file("/keep.txt").unwrap(), KEEP);
+
+    // Écriture refusée sur une plage : les autres passent
+    fs.device.inject(Trigger::WriteRange(0..lba), Fault::Fail);
+    let mut sector = [0u8; 512];
+    assert!(matches!(fs.device.write_sector(1, &sector), Err(Error::Io)));
+    fs.device.read_sector(lba + 100, &mut sector).unwrap();
+    fs.device.write_sector(lba + 100, &[0xAB; 512]).unwrap();
+    assert_eq!(fs.device.triggered, 2);
+}
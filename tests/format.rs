@@ -0,0 +1,94 @@
+use fat32_rust::boot::FatType;
+use fat32_rust::format::{FormatOptions, format};
+use fat32_rust::{BlockDevice, Fat32, std_support::StdBlockDevice};
+
+mod common;
+
+#[test]
+fn formatted_image_mounts_with_empty_root() {
+    let path = common::blank_file("format_default", 32768);
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+    format(&mut dev, 32768, &FormatOptions::default()).unwrap();
+
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.bytes_per_sector(), 512);
+    assert_eq!(fs.boot.num_fats, 2);
+    assert_eq!(fs.boot.root_cluster, 2);
+    assert!(fs.list_root().unwrap().is_empty());
+    assert!(fs.read_fat_entry(2).unwrap() >= 0x0FFF_FFF8);
+    assert_eq!(fs.read_fat_entry(3).unwrap(), 0);
+}
+
+#[test]
+fn format_writes_backup_boot_sector_and_label() {
+    let path = common::blank_file("format_label", 65536);
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+    let opts = FormatOptions {
+        sectors_per_cluster: Some(4),
+        label: Some("firmware".into()),
+        volume_id: 0xCAFE_F00D,
+        ..FormatOptions::default()
+    };
+    format(&mut dev, 65536, &opts).unwrap();
+
+    let mut primary = [0u8; 512];
+    let mut backup = [0u8; 512];
+    dev.read_sector(0, &mut primary).unwrap();
+    dev.read_sector(6, &mut backup).unwrap();
+    assert_eq!(primary, backup);
+    assert_eq!(&primary[71..82], b"FIRMWARE   ");
+    assert_eq!(&primary[67..71], &0xCAFE_F00Du32.to_le_bytes());
+
+    // L'entrée de nom de volume n'apparaît pas comme un fichier.
+    let mut fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.sectors_per_cluster(), 4);
+    assert!(fs.list_root().unwrap().is_empty());
+
+    // Champs décodés du BPB
+    assert_eq!(&fs.boot.oem_name, b"MSWIN4.1");
+    assert_eq!(fs.boot.volume_id, 0xCAFE_F00D);
+    assert_eq!(&fs.boot.volume_label, b"FIRMWARE   ");
+    assert_eq!(&fs.boot.fs_type, b"FAT32   ");
+    assert_eq!(fs.boot.media, 0xF8);
+    assert_eq!(fs.boot.boot_signature, 0x29);
+    assert_eq!(fs.boot.data_start_sector(), fs.data_start_lba);
+    assert_eq!(fs.boot.cluster_count(), fs.cluster_count());
+    // Moins de 65525 clusters : FAT16 au sens de la spécification
+    assert_eq!(fs.boot.fat_type(), FatType::Fat16);
+}
+
+#[test]
+fn format_aligns_fats_and_data_region() {
+    let path = common::blank_file("format_align", 65536);
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+    let opts = FormatOptions {
+        reserved_sectors: 9,
+        num_fats: 1,
+        align: 2048,
+        ..FormatOptions::default()
+    };
+    format(&mut dev, 65536, &opts).unwrap();
+
+    let fs = Fat32::new(dev).unwrap();
+    assert_eq!(fs.boot.num_fats, 1);
+    assert_eq!(fs.fat_start_lba % 2048, 0);
+    assert_eq!(fs.data_start_lba % 2048, 0);
+}
+
+#[test]
+fn format_rejects_invalid_options() {
+    let path = common::blank_file("format_invalid", 32768);
+    let mut dev = StdBlockDevice::open(&path, 512).unwrap();
+
+    let bad_spc = FormatOptions {
+        sectors_per_cluster: Some(3),
+        ..FormatOptions::default()
+    };
+    assert!(format(&mut dev, 32768, &bad_spc).is_err());
+
+    let bad_label = FormatOptions {
+        label: Some("WAY TOO LONG LABEL".into()),
+        ..FormatOptions::default()
+    };
+    assert!(format(&mut dev, 32768, &bad_label).is_err());
+}
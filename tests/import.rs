@@ -0,0 +1,190 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::format::FormatOptions;
+use fat32_rust::host::{ImportOptions, build_image, import_tree, plan_import};
+use fat32_rust::{Error, Fat32, std_support::StdBlockDevice};
+use std::fs::FileTimes;
+use std::path::PathBuf;
+
+mod common;
+
+use common::WHEN;
+
+/// Arborescence : un nom long, un fichier de plusieurs clusters, un
+/// sous-répertoire assez peuplé pour dépasser un cluster d'entrées.
+fn sample_tree(name: &str) -> (PathBuf, Vec<u8>) {
+    let root = common::host_dir(name);
+    let big: Vec<u8> = (0..5000u32).map(|i| (i % 253) as u8).collect();
+    std::fs::write(root.join("A rather long file name.txt"), b"long").unwrap();
+    std::fs::write(root.join("BIG.BIN"), &big).unwrap();
+    std::fs::create_dir(root.join("many")).unwrap();
+    for i in 0..30 {
+        std::fs::write(root.join(format!("many/entry number {i}.dat")), [i as u8]).unwrap();
+    }
+    let file = std::fs::File::options()
+        .write(true)
+        .open(root.join("BIG.BIN"))
+        .unwrap();
+    file.set_times(FileTimes::new().set_modified(WHEN.to_system_time()))
+        .unwrap();
+    (root, big)
+}
+
+#[test]
+fn import_round_trip() {
+    let mut fs = common::mounted_file("import_tree", 32768);
+    let (host, big) = sample_tree("import_tree_src");
+
+    let plan = import_tree(&mut fs, &host, "/", &ImportOptions::default()).unwrap();
+    assert_eq!((plan.files, plan.dirs), (32, 1));
+    assert!(plan.clusters_needed <= plan.clusters_free);
+
+    assert_eq!(fs.read_file("/BIG.BIN").unwrap(), big);
+    assert_eq!(
+        fs.read_file("/a rather long file name.txt").unwrap(),
+        b"long"
+    );
+    assert_eq!(fs.read_file("/many/entry number 29.dat").unwrap(), [29]);
+
+    let names: Vec<String> = fs
+        .list_root()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(names, ["A rather long file name.txt", "BIG.BIN", "many"]);
+
+    let many = fs.resolve_path("/many").unwrap();
+    assert_eq!(many.short_name, "MANY");
+    let entry = fs.resolve_path("/BIG.BIN").unwrap();
+    assert_eq!(entry.modified, Some(WHEN));
+
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn import_into_subdirectory() {
+    let mut fs = common::mounted_file("import_sub", 32768);
+    let (host, _) = sample_tree("import_sub_src");
+    fs.create_dir("/dest", WHEN).unwrap();
+
+    import_tree(&mut fs, &host, "/dest", &ImportOptions::default()).unwrap();
+    assert_eq!(fs.read_file("/dest/many/entry number 0.dat").unwrap(), [0]);
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+
+    // Un second import entre en conflit avec le premier
+    assert!(matches!(
+        import_tree(&mut fs, &host, "/dest", &ImportOptions::default()),
+        Err(Error::AlreadyExists)
+    ));
+}
+
+#[test]
+fn import_without_space_leaves_image_untouched() {
+    let path = common::image_file("import_nospace", 2048);
+    let mut fs = Fat32::new(StdBlockDevice::open(&path, 512).unwrap()).unwrap();
+    let host = common::host_dir("import_nospace_src");
+    std::fs::write(host.join("small.txt"), b"small").unwrap();
+    std::fs::write(host.join("huge.bin"), vec![0xAA; 2 * 1024 * 1024]).unwrap();
+
+    let before = std::fs::read(&path).unwrap();
+    let plan = plan_import(&mut fs, &host, "/").unwrap();
+    assert!(plan.clusters_needed > plan.clusters_free);
+    assert!(matches!(
+        import_tree(&mut fs, &host, "/", &ImportOptions::default()),
+        Err(Error::NoSpace)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn case_conflicts_are_rejected_before_writing() {
+    let path = common::image_file("import_case", 32768);
+    let mut fs = Fat32::new(StdBlockDevice::open(&path, 512).unwrap()).unwrap();
+    let host = common::host_dir("import_case_src");
+    for name in ["B", "a", "b"] {
+        std::fs::write(host.join(name), name).unwrap();
+    }
+
+    let before = std::fs::read(&path).unwrap();
+    assert!(matches!(
+        import_tree(&mut fs, &host, "/", &ImportOptions::default()),
+        Err(Error::AlreadyExists)
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_are_skipped() {
+    let mut fs = common::mounted_file("import_symlink", 32768);
+    let host = common::host_dir("import_symlink_src");
+    std::fs::create_dir(host.join("sub")).unwrap();
+    std::fs::write(host.join("sub/file.txt"), b"data").unwrap();
+    // Boucle vers le parent et homonyme (à la casse près) d'un fichier
+    std::os::unix::fs::symlink("..", host.join("sub/loop")).unwrap();
+    std::os::unix::fs::symlink("file.txt", host.join("sub/FILE.TXT")).unwrap();
+
+    let plan = import_tree(&mut fs, &host, "/", &ImportOptions::default()).unwrap();
+    assert_eq!((plan.files, plan.dirs), (1, 1));
+    let sub = fs.resolve_path("/sub").unwrap().first_cluster;
+    let names: Vec<String> = fs
+        .read_dir_cluster(sub)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(names, [".", "..", "file.txt"]);
+}
+
+#[test]
+fn build_image_is_reproducible() {
+    let (host, big) = sample_tree("build_image_src");
+    let out = |n: u32| common::tmp_path(&format!("build_image_{n}.img"));
+    let format_opts = FormatOptions {
+        label: Some("FIRMWARE".into()),
+        volume_id: 0x1234_5678,
+        ..FormatOptions::default()
+    };
+    let import_opts = ImportOptions {
+        fixed_time: Some(WHEN),
+    };
+
+    build_image(&out(1), 65536, &host, &format_opts, &import_opts).unwrap();
+    // Une date de modification différente ne doit pas changer le résultat
+    std::fs::write(host.join("many/entry number 3.dat"), [3]).unwrap();
+    build_image(&out(2), 65536, &host, &format_opts, &import_opts).unwrap();
+    assert!(std::fs::read(out(1)).unwrap() == std::fs::read(out(2)).unwrap());
+
+    let mut fs = Fat32::new(StdBlockDevice::open(&out(1), 512).unwrap()).unwrap();
+    let info = fs.read_fsinfo().unwrap();
+    assert_eq!(info.free_count, fs.free_cluster_count().unwrap());
+    assert_eq!(fs.read_file("/BIG.BIN").unwrap(), big);
+    assert_eq!(fs.resolve_path("/many").unwrap().modified, Some(WHEN));
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+
+    // Échec : pas d'image partielle laissée derrière
+    build_image(&out(3), 2048, &host, &format_opts, &import_opts).unwrap();
+    std::fs::write(host.join("huge.bin"), vec![0; 2 * 1024 * 1024]).unwrap();
+    assert!(matches!(
+        build_image(&out(3), 2048, &host, &format_opts, &import_opts),
+        Err(Error::NoSpace)
+    ));
+    assert!(!std::path::Path::new(&out(3)).exists());
+}
+
+#[test]
+fn built_image_is_clean() {
+    let (host, _) = sample_tree("build_clean_src");
+    let out = common::tmp_path("build_clean.img");
+    build_image(
+        &out,
+        32768,
+        &host,
+        &FormatOptions::default(),
+        &ImportOptions::default(),
+    )
+    .unwrap();
+
+    let fs = Fat32::new(StdBlockDevice::open(&out, 512).unwrap()).unwrap();
+    assert!(!fs.was_dirty);
+}
@@ -0,0 +1,65 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::overlay::{FileDelta, OverlayDevice};
+use fat32_rust::{Error, Fat32, ram::RamDisk};
+
+mod common;
+
+use common::WHEN;
+
+fn image() -> Vec<u8> {
+    let mut fs = common::mounted();
+    fs.create_file("/base.txt", b"original", WHEN).unwrap();
+    fs.device.into_inner()
+}
+
+#[test]
+fn writes_stay_in_delta() {
+    let original = image();
+    let base = RamDisk::from_bytes(original.clone(), 512);
+    let mut fs = Fat32::new(OverlayDevice::new(base, 512)).unwrap();
+
+    fs.create_file("/new.txt", b"overlay", WHEN).unwrap();
+    fs.remove_file("/base.txt").unwrap();
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+    assert_eq!(fs.device.base.as_bytes(), original);
+
+    let exported = fs.device.export().unwrap();
+    assert_eq!(exported.len(), fs.device.changed_sectors());
+    assert!(exported.windows(2).all(|w| w[0].0 < w[1].0));
+
+    fs.device.discard_delta().unwrap();
+    let mut fs = Fat32::new(fs.device).unwrap();
+    assert!(matches!(fs.read_file("/new.txt"), Err(Error::NotFound)));
+    assert_eq!(fs.read_file("/base.txt").unwrap(), b"original");
+
+    fs.create_dir("/kept", WHEN).unwrap();
+    let written = fs.device.commit().unwrap();
+    assert!(written > 0);
+    assert_eq!(fs.device.changed_sectors(), 0);
+    let mut fs = Fat32::new(fs.device.base).unwrap();
+    assert!(fs.resolve_path("/kept").unwrap().is_dir);
+}
+
+#[test]
+fn file_delta_survives_reopen() {
+    let original = image();
+    let path = common::tmp_path("overlay.delta");
+    let _ = std::fs::remove_file(&path);
+
+    let base = RamDisk::from_bytes(original.clone(), 512);
+    let delta = FileDelta::open(&path, 512).unwrap();
+    let mut fs = Fat32::new(OverlayDevice::with_delta(base, delta, 512)).unwrap();
+    fs.create_file("/a.bin", &[5u8; 3000], WHEN).unwrap();
+    fs.create_file("/b.bin", b"b", WHEN).unwrap();
+    let changed = fs.device.changed_sectors();
+    drop(fs);
+
+    let base = RamDisk::from_bytes(original, 512);
+    let delta = FileDelta::open(&path, 512).unwrap();
+    let mut fs = Fat32::new(OverlayDevice::with_delta(base, delta, 512)).unwrap();
+    assert_eq!(fs.device.changed_sectors(), changed);
+    assert_eq!(fs.read_file("/a.bin").unwrap(), [5u8; 3000]);
+    assert_eq!(fs.read_file("/b.bin").unwrap(), b"b");
+
+    assert!(FileDelta::open(&path, 4096).is_err());
+}
@@ -0,0 +1,46 @@
+use fat32_rust::format::{FormatOptions, format};
+use fat32_rust::ram::RamDisk;
+use fat32_rust::{BlockDevice, Error, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+#[test]
+fn image_round_trips_through_bytes() {
+    let mut dev = RamDisk::new(16384, 512);
+    format(&mut dev, 16384, &FormatOptions::default()).unwrap();
+    let mut fs = Fat32::new(dev).unwrap();
+    fs.create_file("/data.bin", &[0x5A; 3000], WHEN).unwrap();
+
+    let bytes = fs.device.into_inner();
+    assert_eq!(bytes.len(), 16384 * 512);
+
+    let mut fs = Fat32::new(RamDisk::from_bytes(bytes, 512)).unwrap();
+    assert_eq!(fs.read_file("/data.bin").unwrap(), vec![0x5A; 3000]);
+}
+
+#[test]
+fn borrowed_buffer_is_written_in_place() {
+    let mut storage = vec![0u8; 16384 * 512];
+    {
+        let mut dev = RamDisk::from_bytes(&mut storage[..], 512);
+        format(&mut dev, 16384, &FormatOptions::default()).unwrap();
+        let mut fs = Fat32::new(dev).unwrap();
+        fs.create_dir("/logs", WHEN).unwrap();
+    }
+    assert_eq!(&storage[510..512], &[0x55, 0xAA]);
+
+    let mut fs = Fat32::new(RamDisk::from_bytes(&mut storage[..], 512)).unwrap();
+    assert!(fs.resolve_path("/logs").unwrap().is_dir);
+}
+
+#[test]
+fn out_of_range_access_fails() {
+    let mut dev = RamDisk::new(4, 512);
+    let mut buf = [0u8; 512];
+    assert_eq!(dev.sector_count(), 4);
+    assert!(dev.read_sector(3, &mut buf).is_ok());
+    assert!(matches!(dev.read_sector(4, &mut buf), Err(Error::Io)));
+    assert!(matches!(dev.write_sector(u64::MAX, &buf), Err(Error::Io)));
+}
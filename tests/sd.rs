@@ -0,0 +1,74 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::ram::RamDisk;
+use fat32_rust::sd::{CardKind, SdCard, command_frame, crc16};
+use fat32_rust::sd_sim::SdCardSim;
+use fat32_rust::{BlockDevice, Error, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+fn card_image() -> Vec<u8> {
+    let mut fs = common::mounted();
+    fs.create_file("/boot.cfg", b"baud=115200\n", WHEN).unwrap();
+    fs.unmount().unwrap().as_bytes().to_vec()
+}
+
+#[test]
+fn crc_reference_values() {
+    // Trames publiées dans la spécification SD
+    assert_eq!(command_frame(0, 0), [0x40, 0, 0, 0, 0, 0x95]);
+    assert_eq!(command_frame(8, 0x1AA)[5], 0x87);
+    assert_eq!(command_frame(17, 0)[5], 0x55);
+    assert_eq!(crc16(&[0xFF; 512]), 0x7FA1);
+}
+
+#[test]
+fn filesystem_on_every_card_kind() {
+    for kind in [CardKind::HighCapacity, CardKind::V2, CardKind::V1] {
+        let card = SdCard::init(SdCardSim::new(card_image(), kind)).unwrap();
+        assert_eq!(card.kind, kind);
+        let expected: &[u8] = match kind {
+            CardKind::V1 => &[0, 8, 59, 55, 41, 55, 41, 55, 41, 55, 41, 16],
+            _ => &[0, 8, 59, 55, 41, 55, 41, 55, 41, 55, 41, 58],
+        };
+        assert!(card.spi.commands.starts_with(expected), "{kind:?}");
+
+        let mut fs = Fat32::new(card).unwrap();
+        assert_eq!(fs.read_file("/boot.cfg").unwrap(), b"baud=115200\n");
+        fs.create_file("/data.bin", &[0x5A; 9000], WHEN).unwrap();
+        let card = fs.unmount().unwrap();
+
+        // Relu depuis l'image de la carte, sans le pilote
+        let mut fs = Fat32::new(RamDisk::from_bytes(card.spi.data, 512)).unwrap();
+        assert_eq!(fs.read_file("/data.bin").unwrap(), [0x5A; 9000]);
+        assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+    }
+}
+
+#[test]
+fn protocol_errors_are_reported() {
+    let mut sim = SdCardSim::new(card_image(), CardKind::HighCapacity);
+    sim.absent = true;
+    assert!(matches!(SdCard::init(sim), Err(Error::Io)));
+
+    let mut card = SdCard::init(SdCardSim::new(card_image(), CardKind::V2)).unwrap();
+    let mut buf = [0u8; 512];
+    card.spi.corrupt_next_read = true;
+    assert!(matches!(card.read_sector(0, &mut buf), Err(Error::Io)));
+    card.read_sector(0, &mut buf).unwrap();
+    assert_eq!(&buf[510..], &[0x55, 0xAA]);
+
+    // Hors de la carte : jeton d'erreur à la place des données
+    assert!(matches!(card.read_sector(32768, &mut buf), Err(Error::Io)));
+    assert!(matches!(card.write_sector(40000, &buf), Err(Error::Io)));
+    assert!(matches!(
+        card.read_sector(0, &mut [0u8; 100]),
+        Err(Error::Io)
+    ));
+
+    // Plusieurs blocs d'un coup
+    let mut two = [0u8; 1024];
+    card.read_sector(0, &mut two).unwrap();
+    assert_eq!(&two[..512], &buf);
+}
@@ -0,0 +1,76 @@
+use fat32_rust::device::ReadOnlyDevice;
+use fat32_rust::faulty::FaultyDevice;
+use fat32_rust::ram::RamDisk;
+use fat32_rust::trace::{Region, TraceDevice};
+use fat32_rust::{BlockDevice, Fat32};
+
+mod common;
+
+use common::WHEN;
+
+fn traced(cache: usize) -> Fat32<TraceDevice<RamDisk>> {
+    let dev = common::formatted();
+    let dev = TraceDevice::new(dev, 512).with_log().with_cache(cache);
+    let mut fs = Fat32::new(dev).unwrap();
+    fs.create_dir("/dir", WHEN).unwrap();
+    fs.create_file("/dir/file.bin", &[1u8; 5000], WHEN).unwrap();
+    fs.device.layout = Some(fs.layout().unwrap());
+    fs.device.reset();
+    fs
+}
+
+#[test]
+fn counts_and_tags_accesses() {
+    let mut fs = traced(0);
+    assert_eq!(fs.stats().unwrap().reads, 0);
+    assert!(fs.device.log().is_empty());
+
+    assert_eq!(fs.read_file("/dir/file.bin").unwrap().len(), 5000);
+    let stats = fs.stats().unwrap();
+    assert_eq!(stats.writes, 0);
+    assert_eq!(stats.reads as usize, fs.device.log().len());
+    assert_eq!(stats.bytes_read, stats.reads * 512);
+    assert_eq!(stats.cache_hits, 0);
+
+    let regions: Vec<Region> = fs.device.log().iter().map(|a| a.region).collect();
+    for region in [Region::Fat, Region::Dir, Region::Data] {
+        assert!(regions.contains(&region), "{region:?} missing");
+    }
+    assert!(!regions.contains(&Region::Unknown));
+
+    fs.create_file("/x", b"x", WHEN).unwrap();
+    let log = fs.device.log();
+    assert!(log.iter().any(|a| a.write && a.region == Region::Boot)); // FSInfo
+    assert!(log.iter().any(|a| a.write && a.region == Region::Fat));
+    let stats = fs.stats().unwrap();
+    assert_eq!(stats.bytes_written, stats.writes * 512);
+    assert_eq!(stats.flushes, 1);
+
+    // Sans TraceDevice, pas de compteurs
+    let dev = fs.device.inner;
+    let copy = RamDisk::from_bytes(dev.as_bytes().to_vec(), 512);
+    assert!(Fat32::new(copy).unwrap().stats().is_none());
+
+    // Trace sous d'autres périphériques : compteurs toujours visibles
+    let traced = TraceDevice::new(dev, 512);
+    let mut fs = Fat32::new(ReadOnlyDevice::new(FaultyDevice::new(traced, 512))).unwrap();
+    fs.read_file("/x").unwrap();
+    assert!(fs.stats().unwrap().reads > 0);
+}
+
+#[test]
+fn read_cache_hits() {
+    let mut fs = traced(64);
+    fs.read_file("/dir/file.bin").unwrap();
+    let first = fs.stats().unwrap();
+    fs.read_file("/dir/file.bin").unwrap();
+    let second = fs.stats().unwrap();
+    assert!(second.cache_hits > first.cache_hits);
+    assert!(fs.device.log().iter().any(|a| a.cached));
+
+    // Une écriture invalide le secteur en cache
+    let first = fs.resolve_path("/dir/file.bin").unwrap().first_cluster;
+    let lba = fs.cluster_to_lba(first) as u64;
+    fs.device.write_sector(lba, &[9u8; 512]).unwrap();
+    assert_eq!(&fs.read_file("/dir/file.bin").unwrap()[..512], &[9u8; 512]);
+}
@@ -0,0 +1,80 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::{BlockDevice, Error, Fat32, ram::RamDisk};
+
+mod common;
+
+const EOC: u32 = 0x0FFF_FFFF;
+
+/// Image formatée contenant HELLO.TXT (1500 octets, clusters 3..=5,
+/// précédé d'une entrée LFN "hello.txt") puis supprimée comme le ferait un OS.
+fn image_with_deleted_file() -> Fat32<RamDisk> {
+    let mut fs = common::mounted();
+
+    let data: Vec<u8> = (0..1500u32).map(|i| (i % 251) as u8).collect();
+    for (i, chunk) in data.chunks(512).enumerate() {
+        let mut sector = [0u8; 512];
+        sector[..chunk.len()].copy_from_slice(chunk);
+        let lba = fs.cluster_to_lba(3 + i as u32) as u64;
+        fs.device.write_sector(lba, &sector).unwrap();
+    }
+
+    let root = fs.cluster_to_lba(2) as u64;
+    let mut dir = [0u8; 512];
+    // LFN n°1 supprimée : "hello.txt"
+    let lfn = &mut dir[0..32];
+    lfn[0] = 0xE5;
+    lfn[11] = 0x0F;
+    for (i, c) in "hello".encode_utf16().enumerate() {
+        lfn[1 + i * 2..3 + i * 2].copy_from_slice(&c.to_le_bytes());
+    }
+    // Entrée 8.3 supprimée
+    let e = &mut dir[32..64];
+    e[0..11].copy_from_slice(b"\xE5ELLO   TXT");
+    e[11] = 0x20;
+    e[26..28].copy_from_slice(&3u16.to_le_bytes());
+    e[28..32].copy_from_slice(&1500u32.to_le_bytes());
+    fs.device.write_sector(root, &dir).unwrap();
+
+    fs
+}
+
+#[test]
+fn deleted_entries_are_listed_with_guessed_name() {
+    let mut fs = image_with_deleted_file();
+    assert!(fs.list_root().unwrap().is_empty());
+
+    let deleted = fs.list_deleted("/").unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].name, "HELLO.TXT");
+    assert!(deleted[0].first_char_from_lfn);
+    assert_eq!(deleted[0].size, 1500);
+    assert_eq!(deleted[0].first_cluster, 3);
+    assert!(deleted[0].recoverable);
+}
+
+#[test]
+fn recover_rebuilds_contiguous_chain() {
+    let mut fs = image_with_deleted_file();
+    let deleted = fs.list_deleted("/").unwrap();
+    fs.recover(&deleted[0], "HELLO.TXT").unwrap();
+
+    let data = fs.read_file("/HELLO.TXT").unwrap();
+    assert_eq!(data.len(), 1500);
+    assert!(data.iter().enumerate().all(|(i, &b)| b == (i % 251) as u8));
+    assert_eq!(fs.read_fat_entry(5).unwrap(), EOC);
+    assert!(fs.list_deleted("/").unwrap().is_empty());
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn recover_refuses_reused_clusters() {
+    let mut fs = image_with_deleted_file();
+    fs.write_fat_entry(4, EOC).unwrap();
+
+    let deleted = fs.list_deleted("/").unwrap();
+    assert!(!deleted[0].recoverable);
+    assert!(matches!(
+        fs.recover(&deleted[0], "HELLO.TXT"),
+        Err(Error::NotRecoverable)
+    ));
+}
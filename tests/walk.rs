@@ -0,0 +1,92 @@
+use fat32_rust::{BlockDevice, Fat32, ram::RamDisk};
+
+mod common;
+
+const EOC: u32 = 0x0FFF_FFFF;
+
+/// Écrit une entrée 8.3 brute dans le slot `index` d'un répertoire.
+fn put_entry(
+    fs: &mut Fat32<RamDisk>,
+    dir: u32,
+    index: usize,
+    name: &[u8; 11],
+    attr: u8,
+    cluster: u32,
+) {
+    let lba = fs.cluster_to_lba(dir) as u64;
+    let mut buf = [0u8; 512];
+    fs.device.read_sector(lba, &mut buf).unwrap();
+    let e = &mut buf[index * 32..index * 32 + 32];
+    e[0..11].copy_from_slice(name);
+    e[11] = attr;
+    e[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+    fs.device.write_sector(lba, &buf).unwrap();
+}
+
+/// /README.TXT, /A/B/FILE.TXT et /A/B/LOOP (qui repointe vers /A).
+fn sample_tree() -> Fat32<RamDisk> {
+    let mut fs = common::mounted();
+
+    for cluster in [3, 4] {
+        fs.write_fat_entry(cluster, EOC).unwrap();
+        let lba = fs.cluster_to_lba(cluster) as u64;
+        fs.device.write_sector(lba, &[0u8; 512]).unwrap();
+    }
+
+    put_entry(&mut fs, 2, 0, b"README  TXT", 0x20, 0);
+    put_entry(&mut fs, 2, 1, b"A          ", 0x10, 3);
+    put_entry(&mut fs, 3, 0, b".          ", 0x10, 3);
+    put_entry(&mut fs, 3, 1, b"..         ", 0x10, 0);
+    put_entry(&mut fs, 3, 2, b"B          ", 0x10, 4);
+    put_entry(&mut fs, 4, 0, b".          ", 0x10, 4);
+    put_entry(&mut fs, 4, 1, b"..         ", 0x10, 3);
+    put_entry(&mut fs, 4, 2, b"FILE    TXT", 0x20, 0);
+    put_entry(&mut fs, 4, 3, b"LOOP       ", 0x10, 3);
+    fs
+}
+
+fn paths(fs: &mut Fat32<RamDisk>, root: &str, max_depth: Option<usize>) -> Vec<String> {
+    let mut walk = fs.walk(root).unwrap();
+    if let Some(depth) = max_depth {
+        walk = walk.max_depth(depth);
+    }
+    walk.map(|r| r.unwrap().0).collect()
+}
+
+#[test]
+fn walk_is_depth_first_and_stops_on_cycles() {
+    let mut fs = sample_tree();
+    assert_eq!(
+        paths(&mut fs, "/", None),
+        ["/README.TXT", "/A", "/A/B", "/A/B/FILE.TXT", "/A/B/LOOP"]
+    );
+}
+
+#[test]
+fn walk_honors_max_depth_and_start_path() {
+    let mut fs = sample_tree();
+    assert!(paths(&mut fs, "/", Some(0)).is_empty());
+    assert_eq!(paths(&mut fs, "/", Some(1)), ["/README.TXT", "/A"]);
+    assert_eq!(
+        paths(&mut fs, "/A", Some(2)),
+        ["/A/B", "/A/B/FILE.TXT", "/A/B/LOOP"]
+    );
+
+    fs.change_dir("/A").unwrap();
+    assert_eq!(paths(&mut fs, "B", None), ["B/FILE.TXT", "B/LOOP"]);
+}
+
+#[test]
+fn walk_prune_skips_subtree() {
+    let mut fs = sample_tree();
+    let mut walk = fs.walk("/").unwrap();
+    let mut seen = Vec::new();
+    while let Some(item) = walk.next() {
+        let (path, entry) = item.unwrap();
+        if entry.is_dir && path == "/A/B" {
+            walk.prune();
+        }
+        seen.push(path);
+    }
+    assert_eq!(seen, ["/README.TXT", "/A", "/A/B"]);
+}
@@ -0,0 +1,132 @@
+use fat32_rust::check::CheckMode;
+use fat32_rust::time::DateTime;
+use fat32_rust::{Error, Fat32, MountOptions};
+
+mod common;
+
+use common::WHEN;
+
+#[test]
+fn remove_frees_clusters() {
+    let mut fs = common::mounted();
+    let free = fs.free_cluster_count().unwrap();
+
+    fs.create_dir_all("/a/b/c", WHEN).unwrap();
+    fs.create_file("/a/b/Long file name.bin", &[7u8; 10000], WHEN)
+        .unwrap();
+    fs.create_file("/a/x", b"x", WHEN).unwrap();
+    assert!(fs.free_cluster_count().unwrap() < free);
+
+    assert!(matches!(fs.remove_dir("/a/b"), Err(Error::NotEmpty)));
+    assert!(matches!(fs.remove_file("/a/b"), Err(Error::IsDirectory)));
+    fs.remove_file("/a/x").unwrap();
+    assert!(matches!(fs.resolve_path("/a/x"), Err(Error::NotFound)));
+
+    fs.remove_dir_all("/a").unwrap();
+    assert_eq!(fs.free_cluster_count().unwrap(), free);
+    assert!(fs.list_root().unwrap().is_empty());
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn rename_moves_entries() {
+    let mut fs = common::mounted();
+    fs.create_dir_all("/src/sub", WHEN).unwrap();
+    fs.create_dir("/dst", WHEN).unwrap();
+    fs.create_file("/src/sub/data.txt", b"payload", WHEN)
+        .unwrap();
+
+    // Changement de casse seul, dans le même répertoire
+    fs.rename("/src/sub/data.txt", "/src/sub/Data.TXT").unwrap();
+    assert_eq!(
+        fs.resolve_path("/src/sub/data.txt").unwrap().name,
+        "Data.TXT"
+    );
+
+    // Déplacement d'un répertoire : `..` pointe vers le nouveau parent
+    fs.rename("/src/sub", "/dst/moved").unwrap();
+    assert_eq!(fs.read_file("/dst/moved/Data.TXT").unwrap(), b"payload");
+    assert!(matches!(fs.resolve_path("/src/sub"), Err(Error::NotFound)));
+    fs.change_dir("/dst/moved").unwrap();
+    fs.change_dir("..").unwrap();
+    assert!(fs.list_cwd().unwrap().iter().any(|e| e.name == "moved"));
+
+    assert!(matches!(
+        fs.rename("/dst", "/dst/moved/loop"),
+        Err(Error::InvalidName)
+    ));
+    assert!(matches!(
+        fs.rename("/src", "/dst/moved"),
+        Err(Error::AlreadyExists)
+    ));
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn set_modified_updates_entry() {
+    let mut fs = common::mounted();
+    fs.create_file("/f", b"", WHEN).unwrap();
+    let later = DateTime {
+        year: 2030,
+        second: 20,
+        ..WHEN
+    };
+    fs.set_modified("/f", later).unwrap();
+
+    let entry = fs.resolve_path("/f").unwrap();
+    assert_eq!(entry.modified, Some(later));
+    assert_eq!(entry.created, Some(WHEN));
+}
+
+#[test]
+fn discard_on_free() {
+    let dev = common::formatted();
+    let options = MountOptions {
+        discard: true,
+        ..MountOptions::default()
+    };
+    let mut fs = Fat32::mount(dev, options).unwrap();
+
+    fs.create_file("/big.bin", &[0x5A; 9000], WHEN).unwrap();
+    let first = fs.resolve_path("/big.bin").unwrap().first_cluster;
+    let start = fs.cluster_to_lba(first) as usize * 512;
+    fs.remove_file("/big.bin").unwrap();
+
+    // Les clusters libérés ont été signalés au disque (remis à zéro)
+    let bytes = fs.device.as_bytes();
+    assert!(bytes[start..start + 9000].iter().all(|&b| b == 0));
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+}
+
+#[test]
+fn file_write_extends_and_fills_gaps() {
+    let mut fs = common::mounted();
+    fs.create_file("/empty", b"", WHEN).unwrap();
+    fs.create_file("/log.txt", b"abc", WHEN).unwrap();
+
+    let mut file = fs.open_file("/empty").unwrap();
+    assert_eq!(file.write(&[5u8; 9000]).unwrap(), 9000);
+    file.set_position(10000);
+    file.write(b"end").unwrap();
+    file.set_position(1);
+    file.write(b"XY").unwrap();
+    assert_eq!(file.size(), 10003);
+    file.close().unwrap();
+
+    let data = fs.read_file("/empty").unwrap();
+    assert_eq!(&data[..4], &[5, b'X', b'Y', 5]);
+    assert!(data[9000..10000].iter().all(|&b| b == 0));
+    assert_eq!(&data[10000..], b"end");
+
+    let mut file = fs.open_file("/log.txt").unwrap();
+    file.set_position(3);
+    file.write(b"def").unwrap();
+    drop(file);
+    assert_eq!(fs.read_file("/log.txt").unwrap(), b"abcdef");
+    assert!(fs.check(CheckMode::ReportOnly).unwrap().is_clean());
+
+    // Ouvert sans son entrée (via `open_entry`) : pas modifiable
+    let entry = fs.resolve_path("/log.txt").unwrap();
+    let mut file = fs.open_entry(&entry).unwrap();
+    assert!(matches!(file.write(b"!"), Err(Error::ReadOnly)));
+}